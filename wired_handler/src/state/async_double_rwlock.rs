@@ -6,7 +6,7 @@ use crate::{
 use std::{
     any::{Any, TypeId},
     ops::{Deref, DerefMut},
-    sync::Arc,
+    sync::{Arc, Weak},
 };
 
 use rustc_hash::FxHashMap;
@@ -15,10 +15,22 @@ use tokio::sync::{
 };
 
 type SyncedAny = Arc<RwLock<dyn Any + Send + Sync>>;
+type SyncedMap = RwLock<FxHashMap<TypeId, SyncedAny>>;
 
 /// `State` to be shared between async tasks. Utilizes `tokio`'s `RwLock` for concurrency
 #[derive(Debug, Clone, Default, State)]
-pub struct AsyncDoubleRwLockState(Arc<RwLock<FxHashMap<TypeId, SyncedAny>>>);
+pub struct AsyncDoubleRwLockState(Arc<SyncedMap>);
+
+/// Weak reference to an `AsyncDoubleRwLockState`, doesn't keep its data alive
+#[derive(Debug, Clone, Default)]
+pub struct WeakAsyncDoubleRwLockState(Weak<SyncedMap>);
+
+impl WeakAsyncDoubleRwLockState {
+    /// Returns the `AsyncDoubleRwLockState` if it still exists
+    pub fn upgrade(&self) -> Option<AsyncDoubleRwLockState> {
+        self.0.upgrade().map(AsyncDoubleRwLockState)
+    }
+}
 
 impl AsyncDoubleRwLockState {
     /// Creates a new `AsyncDoubleRwLockState`
@@ -26,6 +38,11 @@ impl AsyncDoubleRwLockState {
         Self::default()
     }
 
+    /// Creates a `WeakAsyncDoubleRwLockState` pointing to the same data
+    pub fn downgrade(&self) -> WeakAsyncDoubleRwLockState {
+        WeakAsyncDoubleRwLockState(Arc::downgrade(&self.0))
+    }
+
     async fn internal_get_mut<T: 'static + Send + Sync>(
         &self,
     ) -> Option<OwnedRwLockMappedWriteGuard<dyn Any + Send + Sync, T>> {
@@ -106,15 +123,17 @@ impl StateAsyncGetMutOrInsert for AsyncDoubleRwLockState {
             return current_data;
         }
 
-        let inserted_data = Arc::new(RwLock::new(get_data())) as Arc<RwLock<dyn Any + Send + Sync>>;
-        {
+        // another task could have inserted it in the meantime, `get_data` is only called if it hasn't
+        let data = {
             self.0
                 .write()
                 .await
-                .insert(TypeId::of::<T>(), inserted_data.clone());
-        }
+                .entry(TypeId::of::<T>())
+                .or_insert_with(|| Arc::new(RwLock::new(get_data())) as SyncedAny)
+                .clone()
+        };
 
-        OwnedRwLockWriteGuard::map(inserted_data.write_owned().await, |data| {
+        OwnedRwLockWriteGuard::map(data.write_owned().await, |data| {
             data.downcast_mut().unwrap() // wrong types cannot be inserted
         })
     }
@@ -1,5 +1,11 @@
 #![cfg(test)]
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+};
 
 use crate::{
     Context, ContextBuilder, GetState, Handler, State, StateAsyncGet, StateAsyncGetCloned,
@@ -9,7 +15,7 @@ use crate::{
 };
 use tokio::runtime::Runtime;
 
-use crate::async_double_rwlock::AsyncDoubleRwLockState;
+use crate::async_double_rwlock::{AsyncDoubleRwLockState, WeakAsyncDoubleRwLockState};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 struct SessionId(u32);
@@ -309,3 +315,65 @@ async fn test() {
         assert_eq!(request_value, Some(4u8));
     }
 }
+
+#[test]
+fn run_concurrent_insert_test() {
+    let runtime = Runtime::new().unwrap();
+
+    runtime.block_on(concurrent_insert_test());
+}
+
+async fn concurrent_insert_test() {
+    let state = AsyncDoubleRwLockState::new();
+    let inserted_count = Arc::new(AtomicUsize::new(0));
+    let barrier = Arc::new(tokio::sync::Barrier::new(16));
+
+    let tasks: Vec<_> = (0..16)
+        .map(|_| {
+            let state = state.clone();
+            let inserted_count = inserted_count.clone();
+            let barrier = barrier.clone();
+            tokio::spawn(async move {
+                barrier.wait().await;
+                *state
+                    .get_mut_or_insert_with(|| {
+                        inserted_count.fetch_add(1, Ordering::SeqCst);
+                        0u32
+                    })
+                    .await += 1;
+            })
+        })
+        .collect();
+    for task in tasks {
+        task.await.unwrap();
+    }
+
+    // only one task has inserted the data, no increment has been lost
+    assert_eq!(inserted_count.load(Ordering::SeqCst), 1);
+    assert_eq!(state.get_cloned::<u32>().await, Some(16));
+}
+
+#[test]
+fn run_weak_test() {
+    let runtime = Runtime::new().unwrap();
+
+    runtime.block_on(weak_test());
+}
+
+async fn weak_test() {
+    assert!(WeakAsyncDoubleRwLockState::default().upgrade().is_none());
+
+    let state = AsyncDoubleRwLockState::new();
+    state.insert(1u8).await;
+    let weak_state = state.downgrade();
+
+    {
+        let upgraded_state = weak_state.upgrade().unwrap();
+        assert_eq!(upgraded_state.get_cloned::<u8>().await, Some(1u8));
+        upgraded_state.insert(2u8).await;
+    }
+    assert_eq!(state.get_cloned::<u8>().await, Some(2u8));
+
+    drop(state);
+    assert!(weak_state.upgrade().is_none());
+}
@@ -0,0 +1,123 @@
+use http::{
+    Method, StatusCode,
+    header::{ALLOW, CONTENT_LENGTH},
+};
+use hyper::body::Body;
+
+use super::{
+    http_error::{HttpError, HttpErrorFromResponseExt},
+    response::Response,
+    response_body::ResponseBody,
+};
+use crate::{
+    prelude::*,
+    state::{context::HttpRequestContext, request_state::RequestState},
+};
+
+/// The methods which can be listed in the `Allow` header, in the order they are listed
+const KNOWN_METHODS: [Method; 9] = [
+    Method::GET,
+    Method::HEAD,
+    Method::POST,
+    Method::PUT,
+    Method::PATCH,
+    Method::DELETE,
+    Method::OPTIONS,
+    Method::TRACE,
+    Method::CONNECT,
+];
+
+/// The methods allowed by an `actions!` invocation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AllowedMethods(Vec<Method>);
+
+impl AllowedMethods {
+    /// Collects the known methods for which `is_listed` is `true`.
+    /// `HEAD` is added if `GET` is listed, `OPTIONS` is always added
+    pub fn new(is_listed: impl Fn(&Method) -> bool) -> Self {
+        Self(
+            KNOWN_METHODS
+                .into_iter()
+                .filter(|method| {
+                    is_listed(method)
+                        || *method == Method::OPTIONS
+                        || (*method == Method::HEAD && is_listed(&Method::GET))
+                })
+                .collect(),
+        )
+    }
+
+    pub fn contains(&self, method: &Method) -> bool {
+        self.0.contains(method)
+    }
+
+    /// Returns the value of the `Allow` header, like `GET, HEAD, OPTIONS`
+    pub fn to_header_value(&self) -> String {
+        self.0
+            .iter()
+            .map(Method::as_str)
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Creates the `Response` to an `OPTIONS` request
+    pub fn options_response(&self) -> Response {
+        Response::builder()
+            .header(ALLOW, self.to_header_value())
+            .body(ResponseBody::empty())
+            .expect("Response builder failed with valid methods")
+    }
+
+    /// Creates the error for a method which isn't allowed
+    pub fn method_not_allowed(&self) -> HttpError {
+        let response = Response::builder()
+            .status(StatusCode::METHOD_NOT_ALLOWED)
+            .header(ALLOW, self.to_header_value())
+            .body(ResponseBody::from_bytes("method not allowed"))
+            .expect("Response builder failed with valid methods");
+
+        #[allow(deprecated)]
+        HttpError::from_response(response)
+    }
+}
+
+/// Returns the method of the request, `GET` if there is none in the `RequestState`. Used by `actions!`
+// Different implementation needed because we can't produce a Request<Incoming>
+#[cfg(test)]
+#[doc(hidden)]
+pub fn request_method(ctx: &HttpRequestContext) -> Method {
+    RequestState::get_from_ctx(ctx)
+        .get::<Method>()
+        .cloned()
+        .unwrap_or(Method::GET)
+}
+
+/// Returns the method of the request. Used by `actions!`
+#[cfg(not(test))]
+#[doc(hidden)]
+pub fn request_method(ctx: &HttpRequestContext) -> Method {
+    use crate::prelude::ContextGetRequestExt;
+
+    ctx.request().method().clone()
+}
+
+/// For answering `HEAD` requests with the `Response` to a `GET` request
+pub trait ContextHeadResponseExt {
+    /// Removes the body of the inserted `Response`, keeping its length in the `Content-Length` header if it's known
+    fn strip_response_body(&mut self);
+}
+
+impl ContextHeadResponseExt for HttpRequestContext {
+    fn strip_response_body(&mut self) {
+        let Some(response) = RequestState::get_mut_from_ctx(self).get_mut::<Response>() else {
+            return;
+        };
+
+        let body = std::mem::replace(response.body_mut(), ResponseBody::empty());
+        if !response.headers().contains_key(CONTENT_LENGTH)
+            && let Some(len) = body.size_hint().exact()
+        {
+            response.headers_mut().insert(CONTENT_LENGTH, len.into());
+        }
+    }
+}
@@ -0,0 +1,32 @@
+use std::sync::Arc;
+
+use crate::{
+    data::config::BindConfig,
+    prelude::*,
+    state::{
+        context::{HttpRequestContext, SessionlessRequestContext},
+        request_state::RequestState,
+    },
+};
+
+/// For retrieving the `BindConfig` of the listener a request has been received on
+pub trait ContextBindConfigExt {
+    /// Returns the `BindConfig` of the listener the request has been received on
+    fn bind_config(&self) -> &BindConfig;
+}
+
+impl ContextBindConfigExt for HttpRequestContext {
+    fn bind_config(&self) -> &BindConfig {
+        RequestState::get_from_ctx(self)
+            .get::<Arc<BindConfig>>()
+            .expect("every HttpRequestContext must have a BindConfig")
+    }
+}
+
+impl ContextBindConfigExt for SessionlessRequestContext {
+    fn bind_config(&self) -> &BindConfig {
+        RequestState::get_from_ctx(self)
+            .get::<Arc<BindConfig>>()
+            .expect("every SessionlessRequestContext must have a BindConfig")
+    }
+}
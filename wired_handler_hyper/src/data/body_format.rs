@@ -0,0 +1,70 @@
+/// A serialization format of request and response bodies
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BodyFormat {
+    /// `application/json` and `+json` types
+    Json,
+    /// `application/x-www-form-urlencoded`
+    #[cfg(feature = "form")]
+    Form,
+    /// `application/msgpack`, `application/vnd.msgpack` and `application/x-msgpack`
+    #[cfg(feature = "msgpack")]
+    MessagePack,
+    /// `application/cbor` and `+cbor` types
+    #[cfg(feature = "cbor")]
+    Cbor,
+}
+
+impl BodyFormat {
+    /// All enabled formats, in order of preference
+    pub fn all() -> Vec<Self> {
+        vec![
+            Self::Json,
+            #[cfg(feature = "form")]
+            Self::Form,
+            #[cfg(feature = "msgpack")]
+            Self::MessagePack,
+            #[cfg(feature = "cbor")]
+            Self::Cbor,
+        ]
+    }
+
+    /// Returns the format of a `Content-Type`, ignoring its parameters like `charset`.
+    /// `None` if the format is unknown or not enabled
+    pub fn from_content_type(content_type: &str) -> Option<Self> {
+        let essence = content_type
+            .split(';')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase();
+
+        match essence.as_str() {
+            "application/json" => Some(Self::Json),
+            #[cfg(feature = "form")]
+            "application/x-www-form-urlencoded" => Some(Self::Form),
+            #[cfg(feature = "msgpack")]
+            "application/msgpack" | "application/vnd.msgpack" | "application/x-msgpack" => {
+                Some(Self::MessagePack)
+            }
+            #[cfg(feature = "cbor")]
+            "application/cbor" => Some(Self::Cbor),
+            essence if essence.ends_with("+json") => Some(Self::Json),
+            #[cfg(feature = "cbor")]
+            essence if essence.ends_with("+cbor") => Some(Self::Cbor),
+            _ => None,
+        }
+    }
+
+    /// The `Content-Type` of the format
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Json => "application/json",
+            #[cfg(feature = "form")]
+            Self::Form => "application/x-www-form-urlencoded",
+            #[cfg(feature = "msgpack")]
+            Self::MessagePack => "application/msgpack",
+            #[cfg(feature = "cbor")]
+            Self::Cbor => "application/cbor",
+        }
+    }
+}
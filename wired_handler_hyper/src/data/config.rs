@@ -1,20 +1,303 @@
+use std::{net::IpAddr, time::Duration};
+
 use serde::{Deserialize, Serialize};
 
-/// The bind config for the HTTP part
+/// The bind config for the HTTP part.
+/// Stored with every request instead of the `GlobalState`, get it with `ContextBindConfigExt::bind_config`
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct BindConfig {
+    /// Address to listen on. Either a TCP address like `127.0.0.1:8000`,
+    /// a Unix socket like `unix:/run/app.sock`, an inherited file descriptor like `fd:3`
+    /// or a socket passed by systemd socket activation (`LISTEN_FDS`) like `systemd` or `systemd:1`.
+    /// The environment variables of systemd are kept, remove them with `Command::env_remove`
+    /// when spawning child processes so they don't take the sockets as their own
     #[serde(default)]
     pub addr: String,
+    /// Permissions of the Unix socket file, like `0o660`. Uses the umask if `None`
+    #[serde(default)]
+    pub unix_socket_mode: Option<u32>,
+    /// Seconds to wait for open connections to finish after shutdown has been requested
+    #[serde(default = "default_shutdown_timeout_secs")]
+    pub shutdown_timeout_secs: u64,
+    /// IPs of proxies whose `Forwarded` and `X-Forwarded-For` headers are used to resolve the client IP
+    #[serde(default)]
+    pub trusted_proxies: Vec<IpAddr>,
+    /// Whether peers connected over a Unix socket are trusted like `trusted_proxies`.
+    /// Only enable it if every process able to connect to the socket is a proxy
+    #[serde(default)]
+    pub trust_unix_socket_peers: bool,
+    /// Seconds a client may take to send the headers of a request and to complete the TLS handshake, `None` to disable.
+    /// Also bounds the wait for the next request of an HTTP/1.1 connection.
+    /// Only applies to HTTP/1.1, HTTP/2 connections are only bounded by `keep_alive_timeout_secs`
+    #[serde(default = "default_header_read_timeout_secs")]
+    pub header_read_timeout_secs: Option<u64>,
+    /// Whether HTTP/1.1 connections are kept open for further requests
+    #[serde(default = "default_keep_alive")]
+    pub keep_alive: bool,
+    /// Seconds a connection may stay open without a request in progress before it's closed, `None` to disable
+    #[serde(default = "default_keep_alive_timeout_secs")]
+    pub keep_alive_timeout_secs: Option<u64>,
+    /// Maximum number of requests served on a single connection before it's closed, unlimited if `None`
+    #[serde(default)]
+    pub max_requests_per_connection: Option<usize>,
+    /// Maximum number of headers of an HTTP/1.1 request. Uses hyper's default of 100 if `None`
+    #[serde(default)]
+    pub max_headers: Option<usize>,
+    /// Maximum number of connections handled at the same time, unlimited if `None`
+    #[serde(default)]
+    pub max_connections: Option<usize>,
+    /// What happens to connections exceeding `max_connections`
+    #[serde(default)]
+    pub connection_limit_mode: ConnectionLimitMode,
+    /// Maximum number of concurrent streams of an HTTP/2 connection. Uses hyper's default of 200 if `None`
+    #[cfg(feature = "http2")]
+    #[serde(default)]
+    pub http2_max_concurrent_streams: Option<u32>,
+    /// Seconds between two HTTP/2 pings detecting dead connections, `None` to disable
+    #[cfg(feature = "http2")]
+    #[serde(default)]
+    pub http2_keep_alive_interval_secs: Option<u64>,
+    /// Serves HTTPS instead of HTTP if set
+    #[cfg(feature = "tls")]
+    #[serde(default)]
+    pub tls: Option<TlsConfig>,
+}
+
+fn default_shutdown_timeout_secs() -> u64 {
+    30
+}
+
+fn default_header_read_timeout_secs() -> Option<u64> {
+    Some(30)
+}
+
+fn default_keep_alive() -> bool {
+    true
+}
+
+fn default_keep_alive_timeout_secs() -> Option<u64> {
+    Some(60)
 }
 
 impl Default for BindConfig {
     fn default() -> Self {
         Self {
             addr: "127.0.0.1:8000".into(),
+            unix_socket_mode: None,
+            shutdown_timeout_secs: default_shutdown_timeout_secs(),
+            trusted_proxies: Vec::new(),
+            trust_unix_socket_peers: false,
+            header_read_timeout_secs: default_header_read_timeout_secs(),
+            keep_alive: default_keep_alive(),
+            keep_alive_timeout_secs: default_keep_alive_timeout_secs(),
+            max_requests_per_connection: None,
+            max_headers: None,
+            max_connections: None,
+            connection_limit_mode: ConnectionLimitMode::default(),
+            #[cfg(feature = "http2")]
+            http2_max_concurrent_streams: None,
+            #[cfg(feature = "http2")]
+            http2_keep_alive_interval_secs: None,
+            #[cfg(feature = "tls")]
+            tls: None,
         }
     }
 }
 
+impl BindConfig {
+    /// Time to wait for open connections to finish after shutdown has been requested
+    pub fn shutdown_timeout(&self) -> Duration {
+        Duration::from_secs(self.shutdown_timeout_secs)
+    }
+
+    /// Time a client may take to send the headers of a request and to complete the TLS handshake
+    pub fn header_read_timeout(&self) -> Option<Duration> {
+        self.header_read_timeout_secs.map(Duration::from_secs)
+    }
+
+    /// Time a connection may stay open without a request in progress
+    pub fn keep_alive_timeout(&self) -> Option<Duration> {
+        self.keep_alive_timeout_secs.map(Duration::from_secs)
+    }
+
+    /// Time between two HTTP/2 pings detecting dead connections
+    #[cfg(feature = "http2")]
+    pub fn http2_keep_alive_interval(&self) -> Option<Duration> {
+        self.http2_keep_alive_interval_secs.map(Duration::from_secs)
+    }
+}
+
+/// What happens to connections exceeding `BindConfig::max_connections`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum ConnectionLimitMode {
+    /// Stops accepting connections until a connection is closed, new connections wait in the listen backlog
+    #[default]
+    Queue,
+    /// Accepts and immediately closes new connections
+    Reject,
+}
+
+/// The TLS config for serving HTTPS
+#[cfg(feature = "tls")]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TlsConfig {
+    /// Path to the PEM encoded certificate chain
+    pub cert_path: std::path::PathBuf,
+    /// Path to the PEM encoded private key
+    pub key_path: std::path::PathBuf,
+    /// Seconds between two checks whether the certificate files have changed, `None` to disable reloading
+    #[serde(default = "default_reload_interval_secs")]
+    pub reload_interval_secs: Option<u64>,
+}
+
+#[cfg(feature = "tls")]
+fn default_reload_interval_secs() -> Option<u64> {
+    Some(60)
+}
+
+#[cfg(feature = "tls")]
+impl TlsConfig {
+    /// Creates a `TlsConfig` with the default reload interval
+    pub fn new(
+        cert_path: impl Into<std::path::PathBuf>,
+        key_path: impl Into<std::path::PathBuf>,
+    ) -> Self {
+        Self {
+            cert_path: cert_path.into(),
+            key_path: key_path.into(),
+            reload_interval_secs: default_reload_interval_secs(),
+        }
+    }
+
+    /// Time between two checks whether the certificate files have changed
+    pub fn reload_interval(&self) -> Option<Duration> {
+        self.reload_interval_secs
+            .map(|reload_interval_secs| Duration::from_secs(reload_interval_secs.max(1)))
+    }
+}
+
+/// The `SameSite` attribute of a cookie
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum SameSite {
+    Strict,
+    #[default]
+    Lax,
+    None,
+}
+
+impl From<SameSite> for cookie::SameSite {
+    fn from(value: SameSite) -> Self {
+        match value {
+            SameSite::Strict => Self::Strict,
+            SameSite::Lax => Self::Lax,
+            SameSite::None => Self::None,
+        }
+    }
+}
+
+/// The config for the session cookie
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct SessionConfig {
+    /// Name of the cookie holding the `SessionId`
+    pub cookie_name: String,
+    /// `Path` attribute of the session cookie
+    pub cookie_path: String,
+    /// `SameSite` attribute of the session cookie
+    pub same_site: SameSite,
+    /// Whether the session cookie is only sent over HTTPS
+    pub secure: bool,
+    /// Whether the session cookie is hidden from scripts
+    pub http_only: bool,
+    /// Seconds after the last access until a session expires, `None` to disable
+    pub idle_timeout_secs: Option<u64>,
+    /// Seconds after creation until a session expires, `None` to disable
+    pub max_lifetime_secs: Option<u64>,
+    /// Seconds between two runs of the task evicting expired sessions
+    pub sweep_interval_secs: u64,
+}
+
+impl SessionConfig {
+    /// Time after the last access until a session expires
+    pub fn idle_timeout(&self) -> Option<Duration> {
+        self.idle_timeout_secs.map(Duration::from_secs)
+    }
+
+    /// Time after creation until a session expires
+    pub fn max_lifetime(&self) -> Option<Duration> {
+        self.max_lifetime_secs.map(Duration::from_secs)
+    }
+
+    /// Time between two runs of the task evicting expired sessions
+    pub fn sweep_interval(&self) -> Duration {
+        Duration::from_secs(self.sweep_interval_secs.max(1))
+    }
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        Self {
+            cookie_name: "session_id".into(),
+            cookie_path: "/".into(),
+            same_site: SameSite::default(),
+            secure: true,
+            http_only: true,
+            idle_timeout_secs: Some(24 * 60 * 60),
+            max_lifetime_secs: None,
+            sweep_interval_secs: 60,
+        }
+    }
+}
+
+/// The config for request bodies
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct BodyConfig {
+    /// Maximum size of a request body or websocket message in bytes, `None` to disable.
+    /// Websocket connections are closed when receiving a larger message
+    pub max_size: Option<usize>,
+}
+
+impl Default for BodyConfig {
+    fn default() -> Self {
+        Self {
+            max_size: Some(2 * 1024 * 1024),
+        }
+    }
+}
+
+/// The config for `multipart/form-data` bodies
+#[cfg(feature = "multipart")]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct MultipartConfig {
+    /// Maximum size of a single field or file in bytes, `None` to disable
+    pub max_field_size: Option<usize>,
+    /// Maximum size of the whole body in bytes, `None` to disable
+    pub max_total_size: Option<usize>,
+    /// Directory of the temp files of uploads, the system's temp dir if `None`
+    pub temp_dir: Option<std::path::PathBuf>,
+}
+
+#[cfg(feature = "multipart")]
+impl Default for MultipartConfig {
+    fn default() -> Self {
+        Self {
+            max_field_size: Some(16 * 1024 * 1024),
+            max_total_size: Some(64 * 1024 * 1024),
+            temp_dir: None,
+        }
+    }
+}
+
+/// The config for generating URLs of named routes
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct RouterConfig {
+    /// Prefix of all generated URLs, like `/app` if a proxy serves the app under it
+    pub mount_prefix: String,
+}
+
 #[cfg(feature = "diesel")]
 /// The db config for the database part
 #[derive(Debug, Clone, Default, Deserialize, Serialize)]
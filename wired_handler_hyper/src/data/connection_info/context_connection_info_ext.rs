@@ -0,0 +1,38 @@
+use std::net::IpAddr;
+
+use crate::{
+    prelude::*,
+    state::{
+        context::{HttpRequestContext, SessionlessRequestContext},
+        request_state::RequestState,
+    },
+};
+
+use super::ConnectionInfo;
+
+/// For retrieving information about the connection of a request
+pub trait ContextConnectionInfoExt {
+    /// Returns the `ConnectionInfo` of the request
+    fn connection_info(&self) -> &ConnectionInfo;
+
+    /// Returns the IP of the client, resolved from the forwarding headers of trusted proxies
+    fn client_ip(&self) -> IpAddr {
+        self.connection_info().client_ip()
+    }
+}
+
+impl ContextConnectionInfoExt for HttpRequestContext {
+    fn connection_info(&self) -> &ConnectionInfo {
+        RequestState::get_from_ctx(self)
+            .get::<ConnectionInfo>()
+            .expect("every HttpRequestContext must have a ConnectionInfo")
+    }
+}
+
+impl ContextConnectionInfoExt for SessionlessRequestContext {
+    fn connection_info(&self) -> &ConnectionInfo {
+        RequestState::get_from_ctx(self)
+            .get::<ConnectionInfo>()
+            .expect("every SessionlessRequestContext must have a ConnectionInfo")
+    }
+}
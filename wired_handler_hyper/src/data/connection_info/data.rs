@@ -0,0 +1,158 @@
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+
+use http::{HeaderMap, Version, header::FORWARDED};
+
+const X_FORWARDED_FOR: &str = "x-forwarded-for";
+
+/// Information about the connection a request has been received on, stored in the `RequestState`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConnectionInfo {
+    peer_addr: Option<SocketAddr>,
+    local_addr: Option<SocketAddr>,
+    is_tls: bool,
+    version: Version,
+    client_ip: IpAddr,
+}
+
+impl ConnectionInfo {
+    /// Creates a `ConnectionInfo` of a TCP connection, the client IP is the IP of the peer
+    pub fn new(
+        peer_addr: SocketAddr,
+        local_addr: SocketAddr,
+        is_tls: bool,
+        version: Version,
+    ) -> Self {
+        Self {
+            peer_addr: Some(peer_addr),
+            local_addr: Some(local_addr),
+            is_tls,
+            version,
+            client_ip: peer_addr.ip(),
+        }
+    }
+
+    /// Creates a `ConnectionInfo` of a Unix socket connection, the client IP is the loopback address
+    pub fn new_unix(is_tls: bool, version: Version) -> Self {
+        Self {
+            peer_addr: None,
+            local_addr: None,
+            is_tls,
+            version,
+            client_ip: IpAddr::V4(Ipv4Addr::LOCALHOST),
+        }
+    }
+
+    /// Resolves the client IP from the `Forwarded` or `X-Forwarded-For` header if the peer is one of the `trusted_proxies`.
+    /// The forwarding chain is followed backwards as long as the proxies are trusted.
+    /// Peers connected over a Unix socket are only trusted if `trust_unix_socket_peers` is set
+    pub fn with_forwarded_client_ip(
+        mut self,
+        headers: &HeaderMap,
+        trusted_proxies: &[IpAddr],
+        trust_unix_socket_peers: bool,
+    ) -> Self {
+        let is_peer_trusted = match self.peer_addr {
+            Some(peer_addr) => trusted_proxies.contains(&peer_addr.ip()),
+            None => trust_unix_socket_peers,
+        };
+        if is_peer_trusted {
+            self.client_ip = forwarded_client_ip(self.client_ip, headers, trusted_proxies);
+        }
+        self
+    }
+
+    /// Address of the directly connected peer, can be a proxy. `None` for Unix sockets
+    pub fn peer_addr(&self) -> Option<SocketAddr> {
+        self.peer_addr
+    }
+
+    /// Address the connection has been accepted on. `None` for Unix sockets
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.local_addr
+    }
+
+    /// Whether the connection is encrypted using TLS
+    pub fn is_tls(&self) -> bool {
+        self.is_tls
+    }
+
+    /// HTTP version of the request
+    pub fn version(&self) -> Version {
+        self.version
+    }
+
+    /// IP of the client, differs from the IP of the peer if the peer is a trusted proxy
+    pub fn client_ip(&self) -> IpAddr {
+        self.client_ip
+    }
+}
+
+/// Parses a node of a forwarding header, like `192.0.2.60`, `192.0.2.60:4711` or `[2001:db8::1]:4711`.
+/// Returns `None` for unknown or obfuscated nodes
+fn parse_node(node: &str) -> Option<IpAddr> {
+    let node = node.trim().trim_matches('"');
+    if let Ok(ip) = node.parse() {
+        return Some(ip);
+    }
+
+    if let Some(bracketed) = node.strip_prefix('[') {
+        let (ip, _) = bracketed.split_once(']')?;
+        return ip.parse().ok();
+    }
+
+    let (ip, _port) = node.rsplit_once(':')?;
+    ip.parse().ok()
+}
+
+/// Returns the values of all `name` headers, split at `,`
+fn header_elements<'a>(headers: &'a HeaderMap, name: &str) -> impl Iterator<Item = &'a str> {
+    headers
+        .get_all(name)
+        .into_iter()
+        .filter_map(|header_value| header_value.to_str().ok())
+        .flat_map(|header_value| header_value.split(','))
+}
+
+/// Returns the `for` node of every element of the `Forwarded` headers
+fn forwarded_chain(headers: &HeaderMap) -> Vec<Option<IpAddr>> {
+    header_elements(headers, FORWARDED.as_str())
+        .map(|element| {
+            element
+                .split(';')
+                .filter_map(|pair| pair.split_once('='))
+                .find(|(key, _)| key.trim().eq_ignore_ascii_case("for"))
+                .and_then(|(_, node)| parse_node(node))
+        })
+        .collect()
+}
+
+/// Returns the nodes of the `X-Forwarded-For` headers
+fn x_forwarded_for_chain(headers: &HeaderMap) -> Vec<Option<IpAddr>> {
+    header_elements(headers, X_FORWARDED_FOR)
+        .map(parse_node)
+        .collect()
+}
+
+/// Follows the forwarding chain of a trusted peer backwards to the first untrusted node
+fn forwarded_client_ip(peer_ip: IpAddr, headers: &HeaderMap, trusted_proxies: &[IpAddr]) -> IpAddr {
+    let forwarding_chain = if headers.contains_key(FORWARDED) {
+        forwarded_chain(headers)
+    } else {
+        x_forwarded_for_chain(headers)
+    };
+
+    // the last node has been added by the nearest proxy
+    let mut client_ip = peer_ip;
+    for node in forwarding_chain.into_iter().rev() {
+        let Some(node_ip) = node else {
+            break;
+        };
+        client_ip = node_ip;
+
+        if !trusted_proxies.contains(&node_ip) {
+            break;
+        }
+    }
+
+    client_ip
+}
@@ -0,0 +1,7 @@
+pub use context_connection_info_ext::*;
+pub use data::*;
+
+mod context_connection_info_ext;
+mod data;
+#[cfg(test)]
+mod test;
@@ -0,0 +1,168 @@
+use std::net::{IpAddr, SocketAddr};
+
+use http::{HeaderMap, HeaderValue, Version};
+
+use super::{ConnectionInfo, ContextConnectionInfoExt};
+use crate::{
+    prelude::*,
+    state::{
+        context::HttpRequestContext, global_state::GlobalState, request_state::RequestState,
+        session_state::SessionState,
+    },
+};
+
+const PROXY: &str = "10.0.0.1";
+const OTHER_PROXY: &str = "10.0.0.2";
+const CLIENT: &str = "203.0.113.7";
+
+fn ip(ip: &str) -> IpAddr {
+    ip.parse().unwrap()
+}
+
+fn connection_info(peer_ip: &str) -> ConnectionInfo {
+    ConnectionInfo::new(
+        SocketAddr::new(ip(peer_ip), 50000),
+        "127.0.0.1:8000".parse().unwrap(),
+        false,
+        Version::HTTP_11,
+    )
+}
+
+fn client_ip(peer_ip: &str, headers: &[(&'static str, &str)], trusted_proxies: &[&str]) -> IpAddr {
+    let mut header_map = HeaderMap::new();
+    for (name, value) in headers {
+        header_map.append(*name, HeaderValue::from_str(value).unwrap());
+    }
+    let trusted_proxies: Vec<_> = trusted_proxies.iter().map(|proxy| ip(proxy)).collect();
+
+    connection_info(peer_ip)
+        .with_forwarded_client_ip(&header_map, &trusted_proxies, false)
+        .client_ip()
+}
+
+#[test]
+fn test() {
+    // no proxy
+    assert_eq!(client_ip(CLIENT, &[], &[PROXY]), ip(CLIENT));
+
+    // untrusted peer can't spoof its IP
+    assert_eq!(
+        client_ip(CLIENT, &[("x-forwarded-for", "198.51.100.1")], &[PROXY]),
+        ip(CLIENT)
+    );
+
+    // X-Forwarded-For of a trusted proxy
+    assert_eq!(
+        client_ip(PROXY, &[("x-forwarded-for", CLIENT)], &[PROXY]),
+        ip(CLIENT)
+    );
+
+    // spoofed entries before the client are ignored
+    assert_eq!(
+        client_ip(
+            PROXY,
+            &[(
+                "x-forwarded-for",
+                &format!("198.51.100.1, {CLIENT}, {OTHER_PROXY}")
+            )],
+            &[PROXY, OTHER_PROXY]
+        ),
+        ip(CLIENT)
+    );
+
+    // multiple headers form one chain
+    assert_eq!(
+        client_ip(
+            PROXY,
+            &[
+                ("x-forwarded-for", CLIENT),
+                ("x-forwarded-for", OTHER_PROXY)
+            ],
+            &[PROXY, OTHER_PROXY]
+        ),
+        ip(CLIENT)
+    );
+
+    // all trusted, the first one is the client
+    assert_eq!(
+        client_ip(
+            PROXY,
+            &[("x-forwarded-for", OTHER_PROXY)],
+            &[PROXY, OTHER_PROXY]
+        ),
+        ip(OTHER_PROXY)
+    );
+
+    // invalid entries stop the chain
+    assert_eq!(
+        client_ip(
+            PROXY,
+            &[("x-forwarded-for", &format!("{CLIENT}, invalid"))],
+            &[PROXY]
+        ),
+        ip(PROXY)
+    );
+
+    // Forwarded is preferred and supports ports and IPv6
+    assert_eq!(
+        client_ip(
+            PROXY,
+            &[
+                ("x-forwarded-for", "198.51.100.1"),
+                (
+                    "forwarded",
+                    &format!("for=\"[2001:db8::1]:4711\";proto=https, For=\"{OTHER_PROXY}:80\""),
+                )
+            ],
+            &[PROXY, OTHER_PROXY]
+        ),
+        ip("2001:db8::1")
+    );
+
+    // obfuscated nodes stop the chain
+    assert_eq!(
+        client_ip(PROXY, &[("forwarded", "for=_hidden")], &[PROXY]),
+        ip(PROXY)
+    );
+
+    // Unix socket peers can't spoof their IP
+    let mut header_map = HeaderMap::new();
+    header_map.insert("x-forwarded-for", HeaderValue::from_static(CLIENT));
+    let unix_connection_info = ConnectionInfo::new_unix(false, Version::HTTP_11);
+    assert_eq!(unix_connection_info.client_ip(), ip("127.0.0.1"));
+    assert!(unix_connection_info.peer_addr().is_none());
+    assert_eq!(
+        unix_connection_info
+            .with_forwarded_client_ip(&header_map, &[], false)
+            .client_ip(),
+        ip("127.0.0.1")
+    );
+
+    // unless they're trusted
+    assert_eq!(
+        unix_connection_info
+            .with_forwarded_client_ip(&header_map, &[], true)
+            .client_ip(),
+        ip(CLIENT)
+    );
+
+    // accessible from the context
+    let mut request_state = RequestState::default();
+    request_state.insert(connection_info(PROXY).with_forwarded_client_ip(
+        &HeaderMap::new(),
+        &[ip(PROXY)],
+        false,
+    ));
+    let ctx = HttpRequestContext::from_states(
+        GlobalState::default(),
+        SessionState::default(),
+        request_state,
+    );
+    assert_eq!(ctx.client_ip(), ip(PROXY));
+    assert_eq!(
+        ctx.connection_info().peer_addr(),
+        Some(SocketAddr::new(ip(PROXY), 50000))
+    );
+    assert!(!ctx.connection_info().is_tls());
+    assert_eq!(ctx.connection_info().version(), Version::HTTP_11);
+}
@@ -1,8 +1,10 @@
 use std::collections::HashMap;
 
+use hyper_tungstenite::tungstenite::Message;
+
 use crate::state::connection_state::ConnectionState;
 
-use super::connection_id::ConnectionId;
+use super::{connection_id::ConnectionId, send_message::SendMessageExt};
 
 /// Stores all connections for a state, identified by `ConnectionId`
 #[derive(Debug, Default)]
@@ -17,3 +19,19 @@ impl ConnectionStorage {
         &mut self.0
     }
 }
+
+/// Sends a close frame to all `connection_states`
+pub(crate) async fn close_connections(
+    connection_states: impl IntoIterator<Item = ConnectionState>,
+) {
+    futures::future::join_all(
+        connection_states
+            .into_iter()
+            .map(|connection_state| async move {
+                if let Err(err) = connection_state.send_message(Message::Close(None)).await {
+                    tracing::debug!("failed to close websocket connection: {err}");
+                }
+            }),
+    )
+    .await;
+}
@@ -0,0 +1,176 @@
+use std::future::Future;
+
+use cookie::{Cookie, CookieJar};
+use http::{
+    HeaderValue,
+    header::{COOKIE, SET_COOKIE},
+};
+
+use super::{CookieError, CookieKey};
+use crate::{
+    data::{request, response::Response},
+    prelude::*,
+    state::{context::HttpRequestContext, global_state::GlobalState, request_state::RequestState},
+};
+
+/// The cookies of a request, stored in the `RequestState`. Keeps track of added and removed cookies
+#[derive(Debug)]
+struct RequestCookies(CookieJar);
+
+/// Appends a `Set-Cookie` header to `response` for every added or removed cookie
+pub(crate) fn apply_cookies(request_state: &mut RequestState, response: &mut Response) {
+    let Some(RequestCookies(cookie_jar)) = request_state.remove_get::<RequestCookies>() else {
+        return;
+    };
+
+    for cookie in cookie_jar.delta() {
+        match HeaderValue::from_str(&cookie.to_string()) {
+            Ok(header_value) => {
+                response.headers_mut().append(SET_COOKIE, header_value);
+            }
+            Err(err) => tracing::error!("invalid cookie {}: {err}", cookie.name()),
+        }
+    }
+}
+
+/// Returns the `CookieKey` from the `GlobalState`
+async fn cookie_key(ctx: &HttpRequestContext) -> Result<CookieKey, CookieError> {
+    GlobalState::get_from_ctx(ctx)
+        .get_cloned::<CookieKey>()
+        .await
+        .ok_or(CookieError::MissingKey)
+}
+
+/// For creating the `CookieJar`
+pub trait ContextCreateCookieJarExt {
+    /// Ensure the `CookieJar` parsed from the request's `Cookie` headers is in the state
+    fn create_cookie_jar(&mut self);
+}
+
+impl ContextCreateCookieJarExt for HttpRequestContext {
+    fn create_cookie_jar(&mut self) {
+        if RequestState::get_from_ctx(self).exists::<RequestCookies>() {
+            return;
+        }
+
+        let mut cookie_jar = CookieJar::new();
+        let request_cookies = request::request_headers(self)
+            .into_iter()
+            .flat_map(|headers| headers.get_all(COOKIE))
+            .filter_map(|header_value| header_value.to_str().ok())
+            .flat_map(Cookie::split_parse)
+            .filter_map(Result::ok);
+        for cookie in request_cookies {
+            // the first cookie is the most specific one
+            if cookie_jar.get(cookie.name()).is_none() {
+                cookie_jar.add_original(cookie.into_owned());
+            }
+        }
+
+        RequestState::get_mut_from_ctx(self).insert(RequestCookies(cookie_jar));
+    }
+}
+
+/// For reading and setting cookies. Added and removed cookies are sent with the `Response`
+pub trait ContextCookieExt: ContextCreateCookieJarExt {
+    /// Returns a reference to the `CookieJar`. Parsed from the request if it doesn't exist
+    fn cookie_jar(&mut self) -> &CookieJar;
+
+    /// Returns a mutable reference to the `CookieJar`. Parsed from the request if it doesn't exist
+    fn cookie_jar_mut(&mut self) -> &mut CookieJar;
+
+    /// Returns the cookie named `name`
+    fn cookie(&mut self, name: &str) -> Option<&Cookie<'static>> {
+        self.cookie_jar().get(name)
+    }
+
+    /// Adds a cookie, which is sent with the `Response`
+    fn add_cookie(&mut self, cookie: impl Into<Cookie<'static>>) {
+        self.cookie_jar_mut().add(cookie);
+    }
+
+    /// Removes a cookie from the client. Path and domain have to match the ones the cookie has been set with
+    fn remove_cookie(&mut self, cookie: impl Into<Cookie<'static>>) {
+        self.cookie_jar_mut().remove(cookie);
+    }
+
+    /// Returns the cookie named `name` if its signature is valid. Requires a `CookieKey` in the `GlobalState`
+    fn signed_cookie(
+        &mut self,
+        name: &str,
+    ) -> impl Future<Output = Result<Option<Cookie<'static>>, CookieError>>;
+
+    /// Adds a cookie signed with HMAC, so it can be read but not be tampered with by the client.
+    /// Requires a `CookieKey` in the `GlobalState`
+    fn add_signed_cookie(
+        &mut self,
+        cookie: impl Into<Cookie<'static>>,
+    ) -> impl Future<Output = Result<(), CookieError>>;
+
+    /// Returns the decrypted cookie named `name` if it's authentic. Requires a `CookieKey` in the `GlobalState`
+    fn private_cookie(
+        &mut self,
+        name: &str,
+    ) -> impl Future<Output = Result<Option<Cookie<'static>>, CookieError>>;
+
+    /// Adds a cookie encrypted with AEAD, so it can neither be read nor be tampered with by the client.
+    /// Requires a `CookieKey` in the `GlobalState`
+    fn add_private_cookie(
+        &mut self,
+        cookie: impl Into<Cookie<'static>>,
+    ) -> impl Future<Output = Result<(), CookieError>>;
+}
+
+impl ContextCookieExt for HttpRequestContext {
+    fn cookie_jar(&mut self) -> &CookieJar {
+        self.create_cookie_jar();
+
+        &RequestState::get_from_ctx(self)
+            .get::<RequestCookies>()
+            .unwrap() // just created
+            .0
+    }
+
+    fn cookie_jar_mut(&mut self) -> &mut CookieJar {
+        self.create_cookie_jar();
+
+        &mut RequestState::get_mut_from_ctx(self)
+            .get_mut::<RequestCookies>()
+            .unwrap() // just created
+            .0
+    }
+
+    async fn signed_cookie(&mut self, name: &str) -> Result<Option<Cookie<'static>>, CookieError> {
+        let cookie_key = cookie_key(self).await?;
+        Ok(self.cookie_jar().signed(cookie_key.key()).get(name))
+    }
+
+    async fn add_signed_cookie(
+        &mut self,
+        cookie: impl Into<Cookie<'static>>,
+    ) -> Result<(), CookieError> {
+        let cookie_key = cookie_key(self).await?;
+        self.cookie_jar_mut()
+            .signed_mut(cookie_key.key())
+            .add(cookie);
+
+        Ok(())
+    }
+
+    async fn private_cookie(&mut self, name: &str) -> Result<Option<Cookie<'static>>, CookieError> {
+        let cookie_key = cookie_key(self).await?;
+        Ok(self.cookie_jar().private(cookie_key.key()).get(name))
+    }
+
+    async fn add_private_cookie(
+        &mut self,
+        cookie: impl Into<Cookie<'static>>,
+    ) -> Result<(), CookieError> {
+        let cookie_key = cookie_key(self).await?;
+        self.cookie_jar_mut()
+            .private_mut(cookie_key.key())
+            .add(cookie);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,33 @@
+use cookie::Key;
+
+/// The key for signing and encrypting cookies. Insert it into the `GlobalState` to use signed and private cookies
+#[derive(Clone)]
+pub struct CookieKey(Key);
+
+impl std::fmt::Debug for CookieKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("CookieKey").finish_non_exhaustive()
+    }
+}
+
+impl From<Key> for CookieKey {
+    fn from(value: Key) -> Self {
+        Self(value)
+    }
+}
+
+impl CookieKey {
+    /// Generates a random `CookieKey`. Cookies signed or encrypted with it can't be read after a restart
+    pub fn generate() -> Self {
+        Self(Key::generate())
+    }
+
+    /// Creates a `CookieKey` from a master key of at least 64 bytes
+    pub fn from_master(master_key: &[u8]) -> Result<Self, cookie::KeyError> {
+        Ok(Self(Key::try_from(master_key)?))
+    }
+
+    pub fn key(&self) -> &Key {
+        &self.0
+    }
+}
@@ -0,0 +1,17 @@
+use thiserror::Error;
+
+use crate::data::http_error::HttpError;
+
+/// Error returned when signed or private cookies can't be used
+#[derive(Debug, Error)]
+pub enum CookieError {
+    #[error("no CookieKey has been inserted into the GlobalState")]
+    MissingKey,
+}
+
+impl From<CookieError> for HttpError {
+    fn from(value: CookieError) -> Self {
+        tracing::error!("{value}");
+        Self::internal_server_error("internal server error")
+    }
+}
@@ -0,0 +1,10 @@
+pub use context_cookie_ext::*;
+pub use cookie::{Cookie, CookieJar};
+pub use cookie_key::*;
+pub use error::*;
+
+mod context_cookie_ext;
+mod cookie_key;
+mod error;
+#[cfg(test)]
+mod test;
@@ -0,0 +1,170 @@
+use http::{
+    HeaderMap, HeaderValue, StatusCode,
+    header::{COOKIE, SET_COOKIE},
+};
+
+use super::{Cookie, CookieError, CookieKey, apply_cookies};
+use crate::{
+    data::{http_error::HttpError, response::Response, response_body::ResponseBody},
+    prelude::*,
+    state::{
+        context::HttpRequestContext, global_state::GlobalState, request_state::RequestState,
+        session_state::SessionState,
+    },
+};
+
+#[test]
+fn test() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    runtime.block_on(run_test());
+}
+
+#[test]
+fn test_signed_private() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    runtime.block_on(run_test_signed_private());
+}
+
+fn create_ctx(global_state: &GlobalState, cookie: Option<&str>) -> HttpRequestContext {
+    let mut headers = HeaderMap::new();
+    if let Some(cookie) = cookie {
+        headers.insert(COOKIE, HeaderValue::from_str(cookie).unwrap());
+    }
+    let mut request_state = RequestState::default();
+    request_state.insert(headers);
+
+    HttpRequestContext::from_states(global_state.clone(), SessionState::default(), request_state)
+}
+
+fn set_cookies(ctx: &mut HttpRequestContext) -> Vec<String> {
+    let mut response = Response::builder().body(ResponseBody::empty()).unwrap();
+    apply_cookies(RequestState::get_mut_from_ctx(ctx), &mut response);
+
+    response
+        .headers()
+        .get_all(SET_COOKIE)
+        .iter()
+        .map(|header_value| header_value.to_str().unwrap().to_string())
+        .collect()
+}
+
+async fn run_test() {
+    let global_state = GlobalState::default();
+
+    // request cookies are readable, but not sent back
+    {
+        let mut ctx = create_ctx(&global_state, Some("a=1; b=2"));
+        assert_eq!(ctx.cookie("a").map(Cookie::value), Some("1"));
+        assert_eq!(ctx.cookie("b").map(Cookie::value), Some("2"));
+        assert!(ctx.cookie("c").is_none());
+        assert!(set_cookies(&mut ctx).is_empty());
+    }
+
+    // first cookie of the same name wins
+    {
+        let mut ctx = create_ctx(&global_state, Some("a=1; a=2"));
+        assert_eq!(ctx.cookie("a").map(Cookie::value), Some("1"));
+    }
+
+    // added and removed cookies are sent
+    {
+        let mut ctx = create_ctx(&global_state, Some("a=1; b=2"));
+        ctx.add_cookie(Cookie::build(("c", "3")).path("/"));
+        ctx.remove_cookie("a");
+        assert_eq!(ctx.cookie("c").map(Cookie::value), Some("3"));
+        assert!(ctx.cookie("a").is_none());
+
+        let mut set_cookies = set_cookies(&mut ctx);
+        set_cookies.sort();
+        assert_eq!(set_cookies.len(), 2);
+        assert!(set_cookies[0].starts_with("a=;"));
+        assert!(set_cookies[0].contains("Max-Age=0"));
+        assert_eq!(set_cookies[1], "c=3; Path=/");
+    }
+
+    // no headers, no cookies
+    {
+        let mut ctx = HttpRequestContext::from_states(
+            global_state.clone(),
+            SessionState::default(),
+            RequestState::default(),
+        );
+        assert!(ctx.cookie("a").is_none());
+        assert!(set_cookies(&mut ctx).is_empty());
+    }
+}
+
+async fn run_test_signed_private() {
+    // key is required
+    {
+        let global_state = GlobalState::default();
+        let mut ctx = create_ctx(&global_state, None);
+        assert!(matches!(
+            ctx.signed_cookie("a").await,
+            Err(CookieError::MissingKey)
+        ));
+        assert!(matches!(
+            ctx.add_private_cookie(("a", "1")).await,
+            Err(CookieError::MissingKey)
+        ));
+        let http_error = HttpError::from(CookieError::MissingKey);
+        assert_eq!(
+            Response::from(http_error).status(),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    assert!(CookieKey::from_master(&[0; 16]).is_err());
+
+    let global_state = GlobalState::default();
+    global_state
+        .insert(CookieKey::from_master(&[7; 64]).unwrap())
+        .await;
+
+    // signed round trip
+    let signed_cookie = {
+        let mut ctx = create_ctx(&global_state, None);
+        ctx.add_signed_cookie(("user", "alice")).await.unwrap();
+        let set_cookies = set_cookies(&mut ctx);
+        assert_eq!(set_cookies.len(), 1);
+        assert!(set_cookies[0].contains("alice"));
+        set_cookies[0].clone()
+    };
+    {
+        let mut ctx = create_ctx(&global_state, Some(&signed_cookie));
+        let cookie = ctx.signed_cookie("user").await.unwrap().unwrap();
+        assert_eq!(cookie.value(), "alice");
+    }
+
+    // tampered signed cookie is rejected
+    {
+        let tampered_cookie = signed_cookie.replace("alice", "mallory");
+        let mut ctx = create_ctx(&global_state, Some(&tampered_cookie));
+        assert!(ctx.signed_cookie("user").await.unwrap().is_none());
+        assert!(ctx.cookie("user").is_some());
+    }
+
+    // private round trip
+    let private_cookie = {
+        let mut ctx = create_ctx(&global_state, None);
+        ctx.add_private_cookie(("secret", "value")).await.unwrap();
+        let set_cookies = set_cookies(&mut ctx);
+        assert_eq!(set_cookies.len(), 1);
+        assert!(!set_cookies[0].contains("value"));
+        set_cookies[0].clone()
+    };
+    {
+        let mut ctx = create_ctx(&global_state, Some(&private_cookie));
+        let cookie = ctx.private_cookie("secret").await.unwrap().unwrap();
+        assert_eq!(cookie.value(), "value");
+        assert!(ctx.signed_cookie("secret").await.unwrap().is_none());
+    }
+
+    // a different key can't read the cookies
+    {
+        let global_state = GlobalState::default();
+        global_state.insert(CookieKey::generate()).await;
+        let mut ctx = create_ctx(&global_state, Some(&private_cookie));
+        assert!(ctx.private_cookie("secret").await.unwrap().is_none());
+    }
+}
@@ -1,9 +1,9 @@
 use diesel_async::{
+    AsyncPgConnection,
     pooled_connection::{
-        deadpool::{self, Pool},
         AsyncDieselConnectionManager,
+        deadpool::{self, Pool},
     },
-    AsyncPgConnection,
 };
 
 use crate::data::config::DbConfig;
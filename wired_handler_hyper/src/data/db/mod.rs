@@ -1,12 +1,12 @@
 use std::future::Future;
 
 use diesel_async::{
+    AsyncPgConnection,
     async_connection_wrapper::AsyncConnectionWrapper,
     pooled_connection::{
-        deadpool::{BuildError, Pool, PoolError},
         AsyncDieselConnectionManager,
+        deadpool::{BuildError, Pool, PoolError},
     },
-    AsyncPgConnection,
 };
 use diesel_migrations::EmbeddedMigrations;
 use thiserror::Error;
@@ -109,4 +109,7 @@ generate_constructors!(
     (bad_request, StatusCode::BAD_REQUEST),
     (unauthorized, StatusCode::UNAUTHORIZED),
     (not_implemented, StatusCode::NOT_IMPLEMENTED),
+    (not_acceptable, StatusCode::NOT_ACCEPTABLE),
+    (payload_too_large, StatusCode::PAYLOAD_TOO_LARGE),
+    (unsupported_media_type, StatusCode::UNSUPPORTED_MEDIA_TYPE),
 );
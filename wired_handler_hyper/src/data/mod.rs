@@ -1,19 +1,37 @@
+pub mod bind_config;
 pub mod config;
+pub mod connection_info;
+pub mod cookies;
 pub mod http_error;
+pub mod mount;
 pub mod path;
 pub mod query_params;
 pub mod request;
 pub mod response;
+pub mod router;
+pub mod session;
 pub mod session_id;
 
+#[cfg(feature = "json")]
+pub mod allowed_methods;
+#[cfg(feature = "json")]
+pub mod body_format;
 #[cfg(feature = "json")]
 pub mod request_body;
 #[cfg(feature = "json")]
 pub mod response_body;
+#[cfg(feature = "json")]
+pub mod sse;
 
 #[cfg(feature = "diesel")]
 pub mod db;
 
+#[cfg(feature = "multipart")]
+pub mod multipart;
+
+#[cfg(feature = "serve-dir")]
+pub mod serve_dir;
+
 #[cfg(feature = "websocket")]
 pub mod connection_id;
 #[cfg(feature = "websocket")]
@@ -0,0 +1,82 @@
+use std::{future::Future, ops::ControlFlow};
+
+use async_fn_traits::AsyncFn1;
+
+use crate::{
+    data::http_error::HttpError,
+    prelude::*,
+    state::{context::HttpRequestContext, global_state::GlobalState, request_state::RequestState},
+};
+
+/// The path prefixes of the sub-applications handling the request, without a trailing slash
+#[derive(Debug, Clone, Default)]
+pub(crate) struct MountPrefix(pub String);
+
+/// For handing requests to sub-applications
+pub trait ContextMountExt {
+    /// Runs `handler` if the `RemainingPath` starts with `prefix`, which is consumed.
+    /// While it runs, the `GlobalState` is replaced with its namespace `namespace`,
+    /// so the sub-application can have its own configs without affecting others.
+    /// URLs generated with `ContextUrlForExt` are prefixed with `prefix`.
+    /// If `handler` continues, the outer application gets the `RemainingPath` it had back.
+    ///
+    /// Continues without changing anything if the `RemainingPath` doesn't start with `prefix`
+    fn mount<Fn>(
+        &mut self,
+        prefix: &str,
+        namespace: &str,
+        handler: Fn,
+    ) -> impl Future<Output = Result<ControlFlow<()>, HttpError>>
+    where
+        Fn: for<'a> AsyncFn1<
+                &'a mut HttpRequestContext,
+                Output = Result<ControlFlow<()>, HttpError>,
+            >;
+}
+
+impl ContextMountExt for HttpRequestContext {
+    async fn mount<Fn>(
+        &mut self,
+        prefix: &str,
+        namespace: &str,
+        handler: Fn,
+    ) -> Result<ControlFlow<()>, HttpError>
+    where
+        Fn: for<'a> AsyncFn1<
+                &'a mut HttpRequestContext,
+                Output = Result<ControlFlow<()>, HttpError>,
+            >,
+    {
+        let outer_remaining_path = self.remaining_path().clone();
+        if !self.remaining_path_mut().strip_prefix(prefix) {
+            return Ok(ControlFlow::Continue(()));
+        }
+
+        let namespaced_global_state = GlobalState::get_from_ctx(self).namespace(namespace).await;
+        let global_state =
+            std::mem::replace(GlobalState::get_mut_from_ctx(self), namespaced_global_state);
+
+        let request_state = RequestState::get_mut_from_ctx(self);
+        let outer_mount_prefix = request_state.get::<MountPrefix>().cloned();
+        let prefix = prefix.trim_matches('/');
+        if !prefix.is_empty() {
+            let MountPrefix(mount_prefix) = outer_mount_prefix.clone().unwrap_or_default();
+            request_state.insert(MountPrefix(format!("{mount_prefix}/{prefix}")));
+        }
+
+        let result = handler(self).await;
+
+        // the outer application continues with its own state
+        *GlobalState::get_mut_from_ctx(self) = global_state;
+        let request_state = RequestState::get_mut_from_ctx(self);
+        match outer_mount_prefix {
+            Some(outer_mount_prefix) => request_state.insert(outer_mount_prefix),
+            None => request_state.remove::<MountPrefix>(),
+        }
+        if let Ok(ControlFlow::Continue(())) = result {
+            request_state.insert(outer_remaining_path);
+        }
+
+        result
+    }
+}
@@ -0,0 +1,5 @@
+pub use context_mount_ext::*;
+
+mod context_mount_ext;
+#[cfg(test)]
+mod test;
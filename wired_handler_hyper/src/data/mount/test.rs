@@ -0,0 +1,201 @@
+#![allow(clippy::result_large_err)]
+
+use std::ops::ControlFlow;
+
+use http_body_util::BodyExt;
+use serde::Serialize;
+
+use crate::{
+    data::{
+        config::RouterConfig, http_error::HttpError, response::Response,
+        response_body::ResponseBody, router::Router,
+    },
+    prelude::*,
+    state::{
+        context::HttpRequestContext, global_state::GlobalState, request_state::RequestState,
+        session_state::SessionState,
+    },
+};
+
+#[test]
+fn test() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    runtime.block_on(run_test());
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Greeting(&'static str);
+
+#[derive(Debug, Serialize)]
+struct UserParams {
+    id: u64,
+}
+
+async fn greet(ctx: &mut HttpRequestContext) -> Result<ControlFlow<()>, HttpError> {
+    let greeting = GlobalState::get_from_ctx(ctx)
+        .get_cloned::<Greeting>()
+        .await
+        .unwrap();
+    let next = ctx.remaining_path_mut().next().unwrap_or_default();
+    let body = format!("{} {next}", greeting.0);
+
+    ctx.stop(Response::builder().body(ResponseBody::from_bytes(body))?)
+}
+
+async fn user_url(ctx: &mut HttpRequestContext) -> Result<ControlFlow<()>, HttpError> {
+    let url = ctx.url_for("user", &UserParams { id: 7 }).await?;
+
+    ctx.stop(Response::builder().body(ResponseBody::from_bytes(url))?)
+}
+
+async fn admin(ctx: &mut HttpRequestContext) -> Result<ControlFlow<()>, HttpError> {
+    let router = Router::new().named_route("user", "/users/:id", user_url);
+
+    ctx.route(&router).await
+}
+
+fn context_with_path(global_state: &GlobalState, path: &str) -> HttpRequestContext {
+    let mut request_state = RequestState::default();
+    request_state.insert(path.to_string());
+    HttpRequestContext::from_states(global_state.clone(), SessionState::default(), request_state)
+}
+
+async fn response_body(ctx: &mut HttpRequestContext) -> String {
+    let response = RequestState::get_mut_from_ctx(ctx)
+        .remove_get::<Response>()
+        .unwrap();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+
+    String::from_utf8(body.to_vec()).unwrap()
+}
+
+async fn run_test() {
+    let global_state = GlobalState::default();
+    global_state.insert(Greeting("hello")).await;
+    global_state
+        .insert(RouterConfig {
+            mount_prefix: "/app".into(),
+        })
+        .await;
+
+    // namespaces see the data of their parents, but not the other way round
+    let blog = global_state.namespace("blog").await;
+    assert_eq!(blog.get_cloned::<Greeting>().await, Some(Greeting("hello")));
+    blog.insert(Greeting("welcome to the blog")).await;
+    assert_eq!(
+        blog.get_cloned::<Greeting>().await,
+        Some(Greeting("welcome to the blog"))
+    );
+    assert_eq!(
+        global_state.get_cloned::<Greeting>().await,
+        Some(Greeting("hello"))
+    );
+    assert_eq!(
+        global_state
+            .namespace("shop")
+            .await
+            .get_cloned::<Greeting>()
+            .await,
+        Some(Greeting("hello"))
+    );
+    assert_eq!(
+        blog.namespace("comments")
+            .await
+            .get_cloned::<Greeting>()
+            .await,
+        Some(Greeting("welcome to the blog"))
+    );
+    assert_eq!(
+        blog.root().get_cloned::<Greeting>().await,
+        Some(Greeting("hello"))
+    );
+    blog.remove::<Greeting>().await;
+    assert_eq!(blog.get_cloned::<Greeting>().await, Some(Greeting("hello")));
+    blog.insert(Greeting("welcome to the blog")).await;
+
+    // mutable access only reaches the own namespace unless `root` is used
+    let shop = global_state.namespace("shop").await;
+    assert!(shop.get_mut::<Greeting>().await.is_none());
+    shop.get_mut_or_insert_with(|| Greeting("hello")).await.0 = "welcome to the shop";
+    assert_eq!(
+        shop.get_cloned::<Greeting>().await,
+        Some(Greeting("welcome to the shop"))
+    );
+    assert_eq!(
+        global_state.get_cloned::<Greeting>().await,
+        Some(Greeting("hello"))
+    );
+    shop.root().get_mut::<Greeting>().await.unwrap().0 = "hi";
+    assert_eq!(
+        global_state.get_cloned::<Greeting>().await,
+        Some(Greeting("hi"))
+    );
+    global_state.insert(Greeting("hello")).await;
+    drop(shop);
+
+    // namespaces are kept alive by their root only
+    let weak_blog = blog.downgrade();
+    assert!(weak_blog.upgrade().is_some());
+    drop(blog);
+    assert!(weak_blog.upgrade().is_some());
+
+    // the prefix is consumed and the namespace used
+    {
+        let mut ctx = context_with_path(&global_state, "/blog/posts");
+        let control_flow = ctx.mount("/blog", "blog", greet).await.unwrap();
+        assert_eq!(control_flow, ControlFlow::Break(()));
+        assert_eq!(response_body(&mut ctx).await, "welcome to the blog posts");
+        assert_eq!(
+            GlobalState::get_from_ctx(&ctx)
+                .get_cloned::<Greeting>()
+                .await,
+            Some(Greeting("hello"))
+        );
+    }
+
+    {
+        let mut ctx = context_with_path(&global_state, "/blog");
+        let control_flow = ctx.mount("blog/", "blog", greet).await.unwrap();
+        assert_eq!(control_flow, ControlFlow::Break(()));
+        assert_eq!(response_body(&mut ctx).await, "welcome to the blog ");
+    }
+
+    // other paths are left untouched
+    {
+        let mut ctx = context_with_path(&global_state, "/blogs/posts");
+        let control_flow = ctx.mount("/blog", "blog", greet).await.unwrap();
+        assert_eq!(control_flow, ControlFlow::Continue(()));
+        assert_eq!(ctx.remaining_path_mut().next_str(), Some("blogs"));
+    }
+
+    // the outer application routes on its own path if the mounted one continues
+    {
+        let mut ctx = context_with_path(&global_state, "/blog/posts");
+        let control_flow = ctx
+            .mount("/blog", "blog", async |ctx: &mut HttpRequestContext| {
+                assert_eq!(ctx.remaining_path_mut().next_str(), Some("posts"));
+                Ok(ControlFlow::Continue(()))
+            })
+            .await
+            .unwrap();
+        assert_eq!(control_flow, ControlFlow::Continue(()));
+        assert_eq!(ctx.remaining_path_mut().next_str(), Some("blog"));
+        assert_eq!(ctx.remaining_path_mut().next_str(), Some("posts"));
+    }
+
+    // generated URLs contain the prefixes of all mounts
+    {
+        let mut ctx = context_with_path(&global_state, "/admin/v1/users/1");
+        let control_flow = ctx
+            .mount("/admin", "admin", async |ctx: &mut HttpRequestContext| {
+                ctx.mount("/v1", "v1", admin).await
+            })
+            .await
+            .unwrap();
+        assert_eq!(control_flow, ControlFlow::Break(()));
+        assert_eq!(response_body(&mut ctx).await, "/app/admin/v1/users/7");
+    }
+
+    drop(global_state);
+    assert!(weak_blog.upgrade().is_none());
+}
@@ -0,0 +1,115 @@
+use std::future::Future;
+
+use hyper::header::CONTENT_TYPE;
+use multer::{Constraints, Multipart, SizeLimit};
+use serde::de::DeserializeOwned;
+use tokio::io::AsyncWriteExt;
+
+use super::data::{MultipartForm, UploadedFile};
+use crate::{
+    data::{
+        config::MultipartConfig,
+        request::request_header,
+        request_body::{ContextBodyStreamExt, GetBodyError},
+    },
+    prelude::*,
+    state::{context::HttpRequestContext, global_state::GlobalState},
+};
+
+/// Returns the `MultipartConfig` from the `GlobalState` or the default if there is none
+async fn multipart_config(global_state: &GlobalState) -> MultipartConfig {
+    global_state
+        .get_cloned::<MultipartConfig>()
+        .await
+        .unwrap_or_default()
+}
+
+/// Creates the `Multipart` of the body, limited by `multipart_config`
+fn create_multipart<'a>(
+    ctx: &'a mut HttpRequestContext,
+    multipart_config: &MultipartConfig,
+) -> Result<Multipart<'a>, GetBodyError> {
+    let boundary = request_header(ctx, CONTENT_TYPE)
+        .and_then(|content_type| multer::parse_boundary(content_type).ok())
+        .ok_or(GetBodyError::UnsupportedMediaType)?;
+
+    let mut size_limit = SizeLimit::new();
+    if let Some(max_field_size) = multipart_config.max_field_size {
+        size_limit = size_limit.per_field(max_field_size as u64);
+    }
+    if let Some(max_total_size) = multipart_config.max_total_size {
+        size_limit = size_limit.whole_stream(max_total_size as u64);
+    }
+
+    Ok(Multipart::with_constraints(
+        ctx.body_stream()?,
+        boundary,
+        Constraints::new().size_limit(size_limit),
+    ))
+}
+
+/// For reading `multipart/form-data` bodies
+pub trait ContextMultipartExt {
+    /// Streams the fields of a `multipart/form-data` body, limited by the `MultipartConfig` in the `GlobalState`.
+    /// The default is used if there is none.
+    ///
+    /// The body can only be read once
+    fn multipart_fields(&mut self) -> impl Future<Output = Result<Multipart<'_>, GetBodyError>>;
+
+    /// Reads a `multipart/form-data` body like `multipart_fields`.
+    /// Its text fields are deserialized into `T` like a form body, its files are streamed to temp files.
+    ///
+    /// The body can only be read once
+    fn multipart<T: DeserializeOwned>(
+        &mut self,
+    ) -> impl Future<Output = Result<MultipartForm<T>, GetBodyError>>;
+}
+
+impl ContextMultipartExt for HttpRequestContext {
+    async fn multipart_fields(&mut self) -> Result<Multipart<'_>, GetBodyError> {
+        let multipart_config = multipart_config(GlobalState::get_from_ctx(self)).await;
+
+        create_multipart(self, &multipart_config)
+    }
+
+    async fn multipart<T: DeserializeOwned>(&mut self) -> Result<MultipartForm<T>, GetBodyError> {
+        let multipart_config = multipart_config(GlobalState::get_from_ctx(self)).await;
+        let temp_dir = multipart_config
+            .temp_dir
+            .clone()
+            .unwrap_or_else(std::env::temp_dir);
+        let mut multipart = create_multipart(self, &multipart_config)?;
+
+        let mut text_fields = Vec::new();
+        let mut files = Vec::new();
+        while let Some(mut field) = multipart.next_field().await? {
+            let field_name = field.name().map(ToString::to_string);
+            let Some(file_name) = field.file_name().map(ToString::to_string) else {
+                text_fields.push((field_name.unwrap_or_default(), field.text().await?));
+                continue;
+            };
+
+            // removed again if anything fails before it has been added to `files`
+            let (mut uploaded_file, mut file) = UploadedFile::create(
+                &temp_dir,
+                field_name,
+                Some(file_name),
+                field.content_type().map(ToString::to_string),
+            )
+            .await?;
+            while let Some(chunk) = field.chunk().await? {
+                file.write_all(&chunk).await?;
+                uploaded_file.add_size(chunk.len());
+            }
+            file.flush().await?;
+
+            files.push(uploaded_file);
+        }
+
+        let encoded_text_fields = serde_html_form::to_string(&text_fields)
+            .expect("string pairs can always be serialized");
+        let fields = serde_html_form::from_str(&encoded_text_fields)?;
+
+        Ok(MultipartForm { fields, files })
+    }
+}
@@ -0,0 +1,130 @@
+use std::path::{Path, PathBuf};
+
+use uuid::Uuid;
+
+/// A file in the temp dir, removed when dropped unless it has been persisted
+#[derive(Debug)]
+struct TempFile {
+    path: Option<PathBuf>,
+}
+
+impl TempFile {
+    fn new(temp_dir: &Path) -> Self {
+        Self {
+            path: Some(temp_dir.join(format!("wired_handler_upload_{}", Uuid::new_v4()))),
+        }
+    }
+
+    fn path(&self) -> &Path {
+        self.path
+            .as_deref()
+            .expect("path is only taken when persisting, which consumes the file")
+    }
+
+    /// Moves the file to `target`, it is no longer removed afterwards
+    async fn persist(mut self, target: &Path) -> std::io::Result<()> {
+        let path = self.path();
+        if tokio::fs::rename(path, target).await.is_err() {
+            // the temp dir can be on a different file system
+            tokio::fs::copy(path, target).await?;
+            tokio::fs::remove_file(path).await?;
+        }
+        self.path = None;
+
+        Ok(())
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        if let Some(path) = &self.path {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// A file of a multipart body, streamed to a temp file which is removed when dropped
+#[derive(Debug)]
+pub struct UploadedFile {
+    field_name: Option<String>,
+    file_name: Option<String>,
+    content_type: Option<String>,
+    size: usize,
+    temp_file: TempFile,
+}
+
+impl UploadedFile {
+    /// Creates an empty file in `temp_dir`
+    pub(super) async fn create(
+        temp_dir: &Path,
+        field_name: Option<String>,
+        file_name: Option<String>,
+        content_type: Option<String>,
+    ) -> std::io::Result<(Self, tokio::fs::File)> {
+        let temp_file = TempFile::new(temp_dir);
+        let file = tokio::fs::File::create(temp_file.path()).await?;
+
+        Ok((
+            Self {
+                field_name,
+                file_name,
+                content_type,
+                size: 0,
+                temp_file,
+            },
+            file,
+        ))
+    }
+
+    pub(super) fn add_size(&mut self, size: usize) {
+        self.size += size;
+    }
+
+    /// Name of the form field
+    pub fn field_name(&self) -> Option<&str> {
+        self.field_name.as_deref()
+    }
+
+    /// File name sent by the client. *Don't use it as a path, it can contain anything*
+    pub fn file_name(&self) -> Option<&str> {
+        self.file_name.as_deref()
+    }
+
+    /// `Content-Type` sent by the client
+    pub fn content_type(&self) -> Option<&str> {
+        self.content_type.as_deref()
+    }
+
+    /// Size of the file in bytes
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Path of the temp file
+    pub fn path(&self) -> &Path {
+        self.temp_file.path()
+    }
+
+    /// Moves the temp file to `target`, so it is kept
+    pub async fn persist(self, target: impl AsRef<Path>) -> std::io::Result<()> {
+        self.temp_file.persist(target.as_ref()).await
+    }
+}
+
+/// A multipart body with its text fields deserialized into `T` and its files streamed to temp files
+#[derive(Debug)]
+pub struct MultipartForm<T> {
+    /// The text fields
+    pub fields: T,
+    /// The files, in the order they have been sent
+    pub files: Vec<UploadedFile>,
+}
+
+impl<T> MultipartForm<T> {
+    /// Returns the first file of the field `field_name`
+    pub fn file(&self, field_name: &str) -> Option<&UploadedFile> {
+        self.files
+            .iter()
+            .find(|file| file.field_name() == Some(field_name))
+    }
+}
@@ -0,0 +1,8 @@
+pub use context_multipart_ext::*;
+pub use data::*;
+pub use multer::{Field, Multipart};
+
+mod context_multipart_ext;
+mod data;
+#[cfg(test)]
+mod test;
@@ -0,0 +1,212 @@
+use http::{HeaderMap, HeaderValue, header::CONTENT_TYPE};
+use hyper::body::{Bytes, Frame};
+use serde::Deserialize;
+
+use super::MultipartForm;
+use crate::{
+    data::{config::MultipartConfig, request_body::GetBodyError},
+    prelude::*,
+    state::{
+        context::HttpRequestContext, global_state::GlobalState, request_state::RequestState,
+        session_state::SessionState,
+    },
+    test_util::TestBody,
+};
+
+const BOUNDARY: &str = "X-BOUNDARY";
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+struct Upload {
+    title: String,
+    tags: Vec<String>,
+}
+
+#[test]
+fn test() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    runtime.block_on(run_test());
+}
+
+/// Creates a multipart body of text fields `(name, value)` and files `(name, file name, content)`
+fn multipart_body(text_fields: &[(&str, &str)], files: &[(&str, &str, &str)]) -> Bytes {
+    let mut body = String::new();
+    for (name, value) in text_fields {
+        body += &format!(
+            "--{BOUNDARY}\r\nContent-Disposition: form-data; name=\"{name}\"\r\n\r\n{value}\r\n"
+        );
+    }
+    for (name, file_name, content) in files {
+        body += &format!(
+            "--{BOUNDARY}\r\nContent-Disposition: form-data; name=\"{name}\"; filename=\"{file_name}\"\r\nContent-Type: text/plain\r\n\r\n{content}\r\n"
+        );
+    }
+    body += &format!("--{BOUNDARY}--\r\n");
+
+    body.into()
+}
+
+fn create_context(
+    global_state: &GlobalState,
+    content_type: &str,
+    body: Bytes,
+) -> HttpRequestContext {
+    let mut headers = HeaderMap::new();
+    headers.insert(CONTENT_TYPE, HeaderValue::from_str(content_type).unwrap());
+    let mut request_state = RequestState::default();
+    request_state.insert(headers);
+    request_state.insert(TestBody::from(body));
+
+    HttpRequestContext::from_states(global_state.clone(), SessionState::default(), request_state)
+}
+
+async fn run_test() {
+    let temp_dir =
+        std::env::temp_dir().join(format!("wired_handler_multipart_{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&temp_dir).unwrap();
+
+    let global_state = GlobalState::default();
+    global_state
+        .insert(MultipartConfig {
+            max_field_size: Some(16),
+            max_total_size: Some(1024),
+            temp_dir: Some(temp_dir.clone()),
+        })
+        .await;
+    let content_type = format!("multipart/form-data; boundary={BOUNDARY}");
+
+    // text fields are deserialized, files are streamed to temp files
+    {
+        let mut ctx = create_context(
+            &global_state,
+            &content_type,
+            multipart_body(
+                &[("title", "holiday"), ("tags", "beach"), ("tags", "sun")],
+                &[
+                    ("photo", "beach.txt", "waves"),
+                    ("photo", "sun.txt", "shine"),
+                ],
+            ),
+        );
+
+        let MultipartForm { fields, files } = ctx.multipart::<Upload>().await.unwrap();
+        assert_eq!(
+            fields,
+            Upload {
+                title: "holiday".into(),
+                tags: vec!["beach".into(), "sun".into()],
+            }
+        );
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].field_name(), Some("photo"));
+        assert_eq!(files[0].file_name(), Some("beach.txt"));
+        assert_eq!(files[0].content_type(), Some("text/plain"));
+        assert_eq!(files[0].size(), 5);
+        assert!(files[0].path().starts_with(&temp_dir));
+        assert_eq!(std::fs::read_to_string(files[1].path()).unwrap(), "shine");
+
+        // the body can only be read once
+        assert!(matches!(
+            ctx.multipart::<Upload>().await,
+            Err(GetBodyError::AlreadyParsed)
+        ));
+
+        // persisted files are kept, the others are removed
+        let mut files = files.into_iter();
+        let persisted_path = temp_dir.join("persisted.txt");
+        files
+            .next()
+            .unwrap()
+            .persist(&persisted_path)
+            .await
+            .unwrap();
+        drop(files);
+        assert_eq!(std::fs::read_dir(&temp_dir).unwrap().count(), 1);
+        assert_eq!(std::fs::read_to_string(&persisted_path).unwrap(), "waves");
+        std::fs::remove_file(persisted_path).unwrap();
+    }
+
+    // fields can be streamed
+    {
+        let mut ctx = create_context(
+            &global_state,
+            &content_type,
+            multipart_body(&[("title", "holiday")], &[]),
+        );
+
+        let mut multipart = ctx.multipart_fields().await.unwrap();
+        let field = multipart.next_field().await.unwrap().unwrap();
+        assert_eq!(field.name(), Some("title"));
+        assert_eq!(field.text().await.unwrap(), "holiday");
+        assert!(multipart.next_field().await.unwrap().is_none());
+    }
+
+    // the rest of a partially read body isn't decoded
+    {
+        let body = multipart_body(&[("title", "holiday"), ("tags", "beach")], &[]);
+        let mut ctx = create_context(&global_state, &content_type, Bytes::new());
+        // a frame per part, the last ones haven't arrived yet when the first field is read
+        let delimiter = format!("--{BOUNDARY}");
+        let mut part_starts: Vec<_> = body
+            .windows(delimiter.len())
+            .enumerate()
+            .filter(|(_, window)| *window == delimiter.as_bytes())
+            .map(|(position, _)| position)
+            .collect();
+        part_starts.push(body.len());
+        let frames: Vec<_> = part_starts
+            .windows(2)
+            .map(|part| Frame::data(body.slice(part[0]..part[1])))
+            .collect();
+        assert_eq!(frames.len(), 3);
+        RequestState::get_mut_from_ctx(&mut ctx).insert(TestBody::from_frames(frames));
+
+        {
+            let mut multipart = ctx.multipart_fields().await.unwrap();
+            let field = multipart.next_field().await.unwrap().unwrap();
+            assert_eq!(field.name(), Some("title"));
+        }
+        assert!(matches!(
+            ctx.body::<Upload>().await,
+            Err(GetBodyError::AlreadyParsed)
+        ));
+    }
+
+    // fields larger than the limit are rejected, their temp file is removed
+    {
+        let mut ctx = create_context(
+            &global_state,
+            &content_type,
+            multipart_body(&[], &[("photo", "large.txt", "0123456789abcdefg")]),
+        );
+
+        assert!(matches!(
+            ctx.multipart::<()>().await,
+            Err(GetBodyError::TooLarge(16))
+        ));
+        assert_eq!(std::fs::read_dir(&temp_dir).unwrap().count(), 0);
+    }
+
+    // so are bodies larger than the total limit
+    {
+        let files = vec![("photo", "small.txt", "0123456789"); 20];
+        let mut ctx = create_context(&global_state, &content_type, multipart_body(&[], &files));
+
+        assert!(matches!(
+            ctx.multipart::<()>().await,
+            Err(GetBodyError::TooLarge(1024))
+        ));
+        assert_eq!(std::fs::read_dir(&temp_dir).unwrap().count(), 0);
+    }
+
+    // other content types are unsupported
+    {
+        let mut ctx = create_context(&global_state, "application/json", Bytes::from_static(b"{}"));
+
+        assert!(matches!(
+            ctx.multipart::<()>().await,
+            Err(GetBodyError::UnsupportedMediaType)
+        ));
+    }
+
+    std::fs::remove_dir(temp_dir).unwrap();
+}
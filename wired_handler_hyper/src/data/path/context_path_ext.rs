@@ -16,15 +16,8 @@ impl ContextCreateRemainingPathExt for HttpRequestContext {
         if RequestState::get_from_ctx(self).exists::<RemainingPath>() {
             return;
         }
-        let path_without_starting_slash = {
-            let path = self.path();
-            let mut path_chars = path.chars();
-            path_chars.next();
-
-            path_chars.as_str().to_string()
-        };
-        RequestState::get_mut_from_ctx(self)
-            .insert(RemainingPath(Some(path_without_starting_slash)));
+        let remaining_path = RemainingPath::new(self.path());
+        RequestState::get_mut_from_ctx(self).insert(remaining_path);
     }
 }
 
@@ -1,68 +1,147 @@
-use std::{iter::FusedIterator, mem::take};
+use std::{fmt::Display, iter::FusedIterator, ops::Range, str::FromStr};
 
-/// Stores the part of the path that hasn't been used
-#[derive(Debug)]
-pub struct RemainingPath(pub(crate) Option<String>);
+use percent_encoding::percent_decode_str;
+
+use super::ParseSegmentError;
+
+/// Stores the part of the path that hasn't been used.
+///
+/// The segments are percent-decoded once on creation. Empty segments are skipped except for a trailing one,
+/// so `/users//42/` yields `"users"`, `"42"` and `""`, while `/` yields a single `""`
+#[derive(Debug, Clone)]
+pub struct RemainingPath {
+    /// The decoded segments, one after another
+    decoded: String,
+    /// The ranges of the segments in `decoded`
+    segments: Vec<Range<usize>>,
+    /// The indices of the segments which haven't been used
+    remaining: Range<usize>,
+}
 
 impl RemainingPath {
+    /// Splits `path` into its segments, a leading slash is ignored
+    pub(crate) fn new(path: &str) -> Self {
+        let path = path.strip_prefix('/').unwrap_or(path);
+        let mut raw_segments = path.split('/').peekable();
+        let mut segments = Vec::new();
+        while let Some(raw_segment) = raw_segments.next() {
+            if raw_segment.is_empty() && raw_segments.peek().is_some() {
+                continue;
+            }
+            segments.push(percent_decode_str(raw_segment).decode_utf8_lossy());
+        }
+
+        Self::from_segments(segments)
+    }
+
+    /// Creates it from already decoded segments
+    pub(crate) fn from_segments(segments: impl IntoIterator<Item = impl AsRef<str>>) -> Self {
+        let mut decoded = String::new();
+        let segments: Vec<Range<usize>> = segments
+            .into_iter()
+            .map(|segment| {
+                let start = decoded.len();
+                decoded.push_str(segment.as_ref());
+                start..decoded.len()
+            })
+            .collect();
+
+        Self {
+            decoded,
+            remaining: 0..segments.len(),
+            segments,
+        }
+    }
+
+    /// A path without any remaining segments
+    pub(crate) fn empty() -> Self {
+        Self {
+            decoded: String::new(),
+            segments: Vec::new(),
+            remaining: 0..0,
+        }
+    }
+
+    fn segment(&self, index: usize) -> &str {
+        &self.decoded[self.segments[index].clone()]
+    }
+
     /// Returns the next element without changing the remaining path
     pub fn peek(&self) -> Option<&str> {
-        let string_value = self.0.as_ref()?;
-        let Some((next_element, _)) = string_value.split_once('/') else {
-            return Some(string_value);
-        };
-
-        Some(next_element)
+        (!self.remaining.is_empty()).then(|| self.segment(self.remaining.start))
     }
 
     /// Returns the next last element without changing the remaining path
     pub fn peek_back(&self) -> Option<&str> {
-        let string_value = self.0.as_ref()?;
-        let Some((_, next_element)) = string_value.rsplit_once('/') else {
-            return Some(string_value);
-        };
+        (!self.remaining.is_empty()).then(|| self.segment(self.remaining.end - 1))
+    }
 
-        Some(next_element)
+    /// Returns the next element like `next`, without allocating
+    pub fn next_str(&mut self) -> Option<&str> {
+        let index = self.remaining.next()?;
+        Some(self.segment(index))
+    }
+
+    /// Returns the next last element like `next_back`, without allocating
+    pub fn next_back_str(&mut self) -> Option<&str> {
+        let index = self.remaining.next_back()?;
+        Some(self.segment(index))
+    }
+
+    /// Uses the elements of `prefix` (like `/admin/users`) if the remaining path starts with them.
+    /// Leaves a single `""` like the path `/` if nothing else remains
+    pub fn strip_prefix(&mut self, prefix: &str) -> bool {
+        let prefix_segments: Vec<&str> = prefix
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .collect();
+        if prefix_segments.len() > self.remaining.len()
+            || !prefix_segments
+                .iter()
+                .zip(self.remaining.clone())
+                .all(|(prefix_segment, index)| *prefix_segment == self.segment(index))
+        {
+            return false;
+        }
+
+        self.remaining.start += prefix_segments.len();
+        if self.remaining.is_empty() {
+            *self = Self::from_segments([""]);
+        }
+
+        true
+    }
+
+    /// Parses the next element into `T`. The element is used even if it can't be parsed
+    pub fn next_parsed<T: FromStr>(&mut self) -> Result<T, ParseSegmentError>
+    where
+        T::Err: Display,
+    {
+        let segment = self.next_str().ok_or(ParseSegmentError::Missing)?;
+
+        segment.parse().map_err(|err| {
+            ParseSegmentError::Invalid(format!("invalid path segment `{segment}`: {err}"))
+        })
     }
 }
 
 impl Iterator for RemainingPath {
     type Item = String;
     fn next(&mut self) -> Option<Self::Item> {
-        let Some((next_element, remaining_path)) = self.0.as_ref()?.split_once('/') else {
-            return take(&mut self.0);
-        };
-
-        let remaining_path = remaining_path.to_string();
-        let next_element = next_element.to_string();
-
-        self.0 = if next_element.is_empty() {
-            None
-        } else {
-            Some(remaining_path)
-        };
+        self.next_str().map(str::to_string)
+    }
 
-        Some(next_element)
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.remaining.size_hint()
     }
 }
 
+impl ExactSizeIterator for RemainingPath {}
+
 impl FusedIterator for RemainingPath {}
 
 impl DoubleEndedIterator for RemainingPath {
     fn next_back(&mut self) -> Option<Self::Item> {
-        let Some((remaining_path, next_element)) = self.0.as_ref()?.rsplit_once('/') else {
-            return take(&mut self.0);
-        };
-
-        let remaining_path = remaining_path.to_string();
-        let next_element = next_element.to_string();
-
-        self.0 = if next_element.is_empty() {
-            None
-        } else {
-            Some(remaining_path)
-        };
-
-        Some(next_element)
+        self.next_back_str().map(str::to_string)
     }
 }
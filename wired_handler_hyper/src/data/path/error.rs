@@ -0,0 +1,20 @@
+use thiserror::Error;
+
+use crate::data::http_error::HttpError;
+
+#[derive(Debug, Error)]
+pub enum ParseSegmentError {
+    #[error("missing path segment")]
+    Missing,
+    #[error("{0}")]
+    Invalid(String),
+}
+
+impl From<ParseSegmentError> for HttpError {
+    fn from(value: ParseSegmentError) -> Self {
+        match value {
+            ParseSegmentError::Missing => Self::not_found("not found"),
+            ParseSegmentError::Invalid(message) => Self::bad_request(message),
+        }
+    }
+}
@@ -1,7 +1,9 @@
 pub use context_path_ext::*;
 pub use data::*;
+pub use error::*;
 
 mod context_path_ext;
 mod data;
+mod error;
 #[cfg(test)]
 mod test;
@@ -1,6 +1,9 @@
+use hyper::StatusCode;
 use wired_handler::StateSyncMutableInsert;
 
+use super::ParseSegmentError;
 use crate::{
+    data::{http_error::HttpError, response::Response},
     prelude::*,
     state::{
         context::HttpRequestContext, global_state::GlobalState, request_state::RequestState,
@@ -147,4 +150,76 @@ fn test() {
         assert_eq!(None, remaining_path.peek_back());
         assert_eq!(None, remaining_path.next_back().as_deref());
     }
+
+    // empty and duplicate slashes
+    {
+        let mut ctx = context_with_path("//users//42/");
+        let remaining_path = ctx.remaining_path_mut();
+        assert_eq!(remaining_path.len(), 3);
+        assert_eq!(Some("users"), remaining_path.next().as_deref());
+        assert_eq!(Some("42"), remaining_path.next().as_deref());
+        assert_eq!(Some(""), remaining_path.next().as_deref());
+        assert_eq!(None, remaining_path.next().as_deref());
+    }
+
+    {
+        let mut ctx = context_with_path("//");
+        let remaining_path = ctx.remaining_path_mut();
+        assert_eq!(Some(""), remaining_path.next().as_deref());
+        assert_eq!(None, remaining_path.next().as_deref());
+    }
+
+    // percent-decoding
+    {
+        let mut ctx = context_with_path("/J%C3%BCrgen/a%2Fb/100%25/%FF");
+        let remaining_path = ctx.remaining_path_mut();
+        assert_eq!(Some("Jürgen"), remaining_path.peek());
+        assert_eq!(Some("Jürgen"), remaining_path.next_str());
+        assert_eq!(Some("a/b"), remaining_path.next_str());
+        assert_eq!(Some("\u{FFFD}"), remaining_path.next_back_str());
+        assert_eq!(Some("100%"), remaining_path.next_back_str());
+        assert_eq!(None, remaining_path.next_str());
+    }
+
+    // parsing
+    {
+        let mut ctx = context_with_path("/42/franz/-1");
+        let remaining_path = ctx.remaining_path_mut();
+        assert_eq!(remaining_path.next_parsed::<u32>().unwrap(), 42);
+        let err = remaining_path.next_parsed::<u32>().unwrap_err();
+        assert!(matches!(err, ParseSegmentError::Invalid(_)));
+        assert_eq!(
+            Response::from(HttpError::from(err)).status(),
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(remaining_path.next_parsed::<i8>().unwrap(), -1);
+        let err = remaining_path.next_parsed::<i8>().unwrap_err();
+        assert!(matches!(err, ParseSegmentError::Missing));
+        assert_eq!(
+            Response::from(HttpError::from(err)).status(),
+            StatusCode::NOT_FOUND
+        );
+    }
+
+    // prefixes
+    {
+        let mut ctx = context_with_path("/admin/users/42");
+        let remaining_path = ctx.remaining_path_mut();
+        assert!(!remaining_path.strip_prefix("/admin/posts"));
+        assert!(!remaining_path.strip_prefix("/adm"));
+        assert!(!remaining_path.strip_prefix("/admin/users/42/edit"));
+        assert_eq!(Some("admin"), remaining_path.peek());
+        assert!(remaining_path.strip_prefix("/admin/users/"));
+        assert_eq!(Some("42"), remaining_path.peek());
+        assert!(remaining_path.strip_prefix("/"));
+        assert_eq!(Some("42"), remaining_path.next_str());
+    }
+
+    {
+        let mut ctx = context_with_path("/J%C3%BCrgen");
+        let remaining_path = ctx.remaining_path_mut();
+        assert!(remaining_path.strip_prefix("Jürgen"));
+        assert_eq!(Some(""), remaining_path.next_str());
+        assert_eq!(None, remaining_path.next_str());
+    }
 }
@@ -89,9 +89,11 @@ fn test() {
 
         assert!(context.query_params::<TestParamsShouldFail>().is_err());
         assert!(context.query_params_mut::<TestParamsShouldFail>().is_err());
-        assert!(context
-            .remove_query_params::<TestParamsShouldFail>()
-            .is_err());
+        assert!(
+            context
+                .remove_query_params::<TestParamsShouldFail>()
+                .is_err()
+        );
     }
 
     {
@@ -100,17 +102,23 @@ fn test() {
             SessionState::default(),
             RequestState::default(),
         );
-        assert!(context
-            .query_params::<TestParamsShouldFail>()
-            .unwrap()
-            .is_none());
-        assert!(context
-            .query_params_mut::<TestParamsShouldFail>()
-            .unwrap()
-            .is_none());
-        assert!(context
-            .remove_query_params::<TestParamsShouldFail>()
-            .unwrap()
-            .is_none());
+        assert!(
+            context
+                .query_params::<TestParamsShouldFail>()
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            context
+                .query_params_mut::<TestParamsShouldFail>()
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            context
+                .remove_query_params::<TestParamsShouldFail>()
+                .unwrap()
+                .is_none()
+        );
     }
 }
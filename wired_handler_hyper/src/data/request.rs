@@ -1,3 +1,4 @@
+use http::{HeaderMap, header::AsHeaderName};
 use hyper::body::Incoming;
 
 use crate::{
@@ -26,3 +27,47 @@ impl ContextGetRequestExt for HttpRequestContext {
             .expect("every HttpRequestContext must have a Request")
     }
 }
+
+/// Returns the headers of the request
+// Different implementation needed because we can't produce a Request<Incoming>
+#[cfg(test)]
+pub(crate) fn request_headers(ctx: &HttpRequestContext) -> Option<&HeaderMap> {
+    RequestState::get_from_ctx(ctx).get::<HeaderMap>()
+}
+
+/// Returns the headers of the request
+#[cfg(not(test))]
+pub(crate) fn request_headers(ctx: &HttpRequestContext) -> Option<&HeaderMap> {
+    Some(ctx.request().headers())
+}
+
+/// Returns the request header `name` if it's valid UTF-8
+pub(crate) fn request_header(ctx: &HttpRequestContext, name: impl AsHeaderName) -> Option<&str> {
+    request_headers(ctx)?.get(name)?.to_str().ok()
+}
+
+/// Returns the query of the request
+// Different implementation needed because we can't produce a Request<Incoming>
+#[cfg(all(test, feature = "serve-dir"))]
+pub(crate) fn request_query(ctx: &HttpRequestContext) -> Option<&str> {
+    RequestState::get_from_ctx(ctx).get::<http::Uri>()?.query()
+}
+
+/// Returns the query of the request
+#[cfg(all(not(test), feature = "serve-dir"))]
+pub(crate) fn request_query(ctx: &HttpRequestContext) -> Option<&str> {
+    ctx.request().uri().query()
+}
+
+/// Returns the body of the request
+// Different implementation needed because we can't produce a Request<Incoming>
+#[cfg(test)]
+pub(crate) fn request_body_mut(ctx: &mut HttpRequestContext) -> &mut crate::test_util::TestBody {
+    RequestState::get_mut_from_ctx(ctx).get_mut_or_insert_default()
+}
+
+/// Returns the body of the request
+#[cfg(not(test))]
+pub(crate) fn request_body_mut(ctx: &mut HttpRequestContext) -> &mut Incoming {
+    ctx.request_mut().body_mut()
+}
@@ -0,0 +1,189 @@
+use std::{
+    pin::Pin,
+    task::{Context, Poll, ready},
+};
+
+use futures::Stream;
+use http::HeaderMap;
+use hyper::body::{Body, Bytes, Frame};
+
+use super::{ContextCreateBodyExt, error::GetBodyError};
+use crate::{
+    data::request::request_body_mut,
+    prelude::*,
+    state::{context::HttpRequestContext, request_state::RequestState},
+};
+
+/// For storing the trailers of the body in the `RequestState`
+#[derive(Debug)]
+struct RequestTrailers(HeaderMap);
+
+/// Polls the next frame of the body
+fn poll_body_frame(
+    ctx: &mut HttpRequestContext,
+    cx: &mut Context<'_>,
+) -> Poll<Option<Result<Frame<Bytes>, hyper::Error>>> {
+    Pin::new(request_body_mut(ctx)).poll_frame(cx)
+}
+
+/// The data of a request body, streamed as it arrives. The trailers are available once it has ended
+#[derive(Debug)]
+pub struct BodyStream<'a> {
+    ctx: &'a mut HttpRequestContext,
+    is_done: bool,
+}
+
+impl BodyStream<'_> {
+    /// Returns the trailers of the body, `None` until the stream has ended or if there are none
+    pub fn trailers(&self) -> Option<&HeaderMap> {
+        self.ctx.trailers()
+    }
+}
+
+impl Stream for BodyStream<'_> {
+    type Item = Result<Bytes, GetBodyError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            if self.is_done {
+                return Poll::Ready(None);
+            }
+
+            let frame = match ready!(poll_body_frame(self.ctx, cx)) {
+                Some(Ok(frame)) => frame,
+                Some(Err(err)) => {
+                    self.is_done = true;
+                    return Poll::Ready(Some(Err(err.into())));
+                }
+                None => {
+                    self.is_done = true;
+                    return Poll::Ready(None);
+                }
+            };
+
+            match frame.into_data() {
+                Ok(data) => return Poll::Ready(Some(Ok(data))),
+                Err(frame) => {
+                    if let Ok(trailers) = frame.into_trailers() {
+                        RequestState::get_mut_from_ctx(self.ctx).insert(RequestTrailers(trailers));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// For streaming the body of an `HttpRequestContext`
+pub trait ContextBodyStreamExt {
+    /// Returns the body as a `Stream` of its data, for bodies too large to be buffered.
+    /// The `BodyConfig` doesn't apply, the size has to be checked while streaming.
+    ///
+    /// The body is consumed, so it can't be parsed afterwards
+    fn body_stream(&mut self) -> Result<BodyStream<'_>, GetBodyError>;
+
+    /// Returns the trailers of the body, `None` until a `BodyStream` has ended or if there are none
+    fn trailers(&self) -> Option<&HeaderMap>;
+}
+
+impl ContextBodyStreamExt for HttpRequestContext {
+    fn body_stream(&mut self) -> Result<BodyStream<'_>, GetBodyError> {
+        if self.is_body_parsed() {
+            return Err(GetBodyError::AlreadyParsed);
+        }
+        self.mark_body_parsed();
+
+        Ok(BodyStream {
+            ctx: self,
+            is_done: false,
+        })
+    }
+
+    fn trailers(&self) -> Option<&HeaderMap> {
+        RequestState::get_from_ctx(self)
+            .get::<RequestTrailers>()
+            .map(|trailers| &trailers.0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use futures::TryStreamExt;
+    use http::{HeaderMap, HeaderValue, header::CONTENT_TYPE};
+    use hyper::body::{Bytes, Frame};
+
+    use super::ContextBodyStreamExt;
+    use crate::{
+        data::request_body::GetBodyError,
+        prelude::*,
+        state::{
+            context::HttpRequestContext, global_state::GlobalState, request_state::RequestState,
+            session_state::SessionState,
+        },
+        test_util::TestBody,
+    };
+
+    #[test]
+    fn test() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(run_test());
+    }
+
+    async fn run_test() {
+        let mut trailers = HeaderMap::new();
+        trailers.insert("checksum", HeaderValue::from_static("1234"));
+
+        let mut request_state = RequestState::default();
+        request_state.insert(TestBody::from_frames([
+            Frame::data(Bytes::from_static(b"hello ")),
+            Frame::data(Bytes::from_static(b"world")),
+            Frame::trailers(trailers.clone()),
+        ]));
+        let mut ctx = HttpRequestContext::from_states(
+            GlobalState::default(),
+            SessionState::default(),
+            request_state,
+        );
+
+        let mut body_stream = ctx.body_stream().unwrap();
+        assert!(body_stream.trailers().is_none());
+        assert_eq!(body_stream.try_next().await.unwrap().unwrap(), "hello ");
+        assert_eq!(body_stream.try_next().await.unwrap().unwrap(), "world");
+        assert!(body_stream.try_next().await.unwrap().is_none());
+        assert_eq!(body_stream.trailers(), Some(&trailers));
+        assert_eq!(ctx.trailers(), Some(&trailers));
+
+        // the body has been consumed
+        assert!(matches!(
+            ctx.body::<String>().await,
+            Err(GetBodyError::AlreadyParsed)
+        ));
+        assert!(matches!(
+            ctx.body_stream(),
+            Err(GetBodyError::AlreadyParsed)
+        ));
+
+        // the rest of a partially read body isn't decoded
+        let mut request_state = RequestState::default();
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        request_state.insert(headers);
+        request_state.insert(TestBody::from_frames([
+            Frame::data(Bytes::from_static(b"\"hello\"")),
+            Frame::data(Bytes::from_static(b"\"world\"")),
+        ]));
+        let mut ctx = HttpRequestContext::from_states(
+            GlobalState::default(),
+            SessionState::default(),
+            request_state,
+        );
+
+        {
+            let mut body_stream = ctx.body_stream().unwrap();
+            assert_eq!(body_stream.try_next().await.unwrap().unwrap(), "\"hello\"");
+        }
+        assert!(matches!(
+            ctx.body::<String>().await,
+            Err(GetBodyError::AlreadyParsed)
+        ));
+    }
+}
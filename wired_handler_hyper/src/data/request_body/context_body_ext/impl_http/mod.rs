@@ -1,17 +1,22 @@
-#[cfg(not(test))]
 use http_body_util::BodyExt;
-
-use hyper::body::Bytes;
+use hyper::{
+    body::Bytes,
+    header::{CONTENT_LENGTH, CONTENT_TYPE},
+};
 use serde::de::DeserializeOwned;
 
-use super::ContextCreateBodyExt;
+use super::{ContextCreateBodyExt, check_body_size, max_body_size};
 use crate::{
-    data::request_body::{
-        data::{RequestBody, RequestBodyParsed},
-        error::GetBodyError,
+    data::{
+        body_format::BodyFormat,
+        request::{request_body_mut, request_header},
+        request_body::{
+            data::{RawBody, RequestBody, RequestBodyParsed},
+            error::GetBodyError,
+        },
     },
     prelude::*,
-    state::{context::HttpRequestContext, request_state::RequestState},
+    state::{context::HttpRequestContext, global_state::GlobalState, request_state::RequestState},
 };
 
 impl ContextCreateBodyExt for HttpRequestContext {
@@ -23,28 +28,56 @@ impl ContextCreateBodyExt for HttpRequestContext {
         RequestState::get_mut_from_ctx(self).insert(RequestBodyParsed);
     }
 
+    fn keep_raw_body(&mut self) {
+        let request_state = RequestState::get_mut_from_ctx(self);
+        if !request_state.exists::<RawBody>() {
+            request_state.insert(RawBody::default());
+        }
+    }
+
+    fn cached_raw_body(&self) -> Option<&Bytes> {
+        RequestState::get_from_ctx(self).get::<RawBody>()?.get()
+    }
+
+    fn cache_raw_body(&mut self, bytes: Bytes) {
+        if let Some(raw_body) = RequestState::get_mut_from_ctx(self).get_mut::<RawBody>() {
+            raw_body.set(bytes);
+        }
+    }
+
     fn body_exists<T: DeserializeOwned + Send + Sync + 'static>(&self) -> bool {
         RequestState::get_from_ctx(self).exists::<RequestBody<T>>()
     }
 
-    // Different implementation needed because we can't produce a Request<Incoming>
-    #[cfg(test)]
-    async fn extract_body_bytes(&mut self) -> Result<Bytes, GetBodyError> {
-        Ok(RequestState::get_mut_from_ctx(self)
-            .remove_get::<Bytes>()
-            .unwrap_or_else(Bytes::new))
+    fn body_format(&self) -> Result<BodyFormat, GetBodyError> {
+        request_header(self, CONTENT_TYPE)
+            .and_then(BodyFormat::from_content_type)
+            .ok_or(GetBodyError::UnsupportedMediaType)
     }
 
-    #[cfg(not(test))]
-    async fn extract_body_bytes(&mut self) -> Result<Bytes, GetBodyError> {
-        let request = self.request_mut();
-        let incoming = request.body_mut();
+    async fn max_body_size(&self) -> Option<usize> {
+        max_body_size(GlobalState::get_from_ctx(self)).await
+    }
+
+    async fn extract_body_bytes_with_limit(
+        &mut self,
+        max_size: Option<usize>,
+    ) -> Result<Bytes, GetBodyError> {
+        // reject before reading anything if the announced size is too large
+        let content_length = request_header(self, CONTENT_LENGTH)
+            .and_then(|content_length| content_length.parse::<usize>().ok());
+        if let Some(content_length) = content_length {
+            check_body_size(content_length, max_size)?;
+        }
+
+        let incoming = request_body_mut(self);
         let mut collected_bytes = Vec::new();
         while let Some(next_frame) = incoming.frame().await {
             let next_frame = next_frame?
                 .into_data()
                 .map_err(|_| GetBodyError::FailedFrame)?;
 
+            check_body_size(collected_bytes.len() + next_frame.len(), max_size)?;
             collected_bytes.extend(next_frame);
         }
         Ok(collected_bytes.into())
@@ -62,6 +95,22 @@ impl ContextCreateBodyExt for HttpRequestContext {
 }
 
 impl ContextGetBodyExt for HttpRequestContext {
+    async fn raw_body(&mut self) -> Result<&Bytes, GetBodyError> {
+        self.keep_raw_body();
+
+        if self.cached_raw_body().is_none() {
+            if self.is_body_parsed() {
+                return Err(GetBodyError::AlreadyParsed);
+            }
+
+            let incoming_bytes = self.extract_body_bytes().await?;
+            self.mark_body_parsed();
+            self.cache_raw_body(incoming_bytes);
+        }
+
+        Ok(self.cached_raw_body().unwrap()) // has just been cached
+    }
+
     async fn body<T: DeserializeOwned + Send + Sync + 'static>(
         &mut self,
     ) -> Result<&T, GetBodyError> {
@@ -1,13 +1,23 @@
-use hyper::body::Bytes;
+use http::{
+    HeaderMap, HeaderValue,
+    header::{CONTENT_LENGTH, CONTENT_TYPE},
+};
+use hyper::{StatusCode, body::Bytes};
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    data::request_body::GetBodyError,
+    data::{
+        config::BodyConfig,
+        http_error::HttpError,
+        request_body::{ContextCreateBodyExt, GetBodyError},
+        response::Response,
+    },
     prelude::*,
     state::{
         context::HttpRequestContext, global_state::GlobalState, request_state::RequestState,
         session_state::SessionState,
     },
+    test_util::TestBody,
 };
 
 #[test]
@@ -25,6 +35,23 @@ struct Person {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct UnusedType;
 
+fn create_context(
+    global_state: &GlobalState,
+    session_state: &SessionState,
+    content_type: Option<&'static str>,
+    body: impl Into<Bytes>,
+) -> HttpRequestContext {
+    let mut request_state = RequestState::default();
+    request_state.insert(TestBody::from(body.into()));
+    if let Some(content_type) = content_type {
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static(content_type));
+        request_state.insert(headers);
+    }
+
+    HttpRequestContext::from_states(global_state.clone(), session_state.clone(), request_state)
+}
+
 async fn run_test() {
     let global_state = GlobalState::default();
     let session_state = SessionState::default();
@@ -34,13 +61,11 @@ async fn run_test() {
             name: "Franz".to_string(),
             age: 81,
         };
-        let mut request_state = RequestState::default();
-        request_state.insert(Bytes::from(serde_json::to_vec(&person).unwrap()));
-
-        let mut context = HttpRequestContext::from_states(
-            global_state.clone(),
-            session_state.clone(),
-            request_state,
+        let mut context = create_context(
+            &global_state,
+            &session_state,
+            Some("application/json"),
+            serde_json::to_vec(&person).unwrap(),
         );
 
         assert_eq!(context.body::<Person>().await.unwrap(), &person);
@@ -56,4 +81,168 @@ async fn run_test() {
             Err(GetBodyError::AlreadyParsed)
         ));
     }
+
+    // body size limits
+    {
+        let global_state = GlobalState::default();
+        global_state.insert(BodyConfig { max_size: Some(8) }).await;
+        let create_context = || {
+            let mut request_state = RequestState::default();
+            request_state.insert(TestBody::from(Bytes::from_static(b"0123456789")));
+            HttpRequestContext::from_states(
+                global_state.clone(),
+                session_state.clone(),
+                request_state,
+            )
+        };
+
+        let mut context = create_context();
+        assert_eq!(context.max_body_size().await, Some(8));
+        let err = context.body::<u64>().await.unwrap_err();
+        assert!(matches!(err, GetBodyError::TooLarge(8)));
+        let response: Response = HttpError::from(err).into();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        let mut context = create_context();
+        assert!(matches!(
+            context.extract_body_bytes_with_limit(Some(9)).await,
+            Err(GetBodyError::TooLarge(9))
+        ));
+
+        let mut context = create_context();
+        assert_eq!(
+            context
+                .extract_body_bytes_with_limit(Some(10))
+                .await
+                .unwrap(),
+            "0123456789"
+        );
+
+        let mut context = create_context();
+        assert_eq!(
+            context.extract_body_bytes_with_limit(None).await.unwrap(),
+            "0123456789"
+        );
+
+        // rejected by the announced size without reading the body
+        let mut context = create_context();
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_LENGTH, HeaderValue::from_static("100"));
+        RequestState::get_mut_from_ctx(&mut context).insert(headers);
+        assert!(matches!(
+            context.extract_body_bytes_with_limit(Some(10)).await,
+            Err(GetBodyError::TooLarge(10))
+        ));
+    }
+
+    // decoding depends on the content type
+    {
+        let person = Person {
+            name: "Franz".to_string(),
+            age: 81,
+        };
+
+        let mut context = create_context(
+            &global_state,
+            &session_state,
+            Some("application/problem+json; charset=utf-8"),
+            serde_json::to_vec(&person).unwrap(),
+        );
+        assert_eq!(context.body::<Person>().await.unwrap(), &person);
+
+        #[cfg(feature = "form")]
+        {
+            let mut context = create_context(
+                &global_state,
+                &session_state,
+                Some("Application/X-WWW-Form-Urlencoded"),
+                "name=Franz&age=81",
+            );
+            assert_eq!(context.body::<Person>().await.unwrap(), &person);
+        }
+
+        #[cfg(feature = "msgpack")]
+        {
+            let mut context = create_context(
+                &global_state,
+                &session_state,
+                Some("application/msgpack"),
+                rmp_serde::to_vec(&person).unwrap(),
+            );
+            assert_eq!(context.body::<Person>().await.unwrap(), &person);
+        }
+
+        #[cfg(feature = "cbor")]
+        {
+            let mut cbor = Vec::new();
+            ciborium::into_writer(&person, &mut cbor).unwrap();
+            let mut context = create_context(
+                &global_state,
+                &session_state,
+                Some("application/cbor"),
+                cbor,
+            );
+            assert_eq!(context.body::<Person>().await.unwrap(), &person);
+        }
+
+        for content_type in [None, Some("text/plain")] {
+            let mut context = create_context(
+                &global_state,
+                &session_state,
+                content_type,
+                serde_json::to_vec(&person).unwrap(),
+            );
+            let err = context.body::<Person>().await.unwrap_err();
+            assert!(matches!(err, GetBodyError::UnsupportedMediaType));
+            let response: Response = HttpError::from(err).into();
+            assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        }
+    }
+
+    // the raw body can be kept to decode it multiple times
+    {
+        let person = Person {
+            name: "Franz".to_string(),
+            age: 81,
+        };
+        let person_json = serde_json::to_vec(&person).unwrap();
+
+        let mut context = create_context(
+            &global_state,
+            &session_state,
+            Some("application/json"),
+            person_json.clone(),
+        );
+        assert_eq!(context.raw_body().await.unwrap(), &person_json);
+        assert_eq!(context.body::<Person>().await.unwrap(), &person);
+        assert_eq!(
+            context.body::<serde_json::Value>().await.unwrap()["name"],
+            "Franz"
+        );
+        assert_eq!(context.raw_body().await.unwrap(), &person_json);
+
+        let mut context = create_context(
+            &global_state,
+            &session_state,
+            Some("application/json"),
+            person_json.clone(),
+        );
+        context.keep_raw_body();
+        assert_eq!(context.remove_body::<Person>().await.unwrap(), person);
+        assert_eq!(context.body::<Person>().await.unwrap(), &person);
+        assert_eq!(context.raw_body().await.unwrap(), &person_json);
+
+        // without keeping it, the raw body is gone after parsing
+        let mut context = create_context(
+            &global_state,
+            &session_state,
+            Some("application/json"),
+            person_json,
+        );
+        assert_eq!(context.body::<Person>().await.unwrap(), &person);
+        assert!(matches!(
+            context.raw_body().await,
+            Err(GetBodyError::AlreadyParsed)
+        ));
+    }
 }
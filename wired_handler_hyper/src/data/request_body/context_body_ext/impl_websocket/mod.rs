@@ -1,14 +1,19 @@
 use hyper::body::Bytes;
 use serde::de::DeserializeOwned;
 
-use super::ContextCreateBodyExt;
+use super::{ContextCreateBodyExt, check_body_size, max_body_size};
 use crate::{
-    data::request_body::{
-        data::{RequestBody, RequestBodyParsed},
-        error::GetBodyError,
+    data::{
+        body_format::BodyFormat,
+        request_body::{
+            data::{RawBody, RequestBody, RequestBodyParsed},
+            error::GetBodyError,
+        },
     },
     prelude::*,
-    state::{context::WebsocketRequestContext, request_state::RequestState},
+    state::{
+        context::WebsocketRequestContext, global_state::GlobalState, request_state::RequestState,
+    },
 };
 
 impl ContextCreateBodyExt for WebsocketRequestContext {
@@ -20,14 +25,45 @@ impl ContextCreateBodyExt for WebsocketRequestContext {
         RequestState::get_mut_from_ctx(self).insert(RequestBodyParsed);
     }
 
+    fn keep_raw_body(&mut self) {
+        let request_state = RequestState::get_mut_from_ctx(self);
+        if !request_state.exists::<RawBody>() {
+            request_state.insert(RawBody::default());
+        }
+    }
+
+    fn cached_raw_body(&self) -> Option<&Bytes> {
+        RequestState::get_from_ctx(self).get::<RawBody>()?.get()
+    }
+
+    fn cache_raw_body(&mut self, bytes: Bytes) {
+        if let Some(raw_body) = RequestState::get_mut_from_ctx(self).get_mut::<RawBody>() {
+            raw_body.set(bytes);
+        }
+    }
+
     fn body_exists<T: DeserializeOwned + Send + Sync + 'static>(&self) -> bool {
         RequestState::get_from_ctx(self).exists::<RequestBody<T>>()
     }
 
-    async fn extract_body_bytes(&mut self) -> Result<Bytes, GetBodyError> {
+    /// Messages are always JSON
+    fn body_format(&self) -> Result<BodyFormat, GetBodyError> {
+        Ok(BodyFormat::Json)
+    }
+
+    async fn max_body_size(&self) -> Option<usize> {
+        max_body_size(GlobalState::get_from_ctx(self)).await
+    }
+
+    async fn extract_body_bytes_with_limit(
+        &mut self,
+        max_size: Option<usize>,
+    ) -> Result<Bytes, GetBodyError> {
         use hyper_tungstenite::tungstenite::Message;
 
         let message = self.message_mut();
+        check_body_size(message.len(), max_size)?;
+
         let collected_bytes = match message {
             Message::Text(data) => Bytes::from(std::mem::take(data)),
             Message::Binary(data) => std::mem::take(data),
@@ -49,6 +85,22 @@ impl ContextCreateBodyExt for WebsocketRequestContext {
 }
 
 impl ContextGetBodyExt for WebsocketRequestContext {
+    async fn raw_body(&mut self) -> Result<&Bytes, GetBodyError> {
+        self.keep_raw_body();
+
+        if self.cached_raw_body().is_none() {
+            if self.is_body_parsed() {
+                return Err(GetBodyError::AlreadyParsed);
+            }
+
+            let incoming_bytes = self.extract_body_bytes().await?;
+            self.mark_body_parsed();
+            self.cache_raw_body(incoming_bytes);
+        }
+
+        Ok(self.cached_raw_body().unwrap()) // has just been cached
+    }
+
     async fn body<T: DeserializeOwned + Send + Sync + 'static>(
         &mut self,
     ) -> Result<&T, GetBodyError> {
@@ -3,7 +3,7 @@ use hyper_tungstenite::tungstenite::Message;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    data::request_body::GetBodyError,
+    data::{config::BodyConfig, request_body::GetBodyError},
     prelude::*,
     state::{
         connection_state::ConnectionState, context::WebsocketRequestContext,
@@ -163,4 +163,53 @@ async fn run_test() {
             Err(GetBodyError::InvalidMessageType)
         ));
     }
+
+    // messages over the limit are rejected
+    {
+        let global_state = GlobalState::default();
+        global_state.insert(BodyConfig { max_size: Some(8) }).await;
+        let mut request_state = RequestState::default();
+        request_state.insert(Message::text("\"0123456789\""));
+
+        let mut context = WebsocketRequestContext::from_states(
+            global_state,
+            session_state.clone(),
+            connection_state.clone(),
+            request_state,
+        );
+
+        assert!(matches!(
+            context.body::<String>().await,
+            Err(GetBodyError::TooLarge(8))
+        ));
+    }
+
+    // the raw message can be kept to decode it multiple times
+    {
+        let mut request_state = RequestState::default();
+        request_state.insert(Message::text("{\"name\":\"Franz\",\"age\":81}"));
+
+        let mut context = WebsocketRequestContext::from_states(
+            global_state.clone(),
+            session_state.clone(),
+            connection_state.clone(),
+            request_state,
+        );
+
+        assert_eq!(
+            context.raw_body().await.unwrap(),
+            "{\"name\":\"Franz\",\"age\":81}"
+        );
+        assert_eq!(
+            context.body::<Person>().await.unwrap(),
+            &Person {
+                name: "Franz".to_string(),
+                age: 81,
+            }
+        );
+        assert_eq!(
+            context.body::<serde_json::Value>().await.unwrap()["age"],
+            81
+        );
+    }
 }
@@ -4,6 +4,11 @@ use hyper::body::Bytes;
 use serde::de::DeserializeOwned;
 
 use super::error::GetBodyError;
+use crate::{
+    data::{body_format::BodyFormat, config::BodyConfig},
+    prelude::*,
+    state::global_state::GlobalState,
+};
 
 mod impl_http;
 #[cfg(feature = "websocket")]
@@ -20,32 +25,73 @@ pub trait ContextCreateBodyExt {
     /// Marks that the request's body has been parsed
     fn mark_body_parsed(&mut self);
 
-    /// Returns all bytes of the body data, removing them from the request
-    fn extract_body_bytes(&mut self) -> impl Future<Output = Result<Bytes, GetBodyError>>;
+    /// Keeps the raw bytes once the body is read, so it can be decoded into multiple types and accessed by `raw_body`
+    fn keep_raw_body(&mut self);
+
+    /// Returns the raw bytes of the body if they have been kept
+    fn cached_raw_body(&self) -> Option<&Bytes>;
+
+    /// Caches the raw bytes of the body if `keep_raw_body` has been called
+    fn cache_raw_body(&mut self, bytes: Bytes);
+
+    /// Maximum size of the body in bytes, configured by the `BodyConfig` in the `GlobalState`. The default is used if there is none
+    fn max_body_size(&self) -> impl Future<Output = Option<usize>>;
+
+    /// Returns all bytes of the body data, removing them from the request.
+    /// Fails with `GetBodyError::TooLarge` if the body is larger than `max_size`
+    fn extract_body_bytes_with_limit(
+        &mut self,
+        max_size: Option<usize>,
+    ) -> impl Future<Output = Result<Bytes, GetBodyError>>;
+
+    /// Returns all bytes of the body data, removing them from the request.
+    /// Fails with `GetBodyError::TooLarge` if the body is larger than `max_body_size`
+    fn extract_body_bytes(&mut self) -> impl Future<Output = Result<Bytes, GetBodyError>> {
+        async {
+            let max_size = self.max_body_size().await;
+            self.extract_body_bytes_with_limit(max_size).await
+        }
+    }
 
     /// Creates the body from the context
     fn create_body<T: DeserializeOwned + Send + Sync + 'static>(
         &mut self,
     ) -> impl Future<Output = Result<T, GetBodyError>> {
         async {
-            let incoming_bytes = self.extract_body_bytes().await?;
+            if let Some(raw_body) = self.cached_raw_body().cloned() {
+                return self.decode_data(&raw_body);
+            }
 
-            if incoming_bytes.is_empty() && self.is_body_parsed() {
+            // the rest of a partially read body can't be decoded
+            if self.is_body_parsed() {
                 return Err(GetBodyError::AlreadyParsed);
             }
 
+            let incoming_bytes = self.extract_body_bytes().await?;
             self.mark_body_parsed();
+            self.cache_raw_body(incoming_bytes.clone());
 
             self.decode_data(&incoming_bytes)
         }
     }
 
-    /// Turns the bytes into `T`
+    /// Format of the body, fails with `GetBodyError::UnsupportedMediaType` if it can't be decoded
+    fn body_format(&self) -> Result<BodyFormat, GetBodyError>;
+
+    /// Turns the bytes into `T`, decoding them according to `body_format`
     fn decode_data<T: DeserializeOwned + Send + Sync + 'static>(
         &mut self,
         bytes_to_decode: &[u8],
     ) -> Result<T, GetBodyError> {
-        Ok(serde_json::from_slice(bytes_to_decode)?)
+        match self.body_format()? {
+            BodyFormat::Json => Ok(serde_json::from_slice(bytes_to_decode)?),
+            #[cfg(feature = "form")]
+            BodyFormat::Form => Ok(serde_html_form::from_bytes(bytes_to_decode)?),
+            #[cfg(feature = "msgpack")]
+            BodyFormat::MessagePack => Ok(rmp_serde::from_slice(bytes_to_decode)?),
+            #[cfg(feature = "cbor")]
+            BodyFormat::Cbor => Ok(ciborium::from_reader(bytes_to_decode)?),
+        }
     }
 
     /// Inserts the body into the context
@@ -54,18 +100,42 @@ pub trait ContextCreateBodyExt {
     ) -> impl Future<Output = Result<(), GetBodyError>>;
 }
 
+/// Returns the maximum body size of the `BodyConfig` in the `global_state` or the default if there is none
+async fn max_body_size(global_state: &GlobalState) -> Option<usize> {
+    global_state
+        .get_cloned::<BodyConfig>()
+        .await
+        .unwrap_or_default()
+        .max_size
+}
+
+/// Fails with `GetBodyError::TooLarge` if `size` exceeds `max_size`
+fn check_body_size(size: usize, max_size: Option<usize>) -> Result<(), GetBodyError> {
+    match max_size {
+        Some(max_size) if size > max_size => Err(GetBodyError::TooLarge(max_size)),
+        _ => Ok(()),
+    }
+}
+
 /// Get a decoded body from an `HttpRequestContext`
 pub trait ContextGetBodyExt: ContextCreateBodyExt {
+    /// Reads and returns the raw bytes of the body, keeping them like `keep_raw_body`.
+    ///
+    /// Fails if the body has been parsed before without keeping the raw bytes
+    fn raw_body(&mut self) -> impl Future<Output = Result<&Bytes, GetBodyError>>;
+
     /// Parses and returns a reference to the body. The result is cached.
     ///
-    /// The parsing can only be done once. Trying to get a different body type from the same request will result in an error
+    /// The parsing can only be done once, unless `keep_raw_body` has been called before.
+    /// Otherwise trying to get a different body type from the same request will result in an error
     fn body<T: DeserializeOwned + Send + Sync + 'static>(
         &mut self,
     ) -> impl Future<Output = Result<&T, GetBodyError>>;
 
     /// Parses and returns a mutable reference to the body. The result is cached.
     ///
-    /// The parsing can only be done once. Trying to get a different body type from the same request will result in an error
+    /// The parsing can only be done once, unless `keep_raw_body` has been called before.
+    /// Otherwise trying to get a different body type from the same request will result in an error
     fn body_mut<T: DeserializeOwned + Send + Sync + 'static>(
         &mut self,
     ) -> impl Future<Output = Result<&mut T, GetBodyError>>;
@@ -1,3 +1,4 @@
+use hyper::body::Bytes;
 use serde::de::DeserializeOwned;
 
 /// For storing the body in the `RequestState`
@@ -8,6 +9,20 @@ pub(super) struct RequestBody<T: DeserializeOwned>(T);
 #[derive(Debug)]
 pub(super) struct RequestBodyParsed;
 
+/// For keeping the raw bytes of the body in the `RequestState`, inserted by `keep_raw_body`
+#[derive(Debug, Default)]
+pub(super) struct RawBody(Option<Bytes>);
+
+impl RawBody {
+    pub fn get(&self) -> Option<&Bytes> {
+        self.0.as_ref()
+    }
+
+    pub fn set(&mut self, bytes: Bytes) {
+        self.0 = Some(bytes);
+    }
+}
+
 impl<T: DeserializeOwned> RequestBody<T> {
     pub fn new(data: T) -> Self {
         Self(data)
@@ -9,16 +9,39 @@ pub enum GetBodyError {
     #[cfg(feature = "json")]
     /// JSON decode error
     Json(#[from] serde_json::error::Error),
+    #[cfg(feature = "form")]
+    /// Form decode error
+    Form(#[from] serde_html_form::de::Error),
+    #[cfg(feature = "msgpack")]
+    /// MessagePack decode error
+    MessagePack(#[from] rmp_serde::decode::Error),
+    #[cfg(feature = "cbor")]
+    /// CBOR decode error
+    Cbor(#[from] ciborium::de::Error<std::io::Error>),
     /// Hyper error
     Hyper(#[from] hyper::Error),
     /// Failed to decode frame
     #[error("invalid frame data")]
     FailedFrame,
     /// Body has already been parsed into a different type or removed
-    #[error("body has already been parsed into a different type or has been removed, it can only be parsed once since the original data is consumed")]
+    #[error(
+        "body has already been parsed into a different type or has been removed, it can only be parsed once since the original data is consumed"
+    )]
     AlreadyParsed,
     #[error("invalid message type")]
     InvalidMessageType,
+    /// Body is larger than the limit
+    #[error("body exceeds the limit of {0} bytes")]
+    TooLarge(usize),
+    #[cfg(feature = "multipart")]
+    /// Invalid multipart body
+    Multipart(multer::Error),
+    #[cfg(feature = "multipart")]
+    /// Failed to write an uploaded file
+    Io(#[from] std::io::Error),
+    /// `Content-Type` is missing or not supported
+    #[error("missing or unsupported content type")]
+    UnsupportedMediaType,
 }
 
 impl From<GetBodyError> for HttpError {
@@ -26,6 +49,14 @@ impl From<GetBodyError> for HttpError {
         match value {
             #[cfg(feature = "json")]
             GetBodyError::Json(json_error) => Self::bad_request(json_error.to_string()),
+            #[cfg(feature = "form")]
+            GetBodyError::Form(form_error) => Self::bad_request(form_error.to_string()),
+            #[cfg(feature = "msgpack")]
+            GetBodyError::MessagePack(msgpack_error) => {
+                Self::bad_request(msgpack_error.to_string())
+            }
+            #[cfg(feature = "cbor")]
+            GetBodyError::Cbor(cbor_error) => Self::bad_request(cbor_error.to_string()),
             GetBodyError::Hyper(hyper_error) => {
                 tracing::debug!("Hyper error handling body frame: {:?}", hyper_error);
                 Self::internal_server_error("Hyper error handling body")
@@ -37,6 +68,33 @@ impl From<GetBodyError> for HttpError {
             GetBodyError::InvalidMessageType => {
                 Self::internal_server_error("internal server error")
             }
+            GetBodyError::TooLarge(max_size) => {
+                Self::payload_too_large(format!("body exceeds the limit of {max_size} bytes"))
+            }
+            #[cfg(feature = "multipart")]
+            GetBodyError::Multipart(multipart_error) => {
+                Self::bad_request(multipart_error.to_string())
+            }
+            #[cfg(feature = "multipart")]
+            GetBodyError::Io(io_error) => {
+                tracing::warn!("failed to write uploaded file: {io_error}");
+                Self::internal_server_error("internal server error")
+            }
+            GetBodyError::UnsupportedMediaType => {
+                Self::unsupported_media_type("missing or unsupported content type")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "multipart")]
+impl From<multer::Error> for GetBodyError {
+    fn from(value: multer::Error) -> Self {
+        match value {
+            multer::Error::FieldSizeExceeded { limit, .. }
+            | multer::Error::StreamSizeExceeded { limit } => Self::TooLarge(limit as usize),
+            multer::Error::NoMultipart | multer::Error::NoBoundary => Self::UnsupportedMediaType,
+            _ => Self::Multipart(value),
         }
     }
 }
@@ -1,6 +1,8 @@
+pub use body_stream::*;
 pub use context_body_ext::*;
 pub use error::*;
 
+mod body_stream;
 mod context_body_ext;
 mod data;
 mod error;
@@ -0,0 +1,50 @@
+use std::sync::Arc;
+
+use hyper::body::Bytes;
+
+/// Error of a `ResponseEncoder`
+pub type EncodeError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Serializes response bodies into a format that isn't supported by default
+pub trait ResponseEncoder: Send + Sync + 'static {
+    /// `Content-Type` of the encoded body
+    fn content_type(&self) -> &str;
+
+    /// Serializes `data`
+    fn encode(&self, data: &dyn erased_serde::Serialize) -> Result<Bytes, EncodeError>;
+}
+
+/// Additional `ResponseEncoder`s used by `parse_body`, stored in the `GlobalState`.
+/// An encoder replaces the built-in format with the same `Content-Type`
+#[derive(Clone, Default)]
+pub struct ResponseEncoders(Vec<Arc<dyn ResponseEncoder>>);
+
+impl std::fmt::Debug for ResponseEncoders {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list()
+            .entries(self.0.iter().map(|encoder| encoder.content_type()))
+            .finish()
+    }
+}
+
+impl ResponseEncoders {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `encoder`, it is preferred over the encoders added after it
+    pub fn register(&mut self, encoder: impl ResponseEncoder) -> &mut Self {
+        self.0.push(Arc::new(encoder));
+        self
+    }
+
+    /// Adds `encoder` like `register`
+    pub fn with(mut self, encoder: impl ResponseEncoder) -> Self {
+        self.register(encoder);
+        self
+    }
+
+    pub(super) fn iter(&self) -> impl Iterator<Item = &Arc<dyn ResponseEncoder>> {
+        self.0.iter()
+    }
+}
@@ -0,0 +1,300 @@
+use std::{future::Future, sync::Arc};
+
+use futures::{Stream, StreamExt, TryStreamExt};
+use http::header::ACCEPT;
+use http_body_util::{BodyExt, Empty, Full, StreamBody, combinators::BoxBody};
+use hyper::body::{Bytes, Frame};
+use serde::Serialize;
+use sync_wrapper::SyncStream;
+use thiserror::Error;
+use tokio::io::AsyncRead;
+use tokio_util::io::ReaderStream;
+
+pub use encoder::*;
+use negotiation::negotiate;
+
+use crate::{
+    data::{
+        body_format::BodyFormat, http_error::HttpError, request::request_headers,
+        response::Response,
+    },
+    prelude::*,
+    state::{context::HttpRequestContext, global_state::GlobalState},
+};
+
+mod encoder;
+mod negotiation;
+#[cfg(test)]
+mod test;
+
+/// Error of a streamed `ResponseBody`, aborts the response
+pub type ResponseBodyError = Box<dyn std::error::Error + Send + Sync>;
+
+pub type ResponseBody = BoxBody<hyper::body::Bytes, ResponseBodyError>;
+
+/// For quickly creating a `ResponseBody`
+pub trait ResponseBodyExt {
+    fn from_bytes(bytes_body: impl Into<Bytes>) -> Self;
+    fn empty() -> Self;
+
+    /// Streams the chunks of `stream`, using chunked transfer encoding.
+    /// An error aborts the response, closing the connection
+    fn from_stream<S, E>(stream: S) -> Self
+    where
+        S: Stream<Item = Result<Bytes, E>> + Send + 'static,
+        E: Into<ResponseBodyError> + 'static;
+
+    /// Streams everything read from `reader` like `from_stream`
+    fn from_reader(reader: impl AsyncRead + Send + 'static) -> Self;
+
+    /// Streams the items of `stream` as a JSON array like `from_stream`, serializing one item at a time
+    fn from_json_array_stream<S, T, E>(stream: S) -> Self
+    where
+        S: Stream<Item = Result<T, E>> + Send + 'static,
+        T: Serialize,
+        E: Into<ResponseBodyError> + 'static;
+
+    /// Streams the items of `stream` as newline delimited JSON (NDJSON) like `from_stream`, serializing one item at a time
+    fn from_ndjson_stream<S, T, E>(stream: S) -> Self
+    where
+        S: Stream<Item = Result<T, E>> + Send + 'static,
+        T: Serialize,
+        E: Into<ResponseBodyError> + 'static;
+}
+
+/// Serializes `item` as JSON after `separator`
+fn serialize_json_item<T: Serialize, E: Into<ResponseBodyError>>(
+    separator: &[u8],
+    item: Result<T, E>,
+) -> Result<Bytes, ResponseBodyError> {
+    let item = item.map_err(Into::into)?;
+    let mut bytes = separator.to_vec();
+    serde_json::to_writer(&mut bytes, &item)?;
+
+    Ok(bytes.into())
+}
+
+impl ResponseBodyExt for ResponseBody {
+    fn from_bytes(bytes_body: impl Into<Bytes>) -> Self {
+        Full::new(bytes_body.into())
+            .map_err(|never| match never {})
+            .boxed()
+    }
+
+    fn empty() -> Self {
+        Empty::new().map_err(|never| match never {}).boxed()
+    }
+
+    fn from_stream<S, E>(stream: S) -> Self
+    where
+        S: Stream<Item = Result<Bytes, E>> + Send + 'static,
+        E: Into<ResponseBodyError> + 'static,
+    {
+        // the body has to be `Sync`, which the stream doesn't need to be since it's only accessed mutably
+        let frames = SyncStream::new(stream.map_ok(Frame::data).map_err(Into::into));
+
+        BodyExt::boxed(StreamBody::new(frames))
+    }
+
+    fn from_reader(reader: impl AsyncRead + Send + 'static) -> Self {
+        Self::from_stream(ReaderStream::new(reader))
+    }
+
+    fn from_json_array_stream<S, T, E>(stream: S) -> Self
+    where
+        S: Stream<Item = Result<T, E>> + Send + 'static,
+        T: Serialize,
+        E: Into<ResponseBodyError> + 'static,
+    {
+        let items = stream.enumerate().map(|(index, item)| {
+            let separator: &[u8] = if index == 0 { b"" } else { b"," };
+            serialize_json_item(separator, item)
+        });
+        let start = futures::stream::once(async { Ok(Bytes::from_static(b"[")) });
+        let end = futures::stream::once(async { Ok(Bytes::from_static(b"]")) });
+
+        Self::from_stream::<_, ResponseBodyError>(start.chain(items).chain(end))
+    }
+
+    fn from_ndjson_stream<S, T, E>(stream: S) -> Self
+    where
+        S: Stream<Item = Result<T, E>> + Send + 'static,
+        T: Serialize,
+        E: Into<ResponseBodyError> + 'static,
+    {
+        let items = stream.map(|item| {
+            let mut bytes = Vec::from(serialize_json_item(b"", item)?);
+            bytes.push(b'\n');
+            Ok::<_, ResponseBodyError>(Bytes::from(bytes))
+        });
+
+        Self::from_stream(items)
+    }
+}
+
+/// Lets you add a parsed body to a request builder
+pub trait ResponseBuilderParsedBodyExt {
+    /// Creates a `Response` by applying a `ParsedBody`
+    fn parsed_body(self, body: ParsedBody) -> Result<Response, http::Error>;
+}
+
+impl ResponseBuilderParsedBodyExt for http::response::Builder {
+    fn parsed_body(self, body: ParsedBody) -> Result<Response, http::Error> {
+        let response = self
+            .header("Content-Type", body.content_type.as_str())
+            .body(body.response_body)?;
+
+        Ok(response)
+    }
+}
+
+/// When parsing the body goes wrong
+#[derive(Debug, Error)]
+#[error("{0}")]
+pub enum ParseBodyError {
+    #[cfg(feature = "json")]
+    Json(#[from] serde_json::Error),
+    #[cfg(feature = "form")]
+    Form(#[from] serde_html_form::ser::Error),
+    #[cfg(feature = "msgpack")]
+    MessagePack(#[from] rmp_serde::encode::Error),
+    #[cfg(feature = "cbor")]
+    Cbor(#[from] ciborium::ser::Error<std::io::Error>),
+    /// Error of a `ResponseEncoder`
+    Encoder(EncodeError),
+    /// None of the formats is accepted by the `Accept` header
+    #[error("no acceptable format")]
+    NotAcceptable,
+}
+impl From<ParseBodyError> for HttpError {
+    fn from(value: ParseBodyError) -> Self {
+        if let ParseBodyError::NotAcceptable = value {
+            return Self::not_acceptable("no acceptable format");
+        }
+
+        tracing::debug!("parse error: {value}");
+        Self::internal_server_error("failed to parse response body")
+    }
+}
+
+/// A parsed body, containing the body and type information to be inserted into the Response
+#[derive(Debug)]
+pub struct ParsedBody {
+    response_body: ResponseBody,
+    content_type: String,
+}
+
+/// A format `parse_body` can serialize into
+enum ResponseFormat {
+    BuiltIn(BodyFormat),
+    Custom(Arc<dyn ResponseEncoder>),
+}
+
+impl ResponseFormat {
+    fn content_type(&self) -> &str {
+        match self {
+            Self::BuiltIn(body_format) => body_format.content_type(),
+            Self::Custom(encoder) => encoder.content_type(),
+        }
+    }
+
+    fn encode<T: Serialize>(&self, data: &T) -> Result<Bytes, ParseBodyError> {
+        let bytes = match self {
+            Self::BuiltIn(BodyFormat::Json) => serde_json::to_vec(data)?.into(),
+            #[cfg(feature = "form")]
+            Self::BuiltIn(BodyFormat::Form) => serde_html_form::to_string(data)?.into(),
+            #[cfg(feature = "msgpack")]
+            Self::BuiltIn(BodyFormat::MessagePack) => rmp_serde::to_vec_named(data)?.into(),
+            #[cfg(feature = "cbor")]
+            Self::BuiltIn(BodyFormat::Cbor) => {
+                let mut bytes = Vec::new();
+                ciborium::into_writer(data, &mut bytes)?;
+                bytes.into()
+            }
+            Self::Custom(encoder) => encoder.encode(data).map_err(ParseBodyError::Encoder)?,
+        };
+
+        Ok(bytes)
+    }
+}
+
+/// Whether two content types are the same, ignoring their parameters
+fn is_same_content_type(a: &str, b: &str) -> bool {
+    fn essence(content_type: &str) -> &str {
+        content_type.split(';').next().unwrap_or_default().trim()
+    }
+
+    essence(a).eq_ignore_ascii_case(essence(b))
+}
+
+/// The built-in formats in order of preference, replaced by the `encoders` with the same `Content-Type`, followed by the other `encoders`
+fn response_formats(encoders: &ResponseEncoders) -> Vec<ResponseFormat> {
+    let mut response_formats: Vec<_> = BodyFormat::all()
+        .into_iter()
+        .map(|body_format| {
+            encoders
+                .iter()
+                .find(|encoder| {
+                    is_same_content_type(encoder.content_type(), body_format.content_type())
+                })
+                .map(|encoder| ResponseFormat::Custom(encoder.clone()))
+                .unwrap_or(ResponseFormat::BuiltIn(body_format))
+        })
+        .collect();
+    for encoder in encoders.iter() {
+        if !response_formats.iter().any(|response_format| {
+            is_same_content_type(response_format.content_type(), encoder.content_type())
+        }) {
+            response_formats.push(ResponseFormat::Custom(encoder.clone()));
+        }
+    }
+
+    response_formats
+}
+
+/// Returns the `Accept` headers of the request, joined by `,`
+fn accept(ctx: &HttpRequestContext) -> Option<String> {
+    let accept: Vec<_> = request_headers(ctx)?
+        .get_all(ACCEPT)
+        .into_iter()
+        .filter_map(|header_value| header_value.to_str().ok())
+        .collect();
+
+    (!accept.is_empty()).then(|| accept.join(","))
+}
+
+/// For parsing a body from data
+pub trait CtxParseBodyExt {
+    /// Parses `data` into a `ParsedBody`, using the format the request's `Accept` header prefers.
+    /// Supports JSON, form encoding, MessagePack and CBOR if enabled, and the `ResponseEncoders` in the `GlobalState`.
+    /// JSON is used if there is no `Accept` header.
+    ///
+    /// Fails with `ParseBodyError::NotAcceptable` if no format is accepted
+    fn parse_body<T: Serialize>(
+        &self,
+        data: T,
+    ) -> impl Future<Output = Result<ParsedBody, ParseBodyError>>;
+}
+
+impl CtxParseBodyExt for HttpRequestContext {
+    async fn parse_body<T: Serialize>(&self, data: T) -> Result<ParsedBody, ParseBodyError> {
+        let encoders = GlobalState::get_from_ctx(self)
+            .get_cloned::<ResponseEncoders>()
+            .await
+            .unwrap_or_default();
+        let response_formats = response_formats(&encoders);
+
+        let accept = accept(self);
+        let response_format = negotiate(
+            accept.as_deref(),
+            response_formats.iter().map(ResponseFormat::content_type),
+        )
+        .map(|index| &response_formats[index])
+        .ok_or(ParseBodyError::NotAcceptable)?;
+
+        Ok(ParsedBody {
+            response_body: ResponseBody::from_bytes(response_format.encode(&data)?),
+            content_type: response_format.content_type().to_string(),
+        })
+    }
+}
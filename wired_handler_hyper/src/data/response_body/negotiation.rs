@@ -0,0 +1,98 @@
+/// A media range of an `Accept` header, like `text/*;q=0.5`
+#[derive(Debug)]
+struct MediaRange<'a> {
+    main_type: &'a str,
+    sub_type: &'a str,
+    quality: f32,
+}
+
+impl MediaRange<'_> {
+    /// How specific the range is for `content_type`, `None` if it doesn't match
+    fn specificity(&self, main_type: &str, sub_type: &str) -> Option<u8> {
+        match (self.main_type, self.sub_type) {
+            ("*", "*") => Some(0),
+            (range_main_type, "*") if range_main_type.eq_ignore_ascii_case(main_type) => Some(1),
+            (range_main_type, range_sub_type)
+                if range_main_type.eq_ignore_ascii_case(main_type)
+                    && range_sub_type.eq_ignore_ascii_case(sub_type) =>
+            {
+                Some(2)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Splits a media type like `application/json; charset=utf-8` into `application` and `json`
+fn split_media_type(media_type: &str) -> Option<(&str, &str)> {
+    let essence = media_type.split(';').next()?.trim();
+    let (main_type, sub_type) = essence.split_once('/')?;
+
+    Some((main_type.trim(), sub_type.trim()))
+}
+
+/// Parses the media ranges of an `Accept` header, skipping invalid ones
+fn parse_accept(accept: &str) -> Vec<MediaRange<'_>> {
+    accept
+        .split(',')
+        .filter_map(|element| {
+            let (main_type, sub_type) = split_media_type(element)?;
+            let mut quality = 1.0;
+            for parameter in element.split(';').skip(1) {
+                if let Some((key, value)) = parameter.split_once('=')
+                    && key.trim().eq_ignore_ascii_case("q")
+                {
+                    quality = value.trim().parse::<f32>().ok()?.clamp(0.0, 1.0);
+                }
+            }
+
+            Some(MediaRange {
+                main_type,
+                sub_type,
+                quality,
+            })
+        })
+        .collect()
+}
+
+/// Quality of `content_type`, given by the most specific matching media range
+fn quality(media_ranges: &[MediaRange], content_type: &str) -> f32 {
+    let Some((main_type, sub_type)) = split_media_type(content_type) else {
+        return 0.0;
+    };
+
+    media_ranges
+        .iter()
+        .filter_map(|media_range| {
+            media_range
+                .specificity(main_type, sub_type)
+                .map(|specificity| (specificity, media_range.quality))
+        })
+        .max_by_key(|(specificity, _)| *specificity)
+        .map(|(_, quality)| quality)
+        .unwrap_or(0.0)
+}
+
+/// Returns the index of the content type accepted with the highest quality.
+/// Earlier content types are preferred if the quality is the same, the first one is used if there is no `Accept` header.
+/// `None` if nothing is acceptable
+pub(super) fn negotiate<'a>(
+    accept: Option<&str>,
+    content_types: impl IntoIterator<Item = &'a str>,
+) -> Option<usize> {
+    let mut content_types = content_types.into_iter();
+    let Some(accept) = accept.filter(|accept| !accept.trim().is_empty()) else {
+        return content_types.next().map(|_| 0);
+    };
+    let media_ranges = parse_accept(accept);
+
+    let mut best: Option<(usize, f32)> = None;
+    for (index, content_type) in content_types.enumerate() {
+        let quality = quality(&media_ranges, content_type);
+        if quality > 0.0 && best.is_none_or(|(_, best_quality)| quality > best_quality) {
+            best = Some((index, quality));
+        }
+    }
+
+    best.map(|(index, _)| index)
+}
@@ -0,0 +1,244 @@
+use futures::StreamExt;
+use http::{
+    HeaderMap, HeaderValue, StatusCode,
+    header::{ACCEPT, CONTENT_TYPE},
+};
+use http_body_util::BodyExt;
+use hyper::body::{Body, Bytes};
+use serde::Serialize;
+
+use super::{
+    EncodeError, ParseBodyError, ResponseBody, ResponseBodyError, ResponseEncoder, ResponseEncoders,
+};
+use crate::{
+    data::{http_error::HttpError, response::Response},
+    prelude::*,
+    state::{
+        context::HttpRequestContext, global_state::GlobalState, request_state::RequestState,
+        session_state::SessionState,
+    },
+};
+
+#[derive(Debug, Serialize)]
+struct Person {
+    name: String,
+    age: u8,
+}
+
+/// Encodes everything as JSON, served as plain text
+struct TextEncoder;
+
+impl ResponseEncoder for TextEncoder {
+    fn content_type(&self) -> &str {
+        "text/plain"
+    }
+
+    fn encode(&self, data: &dyn erased_serde::Serialize) -> Result<Bytes, EncodeError> {
+        Ok(serde_json::to_vec(data)?.into())
+    }
+}
+
+#[test]
+fn test() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    runtime.block_on(run_test());
+}
+
+#[test]
+fn test_streams() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    runtime.block_on(run_test_streams());
+}
+
+/// Parses a `Person` for a request with the `accept` header, returns the content type and body
+async fn parse(
+    global_state: &GlobalState,
+    accept: Option<&'static str>,
+) -> Result<(String, Bytes), ParseBodyError> {
+    let mut request_state = RequestState::default();
+    if let Some(accept) = accept {
+        let mut headers = HeaderMap::new();
+        headers.insert(ACCEPT, HeaderValue::from_static(accept));
+        request_state.insert(headers);
+    }
+    let ctx = HttpRequestContext::from_states(
+        global_state.clone(),
+        SessionState::default(),
+        request_state,
+    );
+
+    let parsed_body = ctx
+        .parse_body(Person {
+            name: "Franz".into(),
+            age: 81,
+        })
+        .await?;
+    let response = Response::builder().parsed_body(parsed_body).unwrap();
+    let content_type = response.headers()[CONTENT_TYPE]
+        .to_str()
+        .unwrap()
+        .to_string();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+
+    Ok((content_type, body))
+}
+
+async fn run_test() {
+    let global_state = GlobalState::default();
+    let json = Bytes::from_static(br#"{"name":"Franz","age":81}"#);
+
+    // JSON by default
+    for accept in [
+        None,
+        Some("*/*"),
+        Some("application/*"),
+        Some("text/html, */*;q=0.8"),
+    ] {
+        assert_eq!(
+            parse(&global_state, accept).await.unwrap(),
+            ("application/json".into(), json.clone())
+        );
+    }
+
+    // the most preferred format is used
+    #[cfg(feature = "form")]
+    {
+        assert_eq!(
+            parse(
+                &global_state,
+                Some("application/json;q=0.5, application/x-www-form-urlencoded")
+            )
+            .await
+            .unwrap(),
+            (
+                "application/x-www-form-urlencoded".into(),
+                Bytes::from_static(b"name=Franz&age=81")
+            )
+        );
+        assert_eq!(
+            parse(
+                &global_state,
+                Some("application/*;q=0.1, application/json;q=0")
+            )
+            .await
+            .unwrap()
+            .0,
+            "application/x-www-form-urlencoded"
+        );
+    }
+
+    #[cfg(feature = "msgpack")]
+    {
+        let (content_type, body) = parse(&global_state, Some("application/msgpack"))
+            .await
+            .unwrap();
+        assert_eq!(content_type, "application/msgpack");
+        assert_eq!(
+            body,
+            rmp_serde::to_vec_named(&Person {
+                name: "Franz".into(),
+                age: 81,
+            })
+            .unwrap()
+        );
+    }
+
+    #[cfg(feature = "cbor")]
+    assert_eq!(
+        parse(&global_state, Some("application/cbor"))
+            .await
+            .unwrap()
+            .0,
+        "application/cbor"
+    );
+
+    // nothing acceptable
+    let err = parse(&global_state, Some("text/plain")).await.unwrap_err();
+    assert!(matches!(err, ParseBodyError::NotAcceptable));
+    let response: Response = HttpError::from(err).into();
+    assert_eq!(response.status(), StatusCode::NOT_ACCEPTABLE);
+
+    // registered encoders are used as well
+    global_state
+        .insert(ResponseEncoders::new().with(TextEncoder))
+        .await;
+    assert_eq!(
+        parse(&global_state, Some("text/plain")).await.unwrap(),
+        ("text/plain".into(), json.clone())
+    );
+    assert_eq!(
+        parse(&global_state, None).await.unwrap().0,
+        "application/json"
+    );
+}
+
+async fn collect(response_body: ResponseBody) -> Result<Bytes, ResponseBodyError> {
+    // no known size, so chunked transfer encoding is used
+    assert_eq!(response_body.size_hint().exact(), None);
+
+    Ok(response_body.collect().await?.to_bytes())
+}
+
+fn people() -> impl futures::Stream<Item = Result<Person, std::io::Error>> {
+    futures::stream::iter(["Franz", "Sissi"].map(|name| {
+        Ok(Person {
+            name: name.into(),
+            age: 81,
+        })
+    }))
+}
+
+async fn run_test_streams() {
+    let chunks = futures::stream::iter(
+        ["a", "b", "c"].map(|chunk| Ok::<_, std::io::Error>(Bytes::from(chunk))),
+    );
+    assert_eq!(
+        collect(ResponseBody::from_stream(chunks)).await.unwrap(),
+        "abc"
+    );
+
+    let reader = std::io::Cursor::new(b"read".to_vec());
+    assert_eq!(
+        collect(ResponseBody::from_reader(reader)).await.unwrap(),
+        "read"
+    );
+
+    assert_eq!(
+        collect(ResponseBody::from_json_array_stream(people()))
+            .await
+            .unwrap(),
+        r#"[{"name":"Franz","age":81},{"name":"Sissi","age":81}]"#
+    );
+    assert_eq!(
+        collect(ResponseBody::from_json_array_stream(
+            futures::stream::empty::<Result<Person, std::io::Error>>()
+        ))
+        .await
+        .unwrap(),
+        "[]"
+    );
+
+    assert_eq!(
+        collect(ResponseBody::from_ndjson_stream(people()))
+            .await
+            .unwrap(),
+        "{\"name\":\"Franz\",\"age\":81}\n{\"name\":\"Sissi\",\"age\":81}\n"
+    );
+
+    // errors abort the body
+    let failing =
+        futures::stream::iter([Ok(Bytes::from("a")), Err(std::io::Error::other("failed"))]);
+    let err = collect(ResponseBody::from_stream(failing))
+        .await
+        .unwrap_err();
+    assert_eq!(err.to_string(), "failed");
+
+    let failing_items = people().chain(futures::stream::once(async {
+        Err(std::io::Error::other("failed"))
+    }));
+    assert!(
+        collect(ResponseBody::from_json_array_stream(failing_items))
+            .await
+            .is_err()
+    );
+}
@@ -0,0 +1,44 @@
+use serde::de::DeserializeOwned;
+
+use super::GetPathParamsError;
+use crate::{
+    prelude::*,
+    state::{context::HttpRequestContext, request_state::RequestState},
+};
+
+/// The parameters matched by a `Router`, percent-decoded
+#[derive(Debug, Clone, Default)]
+pub(super) struct PathParams(pub Vec<(String, String)>);
+
+/// For retrieving the parameters matched by a `Router`
+pub trait ContextPathParamsExt {
+    /// Returns the parameter `name`
+    fn path_param(&self, name: &str) -> Option<&str>;
+
+    /// Deserializes the parameters into `T`, like query parameters
+    fn path_params<T: DeserializeOwned>(&self) -> Result<T, GetPathParamsError>;
+}
+
+impl ContextPathParamsExt for HttpRequestContext {
+    fn path_param(&self, name: &str) -> Option<&str> {
+        let PathParams(params) = RequestState::get_from_ctx(self).get()?;
+
+        // the innermost router's parameter wins
+        params
+            .iter()
+            .rev()
+            .find(|(param_name, _)| param_name == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn path_params<T: DeserializeOwned>(&self) -> Result<T, GetPathParamsError> {
+        let params = RequestState::get_from_ctx(self)
+            .get::<PathParams>()
+            .map(|PathParams(params)| params.as_slice())
+            .unwrap_or_default();
+        // can't fail, it's a sequence of string pairs
+        let encoded = serde_html_form::to_string(params).unwrap_or_default();
+
+        Ok(serde_html_form::from_str(&encoded)?)
+    }
+}
@@ -0,0 +1,58 @@
+use std::{future::Future, ops::ControlFlow};
+
+use super::{context_path_params_ext::PathParams, context_url_for_ext::RouteNames, data::Router};
+use crate::{
+    data::{http_error::HttpError, path::RemainingPath},
+    prelude::*,
+    state::{context::HttpRequestContext, request_state::RequestState},
+};
+
+/// For routing requests with a `Router`
+pub trait ContextRouterExt {
+    /// Runs the handler of the route matching the `RemainingPath`, which is consumed.
+    /// Only the segments matched by a wildcard are left in the `RemainingPath`.
+    /// The matched parameters are available through `ContextPathParamsExt`,
+    /// the named routes of `router` through `ContextUrlForExt`.
+    /// Can be called again by the handler of a wildcard route to continue with another `Router`.
+    ///
+    /// Returns a not found error if no route matches
+    fn route(
+        &mut self,
+        router: &Router,
+    ) -> impl Future<Output = Result<ControlFlow<()>, HttpError>>;
+}
+
+impl ContextRouterExt for HttpRequestContext {
+    async fn route(&mut self, router: &Router) -> Result<ControlFlow<()>, HttpError> {
+        let segments: Vec<String> = self
+            .remaining_path_mut()
+            .by_ref()
+            .filter(|segment| !segment.is_empty())
+            .collect();
+
+        let mut params = Vec::new();
+        let Some((handler, wildcard_len)) = router.root.find(&segments, &mut params) else {
+            return Err(HttpError::not_found("not found"));
+        };
+
+        let request_state = RequestState::get_mut_from_ctx(self);
+        let (matched_segments, wildcard_segments) =
+            segments.split_at(segments.len() - wildcard_len.unwrap_or_default());
+        let remaining_path = match wildcard_len {
+            Some(_) => RemainingPath::from_segments(wildcard_segments),
+            None => RemainingPath::empty(),
+        };
+        request_state.insert(remaining_path);
+        // names of outer routers are kept
+        request_state
+            .get_mut_or_insert_default::<RouteNames>()
+            .push(router.names.clone(), matched_segments);
+        match request_state.get_mut::<PathParams>() {
+            // parameters of outer routers are kept
+            Some(PathParams(existing_params)) => existing_params.extend(params),
+            None => request_state.insert(PathParams(params)),
+        }
+
+        handler(self).await
+    }
+}
@@ -0,0 +1,107 @@
+use std::{collections::HashMap, future::Future, sync::Arc};
+
+use serde::Serialize;
+
+use super::{
+    UrlForError,
+    trie::PatternSegment,
+    url::{build_url, encode_segment},
+};
+use crate::{
+    data::{config::RouterConfig, mount::MountPrefix},
+    prelude::*,
+    state::{context::HttpRequestContext, global_state::GlobalState, request_state::RequestState},
+};
+
+/// The named routes of the `Router`s which have routed the request, so `route` can be called by the handler of another route
+#[derive(Debug, Clone, Default)]
+pub(super) struct RouteNames {
+    /// The innermost last
+    routers: Vec<RouterNames>,
+    /// The path matched by all `Router`s, percent-encoded
+    matched_path: String,
+}
+
+/// The named routes of a `Router`
+#[derive(Debug, Clone)]
+struct RouterNames {
+    /// The path matched before the `Router`, percent-encoded
+    prefix: String,
+    names: Arc<HashMap<String, Vec<PatternSegment>>>,
+}
+
+impl RouteNames {
+    /// Adds the named routes of a `Router` which has matched `matched_segments`
+    pub(super) fn push(
+        &mut self,
+        names: Arc<HashMap<String, Vec<PatternSegment>>>,
+        matched_segments: &[String],
+    ) {
+        self.routers.push(RouterNames {
+            prefix: self.matched_path.clone(),
+            names,
+        });
+        for segment in matched_segments {
+            self.matched_path.push('/');
+            self.matched_path.push_str(&encode_segment(segment));
+        }
+    }
+
+    /// Returns the pattern of the route `name` with the path matched before its `Router`, inner routes take precedence
+    fn get(&self, name: &str) -> Option<(&str, &[PatternSegment])> {
+        self.routers.iter().rev().find_map(|router_names| {
+            router_names
+                .names
+                .get(name)
+                .map(|pattern| (router_names.prefix.as_str(), pattern.as_slice()))
+        })
+    }
+}
+
+/// Returns the `mount_prefix` of the `RouterConfig` from the `GlobalState` without a trailing slash
+async fn mount_prefix(global_state: &GlobalState) -> String {
+    let router_config = global_state
+        .get_cloned::<RouterConfig>()
+        .await
+        .unwrap_or_default();
+    let mount_prefix = router_config.mount_prefix.trim_matches('/');
+
+    match mount_prefix {
+        "" => String::new(),
+        mount_prefix => format!("/{mount_prefix}"),
+    }
+}
+
+/// For generating URLs of named routes
+pub trait ContextUrlForExt {
+    /// Returns the URL of the route `name` of the `Router`s which have routed the request,
+    /// prefixed with the `mount_prefix` of the `RouterConfig` in the `GlobalState`,
+    /// the prefixes of the sub-applications it is mounted in (see `ContextMountExt`)
+    /// and the path matched by outer `Router`s whose handlers have called `route`.
+    /// Its parameters are filled from `params`, the other parameters become the query string
+    fn url_for(
+        &self,
+        name: &str,
+        params: &impl Serialize,
+    ) -> impl Future<Output = Result<String, UrlForError>>;
+}
+
+impl ContextUrlForExt for HttpRequestContext {
+    async fn url_for(&self, name: &str, params: &impl Serialize) -> Result<String, UrlForError> {
+        let (router_prefix, pattern) = RequestState::get_from_ctx(self)
+            .get::<RouteNames>()
+            .and_then(|route_names| route_names.get(name))
+            .ok_or_else(|| UrlForError::UnknownRoute(name.to_string()))?;
+        let path = build_url(name, pattern, params)?;
+
+        let sub_application_prefix = RequestState::get_from_ctx(self)
+            .get::<MountPrefix>()
+            .map(|MountPrefix(mount_prefix)| mount_prefix.as_str())
+            .unwrap_or_default();
+
+        Ok(mount_prefix(GlobalState::get_from_ctx(self)).await
+            + sub_application_prefix
+            + router_prefix
+            + &path)
+    }
+}
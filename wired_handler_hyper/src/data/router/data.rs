@@ -0,0 +1,121 @@
+use std::{collections::HashMap, ops::ControlFlow, sync::Arc};
+
+use async_fn_traits::AsyncFn1;
+use futures::future::BoxFuture;
+use serde::Serialize;
+
+use super::{
+    UrlForError,
+    trie::{Node, PatternSegment, parse_pattern},
+    url::build_url,
+};
+use crate::{data::http_error::HttpError, state::context::HttpRequestContext};
+
+/// A handler of a route, see `Router::route`
+pub(super) type RouteHandler = Box<
+    dyn for<'a> Fn(&'a mut HttpRequestContext) -> BoxFuture<'a, Result<ControlFlow<()>, HttpError>>
+        + Send
+        + Sync,
+>;
+
+/// Routes requests by their `RemainingPath` to handlers, see `ContextRouterExt::route`.
+///
+/// Patterns consist of static segments, named parameters like `:id` and a trailing wildcard like `*path`,
+/// which matches all remaining segments: `/users/:id/files/*path`.
+/// Static segments take precedence over parameters, which take precedence over wildcards.
+/// The routes are compiled into a trie, so matching doesn't depend on the number of routes.
+///
+/// Named routes can be turned back into URLs with `url_for` or `ContextUrlForExt::url_for`
+#[derive(Default)]
+pub struct Router {
+    pub(super) root: Node,
+    /// The patterns of the named routes
+    pub(super) names: Arc<HashMap<String, Vec<PatternSegment>>>,
+}
+
+impl std::fmt::Debug for Router {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Router").finish_non_exhaustive()
+    }
+}
+
+impl Router {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a route handled by `handler`
+    ///
+    /// # Panics
+    /// If `pattern` is invalid, the route already exists or a parameter at the same position has a different name
+    pub fn route<Fn>(mut self, pattern: &str, handler: Fn) -> Self
+    where
+        Fn: Send
+            + Sync
+            + 'static
+            + for<'a> AsyncFn1<
+                &'a mut HttpRequestContext,
+                Output = Result<ControlFlow<()>, HttpError>,
+            >,
+        for<'a> <Fn as AsyncFn1<&'a mut HttpRequestContext>>::OutputFuture: Send,
+    {
+        let handler: RouteHandler = Box::new(move |ctx| Box::pin(handler(ctx)));
+        self.root.insert(pattern, &parse_pattern(pattern), handler);
+        self
+    }
+
+    /// Adds a route like `route`, whose URL can be generated from `name`
+    ///
+    /// # Panics
+    /// Like `route` or if there already is a route named `name`
+    pub fn named_route<Fn>(mut self, name: &str, pattern: &str, handler: Fn) -> Self
+    where
+        Fn: Send
+            + Sync
+            + 'static
+            + for<'a> AsyncFn1<
+                &'a mut HttpRequestContext,
+                Output = Result<ControlFlow<()>, HttpError>,
+            >,
+        for<'a> <Fn as AsyncFn1<&'a mut HttpRequestContext>>::OutputFuture: Send,
+    {
+        self.insert_name(name.to_string(), parse_pattern(pattern));
+        self.route(pattern, handler)
+    }
+
+    /// # Panics
+    /// If there already is a route named `name`
+    fn insert_name(&mut self, name: String, pattern: Vec<PatternSegment>) {
+        let names = Arc::make_mut(&mut self.names);
+        assert!(!names.contains_key(&name), "duplicate route name `{name}`");
+        names.insert(name, pattern);
+    }
+
+    /// Adds all routes of `router` under `prefix`, which may contain parameters but no wildcard
+    ///
+    /// # Panics
+    /// Like `route`
+    pub fn nest(mut self, prefix: &str, router: Router) -> Self {
+        let prefix_segments = parse_pattern(prefix);
+        self.root
+            .node_mut(prefix, &prefix_segments)
+            .merge(prefix, router.root);
+
+        for (name, pattern) in Arc::unwrap_or_clone(router.names) {
+            let pattern = prefix_segments.iter().cloned().chain(pattern).collect();
+            self.insert_name(name, pattern);
+        }
+        self
+    }
+
+    /// Returns the path of the route `name` with its parameters filled from `params`.
+    /// The other parameters become the query string
+    pub fn url_for(&self, name: &str, params: &impl Serialize) -> Result<String, UrlForError> {
+        let pattern = self
+            .names
+            .get(name)
+            .ok_or_else(|| UrlForError::UnknownRoute(name.to_string()))?;
+
+        build_url(name, pattern, params)
+    }
+}
@@ -0,0 +1,33 @@
+use thiserror::Error;
+
+use crate::data::http_error::HttpError;
+
+#[derive(Debug, Error)]
+pub enum GetPathParamsError {
+    #[error("{0}")]
+    Parse(#[from] serde_html_form::de::Error),
+}
+
+impl From<GetPathParamsError> for HttpError {
+    fn from(value: GetPathParamsError) -> Self {
+        match value {
+            GetPathParamsError::Parse(err) => Self::bad_request(err.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum UrlForError {
+    #[error("there is no route named `{0}`")]
+    UnknownRoute(String),
+    #[error("missing parameter `{param}` of route `{route}`")]
+    MissingParam { route: String, param: String },
+    #[error("{0}")]
+    Serialize(#[from] serde_html_form::ser::Error),
+}
+
+impl From<UrlForError> for HttpError {
+    fn from(value: UrlForError) -> Self {
+        Self::internal_server_error(value.to_string())
+    }
+}
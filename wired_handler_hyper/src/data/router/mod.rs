@@ -0,0 +1,15 @@
+pub use context_path_params_ext::*;
+pub use context_router_ext::*;
+pub use context_url_for_ext::*;
+pub use data::*;
+pub use error::*;
+
+mod context_path_params_ext;
+mod context_router_ext;
+mod context_url_for_ext;
+mod data;
+mod error;
+#[cfg(test)]
+mod test;
+mod trie;
+mod url;
@@ -0,0 +1,262 @@
+#![allow(clippy::result_large_err)]
+
+use std::ops::ControlFlow;
+
+use http_body_util::BodyExt;
+use hyper::StatusCode;
+use serde::{Deserialize, Serialize};
+
+use super::{Router, UrlForError};
+use crate::{
+    data::{
+        config::RouterConfig, http_error::HttpError, response::Response,
+        response_body::ResponseBody,
+    },
+    prelude::*,
+    state::{
+        context::HttpRequestContext, global_state::GlobalState, request_state::RequestState,
+        session_state::SessionState,
+    },
+};
+
+#[test]
+fn test() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    runtime.block_on(run_test());
+}
+
+#[derive(Debug, Deserialize)]
+struct PostParams {
+    id: u64,
+    post_id: u64,
+}
+
+fn respond(ctx: &mut HttpRequestContext, body: String) -> Result<ControlFlow<()>, HttpError> {
+    ctx.stop(Response::builder().body(ResponseBody::from_bytes(body))?)
+}
+
+async fn home(ctx: &mut HttpRequestContext) -> Result<ControlFlow<()>, HttpError> {
+    respond(ctx, "home".into())
+}
+
+async fn new_user(ctx: &mut HttpRequestContext) -> Result<ControlFlow<()>, HttpError> {
+    respond(ctx, "new user".into())
+}
+
+async fn user(ctx: &mut HttpRequestContext) -> Result<ControlFlow<()>, HttpError> {
+    let id = ctx.path_param("id").unwrap().to_string();
+    respond(ctx, format!("user {id}"))
+}
+
+async fn post(ctx: &mut HttpRequestContext) -> Result<ControlFlow<()>, HttpError> {
+    let PostParams { id, post_id } = ctx.path_params()?;
+    respond(ctx, format!("post {id} {post_id}"))
+}
+
+async fn file(ctx: &mut HttpRequestContext) -> Result<ControlFlow<()>, HttpError> {
+    let path = ctx.path_param("path").unwrap().to_string();
+    let next = ctx.remaining_path_mut().next().unwrap_or_default();
+    respond(ctx, format!("file {path}, next {next}"))
+}
+
+async fn member(ctx: &mut HttpRequestContext) -> Result<ControlFlow<()>, HttpError> {
+    let org = ctx.path_param("org").unwrap().to_string();
+    let id = ctx.path_param("id").unwrap().to_string();
+    respond(ctx, format!("member {id} of {org}"))
+}
+
+#[derive(Debug, Serialize)]
+struct LinkParams {
+    id: &'static str,
+    path: &'static str,
+    page: u32,
+}
+
+async fn link(ctx: &mut HttpRequestContext) -> Result<ControlFlow<()>, HttpError> {
+    let file_url = ctx
+        .url_for(
+            "file",
+            &LinkParams {
+                id: "a b",
+                path: "docs/guide.md",
+                page: 2,
+            },
+        )
+        .await?;
+    let member_url = ctx
+        .url_for("member", &[("org", "acme"), ("id", "7")])
+        .await?;
+    respond(ctx, format!("{file_url} {member_url}"))
+}
+
+async fn settings(ctx: &mut HttpRequestContext) -> Result<ControlFlow<()>, HttpError> {
+    let settings_url = ctx.url_for("settings", &[("section", "mail")]).await?;
+    let post_url = ctx
+        .url_for("post", &[("id", "1"), ("post_id", "2")])
+        .await?;
+    respond(ctx, format!("{settings_url} {post_url}"))
+}
+
+/// Routes the rest of the path with another `Router`, like an independently built part of the application
+async fn admin(ctx: &mut HttpRequestContext) -> Result<ControlFlow<()>, HttpError> {
+    let admin_router = Router::new().named_route("settings", "/settings/:section", settings);
+    ctx.route(&admin_router).await
+}
+
+async fn route(router: &Router, path: &str) -> (StatusCode, String) {
+    route_with_global_state(GlobalState::default(), router, path).await
+}
+
+async fn route_with_global_state(
+    global_state: GlobalState,
+    router: &Router,
+    path: &str,
+) -> (StatusCode, String) {
+    let mut request_state = RequestState::default();
+    request_state.insert(path.to_string());
+    let mut ctx =
+        HttpRequestContext::from_states(global_state, SessionState::default(), request_state);
+
+    let response = match ctx.route(router).await {
+        Ok(_) => RequestState::get_mut_from_ctx(&mut ctx)
+            .remove_get::<Response>()
+            .unwrap(),
+        Err(err) => err.into(),
+    };
+    let status = response.status();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+
+    (status, String::from_utf8(body.to_vec()).unwrap())
+}
+
+async fn run_test() {
+    let router = Router::new()
+        .route("/", home)
+        .route("/users/new", new_user)
+        .route("/users/:id", user)
+        .route("/users/:id/posts/:post_id", post)
+        .route("/files/*path", file)
+        .nest("/orgs/:org", Router::new().route("/members/:id", member));
+
+    for (path, expected_status, expected_body) in [
+        ("/", StatusCode::OK, "home"),
+        ("/users/new", StatusCode::OK, "new user"),
+        ("/users/new/", StatusCode::OK, "new user"),
+        ("/users/franz", StatusCode::OK, "user franz"),
+        ("/users/J%C3%BCrgen", StatusCode::OK, "user Jürgen"),
+        ("/users/12/posts/34", StatusCode::OK, "post 12 34"),
+        ("/users/new/posts/34", StatusCode::BAD_REQUEST, ""),
+        (
+            "/files/docs/guide.md",
+            StatusCode::OK,
+            "file docs/guide.md, next docs",
+        ),
+        ("/files", StatusCode::OK, "file , next "),
+        ("/orgs/acme/members/7", StatusCode::OK, "member 7 of acme"),
+        ("/users", StatusCode::NOT_FOUND, "not found"),
+        ("/users/12/posts", StatusCode::NOT_FOUND, "not found"),
+        ("/orgs/acme", StatusCode::NOT_FOUND, "not found"),
+        ("/missing", StatusCode::NOT_FOUND, "not found"),
+    ] {
+        let (status, body) = route(&router, path).await;
+        assert_eq!(status, expected_status, "{path}");
+        if !expected_body.is_empty() {
+            assert_eq!(body, expected_body, "{path}");
+        }
+    }
+
+    // invalid routes are rejected when building the router
+    for build_router in [
+        || {
+            Router::new()
+                .route("/users/:id", user)
+                .route("/users/:id", user)
+        },
+        || {
+            Router::new()
+                .route("/users/:id", user)
+                .route("/users/:name/posts", post)
+        },
+        || Router::new().route("/files/*path/edit", file),
+        || Router::new().route("/users/:", user),
+        || {
+            Router::new()
+                .route("/", home)
+                .nest("/", Router::new().route("/", home))
+        },
+    ] {
+        assert!(std::panic::catch_unwind(build_router).is_err());
+    }
+
+    // named routes
+    let router = Router::new()
+        .named_route("home", "/", home)
+        .named_route("post", "/users/:id/posts/:post_id", post)
+        .named_route("file", "/files/*path", file)
+        .route("/link", link)
+        .nest(
+            "/orgs/:org",
+            Router::new().named_route("member", "/members/:id", member),
+        );
+
+    assert_eq!(router.url_for("home", &()).unwrap(), "/");
+    assert_eq!(
+        router
+            .url_for("post", &[("id", "12"), ("post_id", "34")])
+            .unwrap(),
+        "/users/12/posts/34"
+    );
+    assert_eq!(
+        router
+            .url_for("file", &[("path", "a/ü?.md"), ("q", "x y")])
+            .unwrap(),
+        "/files/a/%C3%BC%3F.md?q=x+y"
+    );
+    assert_eq!(router.url_for("file", &()).unwrap(), "/files");
+    assert!(matches!(
+        router.url_for("post", &[("id", "12")]),
+        Err(UrlForError::MissingParam { .. })
+    ));
+    assert!(matches!(
+        router.url_for("missing", &()),
+        Err(UrlForError::UnknownRoute(_))
+    ));
+
+    // the generated URLs lead back to the routes
+    let (_, body) = route(&router, "/files/a/%C3%BC%3F.md").await;
+    assert_eq!(body, "file a/ü?.md, next a");
+
+    let (status, body) = route(&router, "/link").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        body,
+        "/files/docs/guide.md?id=a+b&page=2 /orgs/acme/members/7"
+    );
+
+    let global_state = GlobalState::default();
+    global_state
+        .insert(RouterConfig {
+            mount_prefix: "/app/".into(),
+        })
+        .await;
+    let (_, body) = route_with_global_state(global_state, &router, "/link").await;
+    assert_eq!(
+        body,
+        "/app/files/docs/guide.md?id=a+b&page=2 /app/orgs/acme/members/7"
+    );
+
+    // routers called by a handler keep the names and matched path of the outer ones
+    let router = Router::new()
+        .named_route("post", "/users/:id/posts/:post_id", post)
+        .route("/orgs/:org/admin/*rest", admin);
+    let (status, body) = route(&router, "/orgs/a%20b/admin/settings/web").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, "/orgs/a%20b/admin/settings/mail /users/1/posts/2");
+
+    assert!(
+        std::panic::catch_unwind(|| Router::new()
+            .named_route("home", "/", home)
+            .named_route("home", "/home", home))
+        .is_err()
+    );
+}
@@ -0,0 +1,197 @@
+use std::collections::HashMap;
+
+use super::data::RouteHandler;
+
+/// A segment of a route pattern
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum PatternSegment {
+    /// Matches exactly this segment
+    Static(String),
+    /// `:name`, matches any single segment
+    Param(String),
+    /// `*name`, matches all remaining segments, only allowed at the end
+    Wildcard(String),
+}
+
+/// Parses a pattern like `/users/:id/files/*path`, empty segments are ignored
+///
+/// # Panics
+/// If a parameter has no name or a wildcard isn't the last segment
+pub(super) fn parse_pattern(pattern: &str) -> Vec<PatternSegment> {
+    let segments: Vec<PatternSegment> = pattern
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| {
+            let (segment, name) = if let Some(name) = segment.strip_prefix(':') {
+                (PatternSegment::Param(name.to_string()), name)
+            } else if let Some(name) = segment.strip_prefix('*') {
+                (PatternSegment::Wildcard(name.to_string()), name)
+            } else {
+                return PatternSegment::Static(segment.to_string());
+            };
+            assert!(
+                !name.is_empty(),
+                "parameter without name in route `{pattern}`"
+            );
+            segment
+        })
+        .collect();
+
+    let wildcard_position = segments
+        .iter()
+        .position(|segment| matches!(segment, PatternSegment::Wildcard(_)));
+    assert!(
+        wildcard_position.is_none_or(|position| position == segments.len() - 1),
+        "wildcard isn't the last segment of route `{pattern}`"
+    );
+
+    segments
+}
+
+/// A node of the trie of routes. Static segments are matched first, then parameters, then wildcards
+#[derive(Default)]
+pub(super) struct Node {
+    statics: HashMap<String, Node>,
+    param: Option<(String, Box<Node>)>,
+    wildcard: Option<(String, RouteHandler)>,
+    handler: Option<RouteHandler>,
+}
+
+impl Node {
+    /// Inserts `handler` at the route of `segments`, `pattern` is only used for error messages
+    ///
+    /// # Panics
+    /// If the route already exists or a parameter at the same position has a different name
+    pub fn insert(&mut self, pattern: &str, segments: &[PatternSegment], handler: RouteHandler) {
+        let Some((segment, remaining_segments)) = segments.split_first() else {
+            assert!(self.handler.is_none(), "duplicate route `{pattern}`");
+            self.handler = Some(handler);
+            return;
+        };
+
+        match segment {
+            PatternSegment::Static(segment) => self
+                .statics
+                .entry(segment.clone())
+                .or_default()
+                .insert(pattern, remaining_segments, handler),
+            PatternSegment::Param(name) => {
+                let (existing_name, node) = self
+                    .param
+                    .get_or_insert_with(|| (name.clone(), Box::default()));
+                assert_eq!(
+                    existing_name, name,
+                    "conflicting parameter names in route `{pattern}`"
+                );
+                node.insert(pattern, remaining_segments, handler);
+            }
+            PatternSegment::Wildcard(name) => {
+                assert!(self.wildcard.is_none(), "duplicate route `{pattern}`");
+                self.wildcard = Some((name.clone(), handler));
+            }
+        }
+    }
+
+    /// Moves all routes of `other` into this node
+    ///
+    /// # Panics
+    /// Like `insert`
+    pub fn merge(&mut self, pattern: &str, other: Node) {
+        for (segment, other_node) in other.statics {
+            self.statics
+                .entry(segment)
+                .or_default()
+                .merge(pattern, other_node);
+        }
+        if let Some((name, other_node)) = other.param {
+            match &mut self.param {
+                Some((existing_name, node)) => {
+                    assert_eq!(
+                        *existing_name, name,
+                        "conflicting parameter names in routes nested at `{pattern}`"
+                    );
+                    node.merge(pattern, *other_node);
+                }
+                None => self.param = Some((name, other_node)),
+            }
+        }
+        if let Some(wildcard) = other.wildcard {
+            assert!(
+                self.wildcard.is_none(),
+                "duplicate route nested at `{pattern}`"
+            );
+            self.wildcard = Some(wildcard);
+        }
+        if let Some(handler) = other.handler {
+            assert!(self.handler.is_none(), "duplicate route `{pattern}`");
+            self.handler = Some(handler);
+        }
+    }
+
+    /// Returns the node at the route of `segments`, creating it if it doesn't exist
+    ///
+    /// # Panics
+    /// If `segments` contains a wildcard or like `insert`
+    pub fn node_mut(&mut self, pattern: &str, segments: &[PatternSegment]) -> &mut Node {
+        let Some((segment, remaining_segments)) = segments.split_first() else {
+            return self;
+        };
+
+        match segment {
+            PatternSegment::Static(segment) => self
+                .statics
+                .entry(segment.clone())
+                .or_default()
+                .node_mut(pattern, remaining_segments),
+            PatternSegment::Param(name) => {
+                let (existing_name, node) = self
+                    .param
+                    .get_or_insert_with(|| (name.clone(), Box::default()));
+                assert_eq!(
+                    existing_name, name,
+                    "conflicting parameter names in route `{pattern}`"
+                );
+                node.node_mut(pattern, remaining_segments)
+            }
+            PatternSegment::Wildcard(_) => {
+                panic!("can't nest routes under the wildcard of `{pattern}`")
+            }
+        }
+    }
+
+    /// Finds the handler of `segments`, pushing the matched parameters to `params`.
+    /// Returns the segments matched by a wildcard as well
+    pub fn find<'a>(
+        &'a self,
+        segments: &[String],
+        params: &mut Vec<(String, String)>,
+    ) -> Option<(&'a RouteHandler, Option<usize>)> {
+        let Some((segment, remaining_segments)) = segments.split_first() else {
+            if let Some(handler) = &self.handler {
+                return Some((handler, None));
+            }
+            // a wildcard matches no segments as well
+            let (name, handler) = self.wildcard.as_ref()?;
+            params.push((name.clone(), String::new()));
+            return Some((handler, Some(0)));
+        };
+
+        if let Some(node) = self.statics.get(segment)
+            && let Some(found) = node.find(remaining_segments, params)
+        {
+            return Some(found);
+        }
+
+        if let Some((name, node)) = &self.param {
+            params.push((name.clone(), segment.clone()));
+            if let Some(found) = node.find(remaining_segments, params) {
+                return Some(found);
+            }
+            params.pop();
+        }
+
+        let (name, handler) = self.wildcard.as_ref()?;
+        params.push((name.clone(), segments.join("/")));
+        Some((handler, Some(segments.len())))
+    }
+}
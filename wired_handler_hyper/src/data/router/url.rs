@@ -0,0 +1,79 @@
+use percent_encoding::{AsciiSet, CONTROLS, utf8_percent_encode};
+use serde::Serialize;
+
+use super::{UrlForError, trie::PatternSegment};
+
+/// The characters percent-encoded in a path segment
+const PATH_SEGMENT: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'/')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'\\')
+    .add(b'^')
+    .add(b'`')
+    .add(b'{')
+    .add(b'|')
+    .add(b'}');
+
+/// Percent-encodes a decoded path segment
+pub(super) fn encode_segment(segment: &str) -> String {
+    utf8_percent_encode(segment, PATH_SEGMENT).to_string()
+}
+
+/// Removes the first parameter `name` from `params` and returns its value
+fn take_param(params: &mut Vec<(String, String)>, name: &str) -> Option<String> {
+    let index = params
+        .iter()
+        .position(|(param_name, _)| param_name == name)?;
+
+    Some(params.remove(index).1)
+}
+
+/// Fills the parameters of the route `name` with `params`, the ones which aren't part of the route become the query string
+pub(super) fn build_url(
+    name: &str,
+    pattern: &[PatternSegment],
+    params: &impl Serialize,
+) -> Result<String, UrlForError> {
+    let encoded_params = serde_html_form::to_string(params)?;
+    // can't fail, it has just been serialized
+    let mut params: Vec<(String, String)> =
+        serde_html_form::from_str(&encoded_params).unwrap_or_default();
+
+    let mut segments = Vec::new();
+    for segment in pattern {
+        match segment {
+            PatternSegment::Static(segment) => segments.push(encode_segment(segment)),
+            PatternSegment::Param(param_name) => {
+                let value = take_param(&mut params, param_name)
+                    .filter(|value| !value.is_empty())
+                    .ok_or_else(|| UrlForError::MissingParam {
+                        route: name.to_string(),
+                        param: param_name.clone(),
+                    })?;
+                segments.push(encode_segment(&value));
+            }
+            // a wildcard may match no segments at all
+            PatternSegment::Wildcard(param_name) => segments.extend(
+                take_param(&mut params, param_name)
+                    .unwrap_or_default()
+                    .split('/')
+                    .filter(|segment| !segment.is_empty())
+                    .map(encode_segment),
+            ),
+        }
+    }
+
+    let mut url = format!("/{}", segments.join("/"));
+    if !params.is_empty() {
+        url.push('?');
+        url.push_str(&serde_html_form::to_string(&params)?);
+    }
+
+    Ok(url)
+}
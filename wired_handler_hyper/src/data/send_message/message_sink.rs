@@ -5,7 +5,7 @@ use std::{
 
 use futures::stream::SplitSink;
 use hyper::upgrade::Upgraded;
-use hyper_tungstenite::{tungstenite::Message, WebSocketStream};
+use hyper_tungstenite::{WebSocketStream, tungstenite::Message};
 use hyper_util::rt::TokioIo;
 
 use crate::{prelude::*, state::connection_state::ConnectionState};
@@ -0,0 +1,132 @@
+use std::{
+    fs::Metadata,
+    ops::RangeInclusive,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// The validators of a file, to answer conditional requests
+#[derive(Debug)]
+pub(super) struct Validators {
+    pub etag: String,
+    pub last_modified: Option<SystemTime>,
+}
+
+impl Validators {
+    /// Creates the validators from the size and modification time, `encoding` distinguishes precompressed siblings
+    pub fn new(metadata: &Metadata, encoding: Option<&str>) -> Self {
+        let last_modified = metadata.modified().ok();
+        let modified_nanos = last_modified
+            .and_then(|last_modified| last_modified.duration_since(UNIX_EPOCH).ok())
+            .unwrap_or_default()
+            .as_nanos();
+        let etag = match encoding {
+            Some(encoding) => format!("\"{:x}-{modified_nanos:x}-{encoding}\"", metadata.len()),
+            None => format!("\"{:x}-{modified_nanos:x}\"", metadata.len()),
+        };
+
+        Self {
+            etag,
+            last_modified: last_modified.map(truncate_to_secs),
+        }
+    }
+
+    /// Whether the client's cached copy is still fresh according to `If-None-Match` or `If-Modified-Since`
+    pub fn is_not_modified(
+        &self,
+        if_none_match: Option<&str>,
+        if_modified_since: Option<&str>,
+    ) -> bool {
+        // `If-Modified-Since` is ignored if `If-None-Match` is given
+        if let Some(if_none_match) = if_none_match {
+            return if_none_match.trim() == "*"
+                || if_none_match
+                    .split(',')
+                    .any(|etag| etag.trim().trim_start_matches("W/") == self.etag);
+        }
+
+        if let Some(if_modified_since) = if_modified_since
+            && let Some(last_modified) = self.last_modified
+            && let Ok(if_modified_since) = httpdate::parse_http_date(if_modified_since)
+        {
+            return last_modified <= if_modified_since;
+        }
+
+        false
+    }
+
+    /// Whether the `Range` header may be used according to `If-Range`
+    pub fn is_range_allowed(&self, if_range: Option<&str>) -> bool {
+        let Some(if_range) = if_range.map(str::trim) else {
+            return true;
+        };
+        if if_range.starts_with('"') {
+            return if_range == self.etag;
+        }
+
+        match (self.last_modified, httpdate::parse_http_date(if_range)) {
+            (Some(last_modified), Ok(if_range)) => last_modified == if_range,
+            _ => false,
+        }
+    }
+}
+
+/// HTTP dates only have second precision
+fn truncate_to_secs(time: SystemTime) -> SystemTime {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(duration) => UNIX_EPOCH + Duration::from_secs(duration.as_secs()),
+        Err(_) => time,
+    }
+}
+
+/// What the `Range` header asks for
+#[derive(Debug, PartialEq, Eq)]
+pub(super) enum RangeRequest {
+    /// The whole file, also used for multiple or invalid ranges
+    Full,
+    /// The bytes in the range
+    Partial(RangeInclusive<u64>),
+    /// The range starts after the end of the file
+    Unsatisfiable,
+}
+
+impl RangeRequest {
+    /// Parses a single range of the `Range` header for a file of `len` bytes
+    pub fn parse(range: Option<&str>, len: u64) -> Self {
+        let Some(range) = range.and_then(|range| range.trim().strip_prefix("bytes=")) else {
+            return Self::Full;
+        };
+        if range.contains(',') {
+            return Self::Full;
+        }
+        let Some((start, end)) = range.split_once('-') else {
+            return Self::Full;
+        };
+        let (start, end) = (start.trim(), end.trim());
+
+        // suffix range like `-500` for the last 500 bytes
+        if start.is_empty() {
+            return match end.parse::<u64>() {
+                Ok(0) => Self::Unsatisfiable,
+                Ok(_) if len == 0 => Self::Unsatisfiable,
+                Ok(suffix_len) => Self::Partial(len.saturating_sub(suffix_len)..=len - 1),
+                Err(_) => Self::Full,
+            };
+        }
+
+        let Ok(start) = start.parse::<u64>() else {
+            return Self::Full;
+        };
+        let end = match end {
+            "" => None,
+            end => match end.parse::<u64>() {
+                Ok(end) if end >= start => Some(end),
+                _ => return Self::Full,
+            },
+        };
+        if start >= len {
+            return Self::Unsatisfiable;
+        }
+
+        Self::Partial(start..=end.map_or(len - 1, |end| end.min(len - 1)))
+    }
+}
@@ -0,0 +1,272 @@
+use std::{
+    ffi::OsString,
+    fs::Metadata,
+    future::Future,
+    io::SeekFrom,
+    ops::ControlFlow,
+    path::{Component, Path, PathBuf},
+};
+
+use http::{
+    Method, StatusCode,
+    header::{
+        ACCEPT_ENCODING, ACCEPT_RANGES, ALLOW, CONTENT_ENCODING, CONTENT_LENGTH, CONTENT_RANGE,
+        CONTENT_TYPE, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, IF_RANGE, LAST_MODIFIED, LOCATION,
+        RANGE, VARY,
+    },
+};
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+use super::{
+    conditional::{RangeRequest, Validators},
+    data::ServeDir,
+};
+use crate::{
+    data::{
+        allowed_methods::request_method,
+        http_error::HttpError,
+        request::{request_header, request_query},
+        response::Response,
+        response_body::{ResponseBody, ResponseBodyExt},
+    },
+    prelude::*,
+    state::context::HttpRequestContext,
+};
+
+const ALLOWED_METHODS: &str = "GET, HEAD";
+
+/// Turns the segments of the path into a path relative to the root, `None` if a segment could escape it
+fn relative_path(segments: impl IntoIterator<Item = String>) -> Option<PathBuf> {
+    let mut relative_path = PathBuf::new();
+    for segment in segments {
+        if segment.is_empty() || segment == "." {
+            continue;
+        }
+        if segment.contains(['/', '\\', '\0']) {
+            return None;
+        }
+
+        // rejects `..` and prefixes like `C:` on Windows
+        let mut components = Path::new(&segment).components();
+        let (Some(Component::Normal(component)), None) = (components.next(), components.next())
+        else {
+            return None;
+        };
+        relative_path.push(component);
+    }
+
+    Some(relative_path)
+}
+
+/// Returns the canonical path and metadata of `path` if it exists under `root`.
+/// Symlinks are followed, but not outside of `root`
+async fn lookup(root: &Path, path: &Path) -> Option<(PathBuf, Metadata)> {
+    let root = tokio::fs::canonicalize(root).await.ok()?;
+    let path = tokio::fs::canonicalize(path).await.ok()?;
+    if !path.starts_with(&root) {
+        return None;
+    }
+    let metadata = tokio::fs::metadata(&path).await.ok()?;
+
+    Some((path, metadata))
+}
+
+/// Like `lookup`, but only returns files
+async fn lookup_file(root: &Path, path: &Path) -> Option<(PathBuf, Metadata)> {
+    lookup(root, path)
+        .await
+        .filter(|(_, metadata)| metadata.is_file())
+}
+
+/// Whether `encoding` is accepted according to the `Accept-Encoding` header
+fn accepts_encoding(accept_encoding: &str, encoding: &str) -> bool {
+    accept_encoding.split(',').any(|accepted| {
+        let mut parts = accepted.split(';');
+        let name = parts.next().unwrap_or_default().trim();
+        let rejected = parts.any(|param| {
+            param
+                .trim()
+                .strip_prefix("q=")
+                .and_then(|quality| quality.trim().parse::<f32>().ok())
+                == Some(0.0)
+        });
+
+        (name.eq_ignore_ascii_case(encoding) || name == "*") && !rejected
+    })
+}
+
+/// Appends `.{extension}` to the file name of `path`
+fn with_added_extension(path: &Path, extension: &str) -> PathBuf {
+    let mut path = OsString::from(path);
+    path.push(".");
+    path.push(extension);
+
+    path.into()
+}
+
+/// Finds the file the request is for, `Err` contains the response if it isn't served
+#[allow(clippy::result_large_err)]
+async fn find_file(
+    ctx: &mut HttpRequestContext,
+    serve_dir: &ServeDir,
+) -> Result<(PathBuf, Metadata), Result<Response, HttpError>> {
+    let segments: Vec<String> = ctx.remaining_path_mut().by_ref().collect();
+    let not_found = || HttpError::not_found("not found");
+    let Some(relative_path) = relative_path(segments) else {
+        return Err(Err(not_found()));
+    };
+    let root = serve_dir.root();
+
+    let mut file = lookup(root, &root.join(relative_path)).await;
+    if let Some((path, metadata)) = &file
+        && metadata.is_dir()
+    {
+        file = match serve_dir.index_file_name() {
+            // relative links of the index file need the trailing slash
+            Some(_) if !ctx.path().ends_with('/') => {
+                let dir_name = ctx.path().rsplit('/').next().unwrap_or_default();
+                let location = match request_query(ctx) {
+                    Some(query) => format!("./{dir_name}/?{query}"),
+                    None => format!("./{dir_name}/"),
+                };
+                return Err(Response::builder()
+                    .status(StatusCode::PERMANENT_REDIRECT)
+                    .header(LOCATION, location)
+                    .body(ResponseBody::empty())
+                    .map_err(HttpError::from));
+            }
+            Some(index_file) => lookup_file(root, &path.join(index_file)).await,
+            None => None,
+        };
+    }
+
+    if let Some(file) = file.filter(|(_, metadata)| metadata.is_file()) {
+        return Ok(file);
+    }
+    match serve_dir.fallback_path() {
+        Some(fallback) => lookup_file(root, &root.join(fallback))
+            .await
+            .ok_or_else(|| Err(not_found())),
+        None => Err(Err(not_found())),
+    }
+}
+
+/// For serving static files
+pub trait ContextServeDirExt {
+    /// Serves the file under the root of `serve_dir` at the `RemainingPath`, which is consumed.
+    /// Paths escaping the root are not found.
+    ///
+    /// Answers conditional (`If-None-Match`, `If-Modified-Since`) and single range requests,
+    /// other methods than `GET` and `HEAD` are not allowed. Stops the execution on success
+    fn serve_dir(
+        &mut self,
+        serve_dir: &ServeDir,
+    ) -> impl Future<Output = Result<ControlFlow<()>, HttpError>>;
+}
+
+impl ContextServeDirExt for HttpRequestContext {
+    async fn serve_dir(&mut self, serve_dir: &ServeDir) -> Result<ControlFlow<()>, HttpError> {
+        let method = request_method(self);
+        if method != Method::GET && method != Method::HEAD {
+            let response = Response::builder()
+                .status(StatusCode::METHOD_NOT_ALLOWED)
+                .header(ALLOW, ALLOWED_METHODS)
+                .body(ResponseBody::empty())?;
+            return self.stop(response);
+        }
+
+        let (path, metadata) = match find_file(self, serve_dir).await {
+            Ok(file) => file,
+            Err(response) => return self.stop(response?),
+        };
+
+        // the content type is the one of the uncompressed file
+        let content_type = mime_guess::from_path(&path).first_or_octet_stream();
+        let mut file = (path, metadata, None);
+        if let Some(accept_encoding) = request_header(self, ACCEPT_ENCODING) {
+            for (encoding, extension) in serve_dir.precompressed() {
+                if !accepts_encoding(accept_encoding, encoding) {
+                    continue;
+                }
+                if let Some((path, metadata)) =
+                    lookup_file(serve_dir.root(), &with_added_extension(&file.0, extension)).await
+                {
+                    file = (path, metadata, Some(encoding));
+                    break;
+                }
+            }
+        }
+        let (path, metadata, encoding) = file;
+
+        let validators = Validators::new(&metadata, encoding);
+        let mut response = Response::builder()
+            .header(ETAG, &validators.etag)
+            .header(ACCEPT_RANGES, "bytes");
+        if let Some(last_modified) = validators.last_modified {
+            response = response.header(LAST_MODIFIED, httpdate::fmt_http_date(last_modified));
+        }
+        if serve_dir.precompressed().next().is_some() {
+            response = response.header(VARY, ACCEPT_ENCODING);
+        }
+
+        if validators.is_not_modified(
+            request_header(self, IF_NONE_MATCH),
+            request_header(self, IF_MODIFIED_SINCE),
+        ) {
+            let response = response
+                .status(StatusCode::NOT_MODIFIED)
+                .body(ResponseBody::empty())?;
+            return self.stop(response);
+        }
+
+        let len = metadata.len();
+        let range = match validators.is_range_allowed(request_header(self, IF_RANGE)) {
+            true => RangeRequest::parse(request_header(self, RANGE), len),
+            false => RangeRequest::Full,
+        };
+        let range = match range {
+            RangeRequest::Full => 0..=len.saturating_sub(1),
+            RangeRequest::Partial(range) => {
+                response = response.status(StatusCode::PARTIAL_CONTENT).header(
+                    CONTENT_RANGE,
+                    format!("bytes {}-{}/{len}", range.start(), range.end()),
+                );
+                range
+            }
+            RangeRequest::Unsatisfiable => {
+                let response = response
+                    .status(StatusCode::RANGE_NOT_SATISFIABLE)
+                    .header(CONTENT_RANGE, format!("bytes */{len}"))
+                    .body(ResponseBody::empty())?;
+                return self.stop(response);
+            }
+        };
+        let content_len = match len {
+            0 => 0,
+            _ => range.end() - range.start() + 1,
+        };
+
+        response = response
+            .header(CONTENT_TYPE, content_type.as_ref())
+            .header(CONTENT_LENGTH, content_len);
+        if let Some(encoding) = encoding {
+            response = response.header(CONTENT_ENCODING, encoding);
+        }
+
+        let body = match method {
+            Method::HEAD => ResponseBody::empty(),
+            _ => {
+                let mut file = tokio::fs::File::open(&path)
+                    .await
+                    .map_err(|_| HttpError::internal_server_error("failed to open file"))?;
+                file.seek(SeekFrom::Start(*range.start()))
+                    .await
+                    .map_err(|_| HttpError::internal_server_error("failed to read file"))?;
+
+                ResponseBody::from_reader(file.take(content_len))
+            }
+        };
+
+        self.stop(response.body(body)?)
+    }
+}
@@ -0,0 +1,70 @@
+use std::path::{Path, PathBuf};
+
+/// Serves the files under a root directory, see `ContextServeDirExt::serve_dir`
+#[derive(Debug, Clone)]
+pub struct ServeDir {
+    root: PathBuf,
+    index_file: Option<String>,
+    fallback: Option<PathBuf>,
+    precompressed_br: bool,
+    precompressed_gzip: bool,
+}
+
+impl ServeDir {
+    /// Serves the files under `root`
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            index_file: None,
+            fallback: None,
+            precompressed_br: false,
+            precompressed_gzip: false,
+        }
+    }
+
+    /// Serves `index_file` (like `index.html`) for requests to a directory
+    pub fn index_file(mut self, index_file: impl Into<String>) -> Self {
+        self.index_file = Some(index_file.into());
+        self
+    }
+
+    /// Serves `fallback` (relative to the root) for paths without a file, like the `index.html` of a single page app
+    pub fn fallback(mut self, fallback: impl Into<PathBuf>) -> Self {
+        self.fallback = Some(fallback.into());
+        self
+    }
+
+    /// Serves the `.br` sibling of a file instead if it exists and the client accepts it
+    pub fn precompressed_br(mut self) -> Self {
+        self.precompressed_br = true;
+        self
+    }
+
+    /// Serves the `.gz` sibling of a file instead if it exists and the client accepts it
+    pub fn precompressed_gzip(mut self) -> Self {
+        self.precompressed_gzip = true;
+        self
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub(super) fn index_file_name(&self) -> Option<&str> {
+        self.index_file.as_deref()
+    }
+
+    pub(super) fn fallback_path(&self) -> Option<&Path> {
+        self.fallback.as_deref()
+    }
+
+    /// Returns the enabled precompressed siblings as (`Content-Encoding`, file extension), preferred first
+    pub(super) fn precompressed(&self) -> impl Iterator<Item = (&'static str, &'static str)> {
+        [
+            (self.precompressed_br, ("br", "br")),
+            (self.precompressed_gzip, ("gzip", "gz")),
+        ]
+        .into_iter()
+        .filter_map(|(enabled, encoding)| enabled.then_some(encoding))
+    }
+}
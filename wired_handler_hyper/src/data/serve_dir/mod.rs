@@ -0,0 +1,8 @@
+pub use context_serve_dir_ext::*;
+pub use data::*;
+
+mod conditional;
+mod context_serve_dir_ext;
+mod data;
+#[cfg(test)]
+mod test;
@@ -0,0 +1,241 @@
+use http::{
+    HeaderMap, HeaderName, HeaderValue, Method, StatusCode,
+    header::{
+        ACCEPT_ENCODING, ALLOW, CONTENT_ENCODING, CONTENT_LENGTH, CONTENT_RANGE, CONTENT_TYPE,
+        ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, IF_RANGE, LAST_MODIFIED, LOCATION, RANGE,
+    },
+};
+use http_body_util::BodyExt;
+use hyper::body::Bytes;
+
+use super::{ServeDir, conditional::RangeRequest};
+use crate::{
+    data::response::Response,
+    prelude::*,
+    state::{
+        context::HttpRequestContext, global_state::GlobalState, request_state::RequestState,
+        session_state::SessionState,
+    },
+};
+
+#[test]
+fn test() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    runtime.block_on(run_test());
+}
+
+/// Removes the directory again when dropped
+struct TempDir(std::path::PathBuf);
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+fn create_files() -> TempDir {
+    let temp_dir =
+        TempDir(std::env::temp_dir().join(format!("serve_dir_{}", uuid::Uuid::new_v4())));
+    let root = temp_dir.0.join("public");
+    std::fs::create_dir_all(root.join("docs")).unwrap();
+    std::fs::write(root.join("index.html"), "<p>app</p>").unwrap();
+    std::fs::write(root.join("data.txt"), "0123456789").unwrap();
+    std::fs::write(root.join("app.js"), "let app;").unwrap();
+    std::fs::write(root.join("app.js.gz"), "gzipped").unwrap();
+    std::fs::write(root.join("app.js.br"), "brotli").unwrap();
+    std::fs::write(root.join("docs/index.html"), "<p>docs</p>").unwrap();
+    std::fs::write(temp_dir.0.join("secret.txt"), "secret").unwrap();
+    #[cfg(unix)]
+    std::os::unix::fs::symlink(temp_dir.0.join("secret.txt"), root.join("link.txt")).unwrap();
+
+    temp_dir
+}
+
+async fn serve(
+    serve_dir: &ServeDir,
+    method: Method,
+    path: &str,
+    headers: &[(HeaderName, &str)],
+) -> Response {
+    let mut request_state = RequestState::default();
+    request_state.insert(path.split('?').next().unwrap_or_default().to_string());
+    request_state.insert(path.parse::<http::Uri>().unwrap());
+    request_state.insert(method);
+    request_state.insert(HeaderMap::from_iter(
+        headers
+            .iter()
+            .map(|(name, value)| (name.clone(), HeaderValue::from_str(value).unwrap())),
+    ));
+    let mut ctx = HttpRequestContext::from_states(
+        GlobalState::default(),
+        SessionState::default(),
+        request_state,
+    );
+
+    match ctx.serve_dir(serve_dir).await {
+        Ok(control_flow) => {
+            assert!(control_flow.is_break());
+            RequestState::get_mut_from_ctx(&mut ctx)
+                .remove_get::<Response>()
+                .unwrap()
+        }
+        Err(err) => err.into(),
+    }
+}
+
+async fn get(serve_dir: &ServeDir, path: &str, headers: &[(HeaderName, &str)]) -> Response {
+    serve(serve_dir, Method::GET, path, headers).await
+}
+
+async fn body(response: Response) -> Bytes {
+    response.into_body().collect().await.unwrap().to_bytes()
+}
+
+async fn run_test() {
+    let temp_dir = create_files();
+    let root = temp_dir.0.join("public");
+    let serve_dir = ServeDir::new(&root);
+
+    // files are served with their MIME type and validators
+    let response = get(&serve_dir, "/data.txt", &[]).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[CONTENT_TYPE], "text/plain");
+    assert_eq!(response.headers()[CONTENT_LENGTH], "10");
+    let etag = response.headers()[ETAG].to_str().unwrap().to_string();
+    let last_modified = response.headers()[LAST_MODIFIED]
+        .to_str()
+        .unwrap()
+        .to_string();
+    assert_eq!(body(response).await, "0123456789");
+
+    let response = serve(&serve_dir, Method::HEAD, "/data.txt", &[]).await;
+    assert_eq!(response.headers()[CONTENT_LENGTH], "10");
+    assert!(body(response).await.is_empty());
+
+    let response = serve(&serve_dir, Method::POST, "/data.txt", &[]).await;
+    assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+    assert_eq!(response.headers()[ALLOW], "GET, HEAD");
+
+    // paths can't escape the root
+    for path in [
+        "/../secret.txt",
+        "/%2e%2e/secret.txt",
+        "/docs/..%2F..%2Fsecret.txt",
+        "/missing.txt",
+        "/link.txt",
+        "/docs",
+    ] {
+        let response = get(&serve_dir, path, &[]).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND, "{path}");
+    }
+    let response = get(&serve_dir, "/./docs/%69ndex.html", &[]).await;
+    assert_eq!(body(response).await, "<p>docs</p>");
+
+    // conditional requests
+    let response = get(&serve_dir, "/data.txt", &[(IF_NONE_MATCH, &etag)]).await;
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+    assert!(body(response).await.is_empty());
+    let response = get(&serve_dir, "/data.txt", &[(IF_NONE_MATCH, "\"other\"")]).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = get(
+        &serve_dir,
+        "/data.txt",
+        &[(IF_MODIFIED_SINCE, &last_modified)],
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+    let response = get(
+        &serve_dir,
+        "/data.txt",
+        &[(IF_MODIFIED_SINCE, "Thu, 01 Jan 1970 00:00:00 GMT")],
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // range requests
+    let response = get(&serve_dir, "/data.txt", &[(RANGE, "bytes=2-4")]).await;
+    assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+    assert_eq!(response.headers()[CONTENT_RANGE], "bytes 2-4/10");
+    assert_eq!(response.headers()[CONTENT_LENGTH], "3");
+    assert_eq!(body(response).await, "234");
+    let response = get(&serve_dir, "/data.txt", &[(RANGE, "bytes=-3")]).await;
+    assert_eq!(body(response).await, "789");
+    let response = get(&serve_dir, "/data.txt", &[(RANGE, "bytes=10-")]).await;
+    assert_eq!(response.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+    assert_eq!(response.headers()[CONTENT_RANGE], "bytes */10");
+    let response = get(
+        &serve_dir,
+        "/data.txt",
+        &[(RANGE, "bytes=2-4"), (IF_RANGE, "\"outdated\"")],
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(body(response).await, "0123456789");
+    let response = get(
+        &serve_dir,
+        "/data.txt",
+        &[(RANGE, "bytes=2-4"), (IF_RANGE, &etag)],
+    )
+    .await;
+    assert_eq!(body(response).await, "234");
+
+    assert_eq!(
+        RangeRequest::parse(Some("bytes=5-100"), 10),
+        RangeRequest::Partial(5..=9)
+    );
+    assert_eq!(
+        RangeRequest::parse(Some("bytes=0-1,4-5"), 10),
+        RangeRequest::Full
+    );
+    assert_eq!(
+        RangeRequest::parse(Some("bytes=4-2"), 10),
+        RangeRequest::Full
+    );
+    assert_eq!(
+        RangeRequest::parse(Some("items=0-1"), 10),
+        RangeRequest::Full
+    );
+    assert_eq!(
+        RangeRequest::parse(Some("bytes=-0"), 10),
+        RangeRequest::Unsatisfiable
+    );
+
+    // precompressed siblings
+    let response = get(&serve_dir, "/app.js", &[(ACCEPT_ENCODING, "gzip, br")]).await;
+    assert!(response.headers().get(CONTENT_ENCODING).is_none());
+    assert_eq!(body(response).await, "let app;");
+
+    let serve_dir = ServeDir::new(&root).precompressed_br().precompressed_gzip();
+    let response = get(&serve_dir, "/app.js", &[(ACCEPT_ENCODING, "gzip, br")]).await;
+    assert_eq!(response.headers()[CONTENT_ENCODING], "br");
+    assert_eq!(response.headers()[CONTENT_TYPE], "text/javascript");
+    assert_eq!(body(response).await, "brotli");
+    let response = get(&serve_dir, "/app.js", &[(ACCEPT_ENCODING, "gzip, br;q=0")]).await;
+    assert_eq!(response.headers()[CONTENT_ENCODING], "gzip");
+    assert_eq!(body(response).await, "gzipped");
+    let response = get(&serve_dir, "/app.js", &[]).await;
+    assert!(response.headers().get(CONTENT_ENCODING).is_none());
+    assert_eq!(body(response).await, "let app;");
+
+    // index files and the fallback
+    let serve_dir = ServeDir::new(&root).index_file("index.html");
+    let response = get(&serve_dir, "/docs", &[]).await;
+    assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+    assert_eq!(response.headers()[LOCATION], "./docs/");
+    let response = get(&serve_dir, "/docs?lang=en", &[]).await;
+    assert_eq!(response.headers()[LOCATION], "./docs/?lang=en");
+    let response = get(&serve_dir, "/docs/", &[]).await;
+    assert_eq!(response.headers()[CONTENT_TYPE], "text/html");
+    assert_eq!(body(response).await, "<p>docs</p>");
+    let response = get(&serve_dir, "/", &[]).await;
+    assert_eq!(body(response).await, "<p>app</p>");
+    let response = get(&serve_dir, "/some/route", &[]).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let serve_dir = serve_dir.fallback("index.html");
+    let response = get(&serve_dir, "/some/route", &[]).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(body(response).await, "<p>app</p>");
+    let response = get(&serve_dir, "/../secret.txt", &[]).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
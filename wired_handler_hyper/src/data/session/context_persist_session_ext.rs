@@ -0,0 +1,151 @@
+use std::{future::Future, time::SystemTime};
+
+use wired_handler::{Context, GetState};
+
+use super::{
+    SessionData, SessionRecord, SessionStorage, SessionStoreError, SessionTimestamps,
+    SharedSessionStore, session_sweeper::session_config,
+};
+use crate::{
+    data::{config::SessionConfig, session_id::SessionId},
+    prelude::*,
+    state::{global_state::GlobalState, session_state::SessionState},
+};
+
+/// Loads a session from the `SharedSessionStore` and inserts it into the `SessionStorage`.
+/// Returns `None` if there is no store or it doesn't know the session
+pub(crate) async fn rehydrate_session(
+    global_state: &GlobalState,
+    session_id: SessionId,
+    session_config: &SessionConfig,
+) -> Option<SessionState> {
+    let session_store = global_state.get_cloned::<SharedSessionStore>().await?;
+
+    let session_record = match session_store.get().load(session_id).await {
+        Ok(session_record) => session_record?,
+        Err(err) => {
+            tracing::warn!("failed to load session {session_id}: {err}");
+            return None;
+        }
+    };
+
+    if session_record.is_expired(session_config, SystemTime::now()) {
+        remove_stored_session(global_state, session_id).await;
+        return None;
+    }
+
+    let mut session_timestamps = session_record.timestamps();
+    session_timestamps.touch();
+    let mut session_data = session_record.data;
+    session_data.mark_saved(session_record.last_access);
+
+    let session_state = SessionState::default();
+    session_state.insert(session_id).await;
+    session_state.insert(session_timestamps).await;
+    session_state.insert(session_data).await;
+
+    // another request could have loaded the session in the meantime
+    Some(
+        global_state
+            .get_mut_or_insert_default::<SessionStorage>()
+            .await
+            .get_mut()
+            .entry(session_id)
+            .or_insert(session_state)
+            .clone(),
+    )
+}
+
+/// Removes a session from the `SharedSessionStore` if there is one
+pub(crate) async fn remove_stored_session(global_state: &GlobalState, session_id: SessionId) {
+    let Some(session_store) = global_state.get_cloned::<SharedSessionStore>().await else {
+        return;
+    };
+
+    if let Err(err) = session_store.get().remove(session_id).await {
+        tracing::warn!("failed to remove session {session_id} from store: {err}");
+    }
+}
+
+/// Removes all expired sessions from the `SharedSessionStore` if there is one
+pub(crate) async fn remove_expired_stored_sessions(
+    global_state: &GlobalState,
+    session_config: &SessionConfig,
+    now: SystemTime,
+) -> usize {
+    let Some(session_store) = global_state.get_cloned::<SharedSessionStore>().await else {
+        return 0;
+    };
+
+    session_store
+        .get()
+        .remove_expired(session_config, now)
+        .await
+        .inspect_err(|err| tracing::warn!("failed to remove expired sessions from store: {err}"))
+        .unwrap_or_default()
+}
+
+/// For saving the `SessionData` to the `SharedSessionStore`
+pub trait ContextPersistSessionExt {
+    /// Saves the `SessionData` to the `SharedSessionStore` in the `GlobalState` if it has changed.
+    /// Has no effect if there is no `SharedSessionStore`
+    ///
+    /// Done automatically after every HTTP request. The last access is saved again after a quarter of the idle timeout
+    fn persist_session(&self) -> impl Future<Output = Result<(), SessionStoreError>>;
+}
+
+// impl for any `Context` that contains a `SessionState`
+impl<T: Context> ContextPersistSessionExt for T
+where
+    GlobalState: GetState<T>,
+    SessionState: GetState<T>,
+{
+    async fn persist_session(&self) -> Result<(), SessionStoreError> {
+        let global_state = GlobalState::get_from_ctx(self);
+        let Some(session_store) = global_state.get_cloned::<SharedSessionStore>().await else {
+            return Ok(());
+        };
+
+        let session_state = SessionState::get_from_ctx(self);
+        let Some(session_id) = session_state.get_cloned::<SessionId>().await else {
+            return Ok(());
+        };
+        let session_timestamps = session_state
+            .get_cloned::<SessionTimestamps>()
+            .await
+            .unwrap_or_default();
+        let session_config = session_config(global_state).await;
+
+        let session_record = {
+            let Some(mut session_data) = session_state.get_mut::<SessionData>().await else {
+                return Ok(());
+            };
+
+            // the stored last access must stay recent, otherwise the store considers the session idle
+            let refresh_due = session_config.idle_timeout().is_some_and(|idle_timeout| {
+                session_data.last_saved().is_none_or(|last_saved| {
+                    session_timestamps
+                        .last_access()
+                        .duration_since(last_saved)
+                        .unwrap_or_default()
+                        > idle_timeout / 4
+                })
+            });
+            if !session_data.is_changed() && !refresh_due {
+                return Ok(());
+            }
+
+            session_data.mark_saved(session_timestamps.last_access());
+            SessionRecord::new(session_data.clone(), session_timestamps)
+        };
+
+        if let Err(err) = session_store.get().save(session_id, session_record).await {
+            if let Some(mut session_data) = session_state.get_mut::<SessionData>().await {
+                session_data.mark_changed();
+            }
+            return Err(err);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,170 @@
+use std::{future::Future, time::SystemTime};
+
+use cookie::Cookie;
+use http::{HeaderMap, header::COOKIE};
+use wired_handler::{Context, GetState};
+
+use super::{
+    SessionStorage, SessionTimestamps,
+    context_persist_session_ext::rehydrate_session,
+    session_sweeper::{ensure_session_sweeper, evict_session, is_session_expired, session_config},
+};
+use crate::{
+    data::{config::SessionConfig, session_id::SessionId},
+    prelude::*,
+    state::{
+        context::{HttpRequestContext, SessionlessRequestContext},
+        global_state::GlobalState,
+        request_state::RequestState,
+        session_state::SessionState,
+    },
+};
+
+/// Returns the `SessionId` sent by the client, if it's valid
+fn requested_session_id(headers: &HeaderMap, cookie_name: &str) -> Option<SessionId> {
+    headers
+        .get_all(COOKIE)
+        .iter()
+        .filter_map(|header_value| header_value.to_str().ok())
+        .flat_map(Cookie::split_parse)
+        .filter_map(Result::ok)
+        .filter(|cookie| cookie.name() == cookie_name)
+        .find_map(|cookie| cookie.value().parse().ok())
+}
+
+/// Creates the cookie for the session
+fn session_cookie(session_config: &SessionConfig, session_id: SessionId) -> Cookie<'static> {
+    let mut cookie_builder =
+        Cookie::build((session_config.cookie_name.as_str(), session_id.to_string()))
+            .path(session_config.cookie_path.as_str())
+            .same_site(session_config.same_site.into())
+            .secure(session_config.secure)
+            .http_only(session_config.http_only);
+
+    if let Some(max_age) = session_config
+        .max_lifetime()
+        .and_then(|max_lifetime| cookie::time::Duration::try_from(max_lifetime).ok())
+    {
+        cookie_builder = cookie_builder.max_age(max_age);
+    }
+
+    cookie_builder.build().into_owned()
+}
+
+// Different implementation needed because we can't produce a Request<Incoming>
+#[cfg(test)]
+fn request_headers(ctx: &SessionlessRequestContext) -> &HeaderMap {
+    RequestState::get_from_ctx(ctx)
+        .get::<HeaderMap>()
+        .expect("must have headers (as HeaderMap)")
+}
+
+#[cfg(not(test))]
+fn request_headers(ctx: &SessionlessRequestContext) -> &HeaderMap {
+    RequestState::get_from_ctx(ctx)
+        .get::<crate::data::request::Request>()
+        .expect("every SessionlessRequestContext must have a Request")
+        .headers()
+}
+
+/// For resolving the `SessionState` of a request
+pub trait ContextResolveSessionExt {
+    /// Looks up the `SessionState` by the session cookie and turns the context into an `HttpRequestContext`.
+    /// Sessions not in memory are loaded from the `SharedSessionStore` if there is one.
+    /// Creates a new session if the cookie is missing, unknown or expired, the cookie is then set on the `Response`
+    ///
+    /// The cookie and expiry are configured by the `SessionConfig` in the `GlobalState`, the default is used if there is none.
+    /// Also starts the background task evicting expired sessions
+    fn resolve_session(self) -> impl Future<Output = HttpRequestContext>;
+}
+
+impl ContextResolveSessionExt for SessionlessRequestContext {
+    async fn resolve_session(self) -> HttpRequestContext {
+        let session_config = session_config(GlobalState::get_from_ctx(&self)).await;
+        let requested_session_id =
+            requested_session_id(request_headers(&self), &session_config.cookie_name);
+
+        let (global_state, request_state) = self.into_states();
+        // sessions are shared by all namespaces
+        let root_global_state = global_state.root();
+
+        // look up existing session
+        let existing_session = match requested_session_id {
+            Some(session_id) => {
+                let stored_session_state = root_global_state
+                    .get::<SessionStorage>()
+                    .await
+                    .and_then(|session_storage| session_storage.get().get(&session_id).cloned());
+
+                match stored_session_state {
+                    Some(session_state) => Some((session_id, session_state)),
+                    None => rehydrate_session(&root_global_state, session_id, &session_config)
+                        .await
+                        .map(|session_state| (session_id, session_state)),
+                }
+            }
+            None => None,
+        };
+
+        // evict if expired but not swept yet
+        let existing_session = match existing_session {
+            Some((session_id, session_state))
+                if is_session_expired(&session_state, &session_config, SystemTime::now()).await =>
+            {
+                evict_session(&root_global_state, session_id).await;
+                None
+            }
+            existing_session => existing_session.map(|(_, session_state)| session_state),
+        };
+
+        let (session_state, new_session_id) = match existing_session {
+            Some(session_state) => {
+                if let Some(mut session_timestamps) =
+                    session_state.get_mut::<SessionTimestamps>().await
+                {
+                    session_timestamps.touch();
+                }
+                (session_state, None)
+            }
+            None => {
+                let session_id = SessionId::generate();
+                let session_state = SessionState::default();
+                session_state.insert(session_id).await;
+                session_state.insert(SessionTimestamps::new()).await;
+
+                root_global_state
+                    .get_mut_or_insert_default::<SessionStorage>()
+                    .await
+                    .get_mut()
+                    .insert(session_id, session_state.clone());
+
+                (session_state, Some(session_id))
+            }
+        };
+
+        ensure_session_sweeper(&root_global_state).await;
+
+        let mut ctx = HttpRequestContext::from_states(global_state, session_state, request_state);
+        if let Some(session_id) = new_session_id {
+            ctx.add_cookie(session_cookie(&session_config, session_id));
+        }
+
+        ctx
+    }
+}
+
+/// For getting the `SessionId` of a context
+pub trait ContextGetSessionIdExt {
+    /// Returns the `SessionId` if the session has been resolved by `resolve_session`
+    fn session_id(&self) -> impl Future<Output = Option<SessionId>>;
+}
+
+// impl for any `Context` that contains a `SessionState`
+impl<T: Context> ContextGetSessionIdExt for T
+where
+    SessionState: GetState<T>,
+{
+    async fn session_id(&self) -> Option<SessionId> {
+        SessionState::get_from_ctx(self).get_cloned().await
+    }
+}
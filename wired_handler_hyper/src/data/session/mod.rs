@@ -0,0 +1,17 @@
+pub use context_persist_session_ext::ContextPersistSessionExt;
+pub use context_session_ext::*;
+pub use session_data::*;
+pub use session_storage::*;
+pub use session_store::*;
+pub use session_sweeper::evict_expired_sessions;
+pub use session_timestamps::*;
+
+mod context_persist_session_ext;
+mod context_session_ext;
+mod session_data;
+mod session_storage;
+mod session_store;
+mod session_sweeper;
+mod session_timestamps;
+#[cfg(test)]
+mod test;
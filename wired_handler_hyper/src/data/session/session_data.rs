@@ -0,0 +1,98 @@
+use std::{future::Future, ops::DerefMut, time::SystemTime};
+
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use serde_json::{Map, Value};
+use wired_handler::{Context, GetState};
+
+use crate::{prelude::*, state::session_state::SessionState};
+
+/// Serializable session data, stored in the `SessionState`. Persisted by the `SessionStore` if there is one
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SessionData {
+    values: Map<String, Value>,
+    #[serde(skip)]
+    changed: bool,
+    #[serde(skip)]
+    last_saved: Option<SystemTime>,
+}
+
+impl SessionData {
+    /// Deserializes the value stored under `key`
+    pub fn get<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>, serde_json::Error> {
+        self.values
+            .get(key)
+            .map(|value| T::deserialize(value))
+            .transpose()
+    }
+
+    /// Serializes `value` and stores it under `key`
+    pub fn insert<T: Serialize>(
+        &mut self,
+        key: impl Into<String>,
+        value: &T,
+    ) -> Result<(), serde_json::Error> {
+        self.values.insert(key.into(), serde_json::to_value(value)?);
+        self.changed = true;
+
+        Ok(())
+    }
+
+    /// Removes the value stored under `key`
+    pub fn remove(&mut self, key: &str) -> Option<Value> {
+        let removed_value = self.values.remove(key);
+        self.changed |= removed_value.is_some();
+        removed_value
+    }
+
+    /// Removes all values
+    pub fn clear(&mut self) {
+        self.changed |= !self.values.is_empty();
+        self.values.clear();
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.values.contains_key(key)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Whether the data has changed since it has last been saved
+    pub fn is_changed(&self) -> bool {
+        self.changed
+    }
+
+    /// When the data has last been saved, `None` if it never has been
+    pub fn last_saved(&self) -> Option<SystemTime> {
+        self.last_saved
+    }
+
+    pub(crate) fn mark_saved(&mut self, saved_at: SystemTime) {
+        self.changed = false;
+        self.last_saved = Some(saved_at);
+    }
+
+    pub(crate) fn mark_changed(&mut self) {
+        self.changed = true;
+    }
+}
+
+/// For accessing the `SessionData` of a context
+pub trait ContextSessionDataExt {
+    /// Returns the `SessionData` of the session mutably, inserts it if not found
+    fn session_data(&self) -> impl Future<Output = impl DerefMut<Target = SessionData>>;
+}
+
+// impl for any `Context` that contains a `SessionState`
+impl<T: Context> ContextSessionDataExt for T
+where
+    SessionState: GetState<T>,
+{
+    async fn session_data(&self) -> impl DerefMut<Target = SessionData> {
+        SessionState::get_from_ctx(self)
+            .get_mut_or_insert_default::<SessionData>()
+            .await
+    }
+}
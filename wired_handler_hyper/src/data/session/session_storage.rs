@@ -0,0 +1,17 @@
+use std::collections::HashMap;
+
+use crate::{data::session_id::SessionId, state::session_state::SessionState};
+
+/// Stores all sessions, identified by `SessionId`
+#[derive(Debug, Default)]
+pub struct SessionStorage(HashMap<SessionId, SessionState>);
+
+impl SessionStorage {
+    pub fn get(&self) -> &HashMap<SessionId, SessionState> {
+        &self.0
+    }
+
+    pub fn get_mut(&mut self) -> &mut HashMap<SessionId, SessionState> {
+        &mut self.0
+    }
+}
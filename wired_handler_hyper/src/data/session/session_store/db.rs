@@ -0,0 +1,171 @@
+use std::time::{Duration, SystemTime};
+
+use diesel::{
+    BoolExpressionMethods, ExpressionMethods, OptionalExtension, QueryDsl, upsert::excluded,
+};
+use diesel_async::RunQueryDsl;
+use futures::future::BoxFuture;
+
+use super::{SessionRecord, SessionStore, SessionStoreError};
+use crate::data::{config::SessionConfig, db::DbPool, session_id::SessionId};
+
+mod schema {
+    diesel::table! {
+        wired_handler_sessions (id) {
+            id -> Text,
+            data -> Text,
+            created_at -> BigInt,
+            last_access -> BigInt,
+        }
+    }
+}
+
+use schema::wired_handler_sessions as sessions;
+
+fn to_unix_millis(timestamp: SystemTime) -> i64 {
+    let millis = timestamp
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    i64::try_from(millis).unwrap_or(i64::MAX)
+}
+
+fn from_unix_millis(millis: i64) -> SystemTime {
+    SystemTime::UNIX_EPOCH + Duration::from_millis(u64::try_from(millis).unwrap_or_default())
+}
+
+/// Unix millis before which a timestamp is older than `max_age` at `now`. `i64::MIN` (never) if `max_age` is `None`
+fn expiry_threshold(now: SystemTime, max_age: Option<Duration>) -> i64 {
+    max_age
+        .and_then(|max_age| now.checked_sub(max_age))
+        .map_or(i64::MIN, to_unix_millis)
+}
+
+/// Stores sessions in the `wired_handler_sessions` table, using the `DbPool`
+///
+/// The table can be created using `create_table` or by adding `DbSessionStore::CREATE_TABLE_SQL` to your migrations
+#[derive(Clone)]
+pub struct DbSessionStore {
+    db_pool: DbPool,
+}
+
+impl std::fmt::Debug for DbSessionStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DbSessionStore").finish_non_exhaustive()
+    }
+}
+
+impl DbSessionStore {
+    /// SQL creating the table used by the `DbSessionStore`
+    pub const CREATE_TABLE_SQL: &str = "CREATE TABLE IF NOT EXISTS wired_handler_sessions (
+    id TEXT PRIMARY KEY,
+    data TEXT NOT NULL,
+    created_at BIGINT NOT NULL,
+    last_access BIGINT NOT NULL
+)";
+
+    pub fn new(db_pool: DbPool) -> Self {
+        Self { db_pool }
+    }
+
+    /// Creates the table used by the `DbSessionStore` if it doesn't exist
+    pub async fn create_table(&self) -> Result<(), SessionStoreError> {
+        let mut db = self.db_pool.get().await?;
+        diesel::sql_query(Self::CREATE_TABLE_SQL)
+            .execute(&mut db)
+            .await?;
+
+        Ok(())
+    }
+}
+
+impl SessionStore for DbSessionStore {
+    fn load(
+        &self,
+        session_id: SessionId,
+    ) -> BoxFuture<'_, Result<Option<SessionRecord>, SessionStoreError>> {
+        Box::pin(async move {
+            let mut db = self.db_pool.get().await?;
+            let row: Option<(String, i64, i64)> = sessions::table
+                .find(session_id.to_string())
+                .select((sessions::data, sessions::created_at, sessions::last_access))
+                .first(&mut db)
+                .await
+                .optional()?;
+
+            let Some((serialized_data, created_at, last_access)) = row else {
+                return Ok(None);
+            };
+
+            Ok(Some(SessionRecord {
+                data: serde_json::from_str(&serialized_data)?,
+                created_at: from_unix_millis(created_at),
+                last_access: from_unix_millis(last_access),
+            }))
+        })
+    }
+
+    fn save(
+        &self,
+        session_id: SessionId,
+        session_record: SessionRecord,
+    ) -> BoxFuture<'_, Result<(), SessionStoreError>> {
+        Box::pin(async move {
+            let serialized_data = serde_json::to_string(&session_record.data)?;
+
+            let mut db = self.db_pool.get().await?;
+            diesel::insert_into(sessions::table)
+                .values((
+                    sessions::id.eq(session_id.to_string()),
+                    sessions::data.eq(serialized_data),
+                    sessions::created_at.eq(to_unix_millis(session_record.created_at)),
+                    sessions::last_access.eq(to_unix_millis(session_record.last_access)),
+                ))
+                .on_conflict(sessions::id)
+                .do_update()
+                .set((
+                    sessions::data.eq(excluded(sessions::data)),
+                    sessions::last_access.eq(excluded(sessions::last_access)),
+                ))
+                .execute(&mut db)
+                .await?;
+
+            Ok(())
+        })
+    }
+
+    fn remove(&self, session_id: SessionId) -> BoxFuture<'_, Result<(), SessionStoreError>> {
+        Box::pin(async move {
+            let mut db = self.db_pool.get().await?;
+            diesel::delete(sessions::table.find(session_id.to_string()))
+                .execute(&mut db)
+                .await?;
+
+            Ok(())
+        })
+    }
+
+    fn remove_expired<'a>(
+        &'a self,
+        session_config: &'a SessionConfig,
+        now: SystemTime,
+    ) -> BoxFuture<'a, Result<usize, SessionStoreError>> {
+        Box::pin(async move {
+            let idle_threshold = expiry_threshold(now, session_config.idle_timeout());
+            let lifetime_threshold = expiry_threshold(now, session_config.max_lifetime());
+
+            let mut db = self.db_pool.get().await?;
+            let removed_count = diesel::delete(
+                sessions::table.filter(
+                    sessions::last_access
+                        .lt(idle_threshold)
+                        .or(sessions::created_at.lt(lifetime_threshold)),
+                ),
+            )
+            .execute(&mut db)
+            .await?;
+
+            Ok(removed_count)
+        })
+    }
+}
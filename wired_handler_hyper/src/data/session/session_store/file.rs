@@ -0,0 +1,125 @@
+use std::{
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use futures::future::BoxFuture;
+
+use super::{SessionRecord, SessionStore, SessionStoreError};
+use crate::data::{config::SessionConfig, session_id::SessionId};
+
+/// Stores every session as a JSON file in a directory
+#[derive(Debug, Clone)]
+pub struct FileSessionStore {
+    directory: PathBuf,
+}
+
+impl FileSessionStore {
+    /// Creates a `FileSessionStore` using `directory`, which is created if it doesn't exist
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            directory: directory.into(),
+        }
+    }
+
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    fn session_path(&self, session_id: SessionId) -> PathBuf {
+        self.directory.join(format!("{session_id}.json"))
+    }
+}
+
+/// Removes the file, ignoring it if it doesn't exist
+async fn remove_file_if_exists(path: &Path) -> Result<(), SessionStoreError> {
+    match tokio::fs::remove_file(path).await {
+        Err(err) if err.kind() != ErrorKind::NotFound => Err(err.into()),
+        _ => Ok(()),
+    }
+}
+
+impl SessionStore for FileSessionStore {
+    fn load(
+        &self,
+        session_id: SessionId,
+    ) -> BoxFuture<'_, Result<Option<SessionRecord>, SessionStoreError>> {
+        Box::pin(async move {
+            let serialized_record = match tokio::fs::read(self.session_path(session_id)).await {
+                Ok(serialized_record) => serialized_record,
+                Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+                Err(err) => return Err(err.into()),
+            };
+
+            Ok(Some(serde_json::from_slice(&serialized_record)?))
+        })
+    }
+
+    fn save(
+        &self,
+        session_id: SessionId,
+        session_record: SessionRecord,
+    ) -> BoxFuture<'_, Result<(), SessionStoreError>> {
+        Box::pin(async move {
+            let serialized_record = serde_json::to_vec(&session_record)?;
+            tokio::fs::create_dir_all(&self.directory).await?;
+
+            // write to a temporary file first, so a session is never partially written
+            let session_path = self.session_path(session_id);
+            let temporary_path = session_path.with_extension("json.tmp");
+            tokio::fs::write(&temporary_path, serialized_record).await?;
+            tokio::fs::rename(&temporary_path, &session_path).await?;
+
+            Ok(())
+        })
+    }
+
+    fn remove(&self, session_id: SessionId) -> BoxFuture<'_, Result<(), SessionStoreError>> {
+        Box::pin(async move { remove_file_if_exists(&self.session_path(session_id)).await })
+    }
+
+    fn remove_expired<'a>(
+        &'a self,
+        session_config: &'a SessionConfig,
+        now: SystemTime,
+    ) -> BoxFuture<'a, Result<usize, SessionStoreError>> {
+        Box::pin(async move {
+            let mut entries = match tokio::fs::read_dir(&self.directory).await {
+                Ok(entries) => entries,
+                Err(err) if err.kind() == ErrorKind::NotFound => return Ok(0),
+                Err(err) => return Err(err.into()),
+            };
+
+            let mut removed_count = 0;
+            while let Some(entry) = entries.next_entry().await? {
+                let path = entry.path();
+                if path.extension().is_none_or(|extension| extension != "json") {
+                    continue;
+                }
+
+                let is_expired = match tokio::fs::read(&path).await {
+                    Ok(serialized_record) => {
+                        match serde_json::from_slice::<SessionRecord>(&serialized_record) {
+                            Ok(session_record) => session_record.is_expired(session_config, now),
+                            Err(err) => {
+                                tracing::debug!("skipping invalid session file {path:?}: {err}");
+                                false
+                            }
+                        }
+                    }
+                    // removed in the meantime
+                    Err(err) if err.kind() == ErrorKind::NotFound => false,
+                    Err(err) => return Err(err.into()),
+                };
+
+                if is_expired {
+                    remove_file_if_exists(&path).await?;
+                    removed_count += 1;
+                }
+            }
+
+            Ok(removed_count)
+        })
+    }
+}
@@ -0,0 +1,59 @@
+use std::{collections::HashMap, time::SystemTime};
+
+use futures::future::BoxFuture;
+use tokio::sync::RwLock;
+
+use super::{SessionRecord, SessionStore, SessionStoreError};
+use crate::data::{config::SessionConfig, session_id::SessionId};
+
+/// Keeps sessions in memory, they are lost on restart
+#[derive(Debug, Default)]
+pub struct MemorySessionStore(RwLock<HashMap<SessionId, SessionRecord>>);
+
+impl MemorySessionStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl SessionStore for MemorySessionStore {
+    fn load(
+        &self,
+        session_id: SessionId,
+    ) -> BoxFuture<'_, Result<Option<SessionRecord>, SessionStoreError>> {
+        Box::pin(async move { Ok(self.0.read().await.get(&session_id).cloned()) })
+    }
+
+    fn save(
+        &self,
+        session_id: SessionId,
+        session_record: SessionRecord,
+    ) -> BoxFuture<'_, Result<(), SessionStoreError>> {
+        Box::pin(async move {
+            self.0.write().await.insert(session_id, session_record);
+            Ok(())
+        })
+    }
+
+    fn remove(&self, session_id: SessionId) -> BoxFuture<'_, Result<(), SessionStoreError>> {
+        Box::pin(async move {
+            self.0.write().await.remove(&session_id);
+            Ok(())
+        })
+    }
+
+    fn remove_expired<'a>(
+        &'a self,
+        session_config: &'a SessionConfig,
+        now: SystemTime,
+    ) -> BoxFuture<'a, Result<usize, SessionStoreError>> {
+        Box::pin(async move {
+            let mut session_records = self.0.write().await;
+            let count_before = session_records.len();
+            session_records
+                .retain(|_, session_record| !session_record.is_expired(session_config, now));
+
+            Ok(count_before - session_records.len())
+        })
+    }
+}
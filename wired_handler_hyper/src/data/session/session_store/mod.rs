@@ -0,0 +1,97 @@
+use std::{fmt::Debug, sync::Arc, time::SystemTime};
+
+use futures::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::{SessionData, SessionTimestamps};
+use crate::data::{config::SessionConfig, session_id::SessionId};
+
+#[cfg(feature = "diesel")]
+pub use db::*;
+pub use file::*;
+pub use memory::*;
+
+#[cfg(feature = "diesel")]
+mod db;
+mod file;
+mod memory;
+
+/// A persisted session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionRecord {
+    pub data: SessionData,
+    pub created_at: SystemTime,
+    pub last_access: SystemTime,
+}
+
+impl SessionRecord {
+    pub fn new(data: SessionData, session_timestamps: SessionTimestamps) -> Self {
+        Self {
+            data,
+            created_at: session_timestamps.created_at(),
+            last_access: session_timestamps.last_access(),
+        }
+    }
+
+    pub fn timestamps(&self) -> SessionTimestamps {
+        SessionTimestamps::from_parts(self.created_at, self.last_access)
+    }
+
+    /// Whether the session has expired at `now` according to `session_config`
+    pub fn is_expired(&self, session_config: &SessionConfig, now: SystemTime) -> bool {
+        self.timestamps().is_expired(session_config, now)
+    }
+}
+
+/// Error returned by a `SessionStore`
+#[derive(Debug, Error)]
+#[error("{0}")]
+pub enum SessionStoreError {
+    Io(#[from] std::io::Error),
+    Json(#[from] serde_json::Error),
+    #[cfg(feature = "diesel")]
+    Db(#[from] diesel::result::Error),
+    #[cfg(feature = "diesel")]
+    DbPool(#[from] diesel_async::pooled_connection::deadpool::PoolError),
+}
+
+/// Loads and persists `SessionRecord`s, so sessions survive restarts
+pub trait SessionStore: Debug + Send + Sync + 'static {
+    /// Loads the session, `None` if it doesn't exist
+    fn load(
+        &self,
+        session_id: SessionId,
+    ) -> BoxFuture<'_, Result<Option<SessionRecord>, SessionStoreError>>;
+
+    /// Saves the session, overwriting it if it exists
+    fn save(
+        &self,
+        session_id: SessionId,
+        session_record: SessionRecord,
+    ) -> BoxFuture<'_, Result<(), SessionStoreError>>;
+
+    /// Removes the session if it exists
+    fn remove(&self, session_id: SessionId) -> BoxFuture<'_, Result<(), SessionStoreError>>;
+
+    /// Removes all sessions expired at `now`, returns the count of removed sessions
+    fn remove_expired<'a>(
+        &'a self,
+        session_config: &'a SessionConfig,
+        now: SystemTime,
+    ) -> BoxFuture<'a, Result<usize, SessionStoreError>>;
+}
+
+/// The `SessionStore` used for sessions, insert it into the `GlobalState` to persist sessions
+#[derive(Debug, Clone)]
+pub struct SharedSessionStore(Arc<dyn SessionStore>);
+
+impl SharedSessionStore {
+    pub fn new(session_store: impl SessionStore) -> Self {
+        Self(Arc::new(session_store))
+    }
+
+    pub fn get(&self) -> &dyn SessionStore {
+        self.0.as_ref()
+    }
+}
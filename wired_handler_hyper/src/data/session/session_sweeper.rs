@@ -0,0 +1,174 @@
+use std::time::SystemTime;
+
+use tokio::task::JoinHandle;
+
+use super::{
+    SessionStorage, SessionTimestamps,
+    context_persist_session_ext::{remove_expired_stored_sessions, remove_stored_session},
+};
+#[cfg(feature = "websocket")]
+use crate::data::connection_storage::{ConnectionStorage, close_connections};
+use crate::{
+    data::{config::SessionConfig, session_id::SessionId},
+    prelude::*,
+    state::{
+        global_state::{GlobalState, WeakGlobalState},
+        session_state::SessionState,
+    },
+};
+
+/// Handle of the task evicting expired sessions, stored in the `GlobalState`. Aborts the task when dropped
+#[derive(Debug)]
+pub(crate) struct SessionSweeper(JoinHandle<()>);
+
+impl Drop for SessionSweeper {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// Returns the `SessionConfig` from the `GlobalState` or the default if there is none
+pub(crate) async fn session_config(global_state: &GlobalState) -> SessionConfig {
+    global_state
+        .get_cloned::<SessionConfig>()
+        .await
+        .unwrap_or_default()
+}
+
+/// Starts the task evicting expired sessions if it isn't running yet. The task stops when the `GlobalState` is dropped
+pub(crate) async fn ensure_session_sweeper(global_state: &GlobalState) {
+    if global_state.exists::<SessionSweeper>().await {
+        return;
+    }
+
+    // only spawned once if several requests get here at the same time
+    let weak_global_state = global_state.downgrade();
+    global_state
+        .get_mut_or_insert_with(|| {
+            SessionSweeper(tokio::spawn(run_session_sweeper(weak_global_state)))
+        })
+        .await;
+}
+
+async fn run_session_sweeper(weak_global_state: WeakGlobalState) {
+    loop {
+        let sweep_interval = match weak_global_state.upgrade() {
+            Some(global_state) => session_config(&global_state).await.sweep_interval(),
+            None => return,
+        };
+
+        tokio::time::sleep(sweep_interval).await;
+
+        let Some(global_state) = weak_global_state.upgrade() else {
+            return;
+        };
+
+        let evicted_count = evict_expired_sessions(&global_state).await;
+        if evicted_count > 0 {
+            tracing::debug!("evicted {evicted_count} expired sessions");
+        }
+    }
+}
+
+/// Whether the session has expired. Sessions without `SessionTimestamps` never expire
+pub(crate) async fn is_session_expired(
+    session_state: &SessionState,
+    session_config: &SessionConfig,
+    now: SystemTime,
+) -> bool {
+    session_state
+        .get::<SessionTimestamps>()
+        .await
+        .is_some_and(|session_timestamps| session_timestamps.is_expired(session_config, now))
+}
+
+/// Sends a close frame to all websocket connections of the session
+#[cfg(feature = "websocket")]
+async fn close_session_connections(session_state: &SessionState) {
+    let connection_states: Vec<_> = match session_state.get::<ConnectionStorage>().await {
+        Some(connection_storage) => connection_storage.get().values().cloned().collect(),
+        None => return,
+    };
+
+    close_connections(connection_states).await;
+}
+
+#[cfg(not(feature = "websocket"))]
+async fn close_session_connections(_session_state: &SessionState) {}
+
+/// Removes a single session from the `SessionStorage` and `SharedSessionStore`, closing its websocket connections
+pub(crate) async fn evict_session(global_state: &GlobalState, session_id: SessionId) {
+    remove_stored_session(global_state, session_id).await;
+
+    let evicted_session_state = match global_state.get_mut::<SessionStorage>().await {
+        Some(mut session_storage) => session_storage.get_mut().remove(&session_id),
+        None => None,
+    };
+
+    if let Some(session_state) = evicted_session_state {
+        close_session_connections(&session_state).await;
+    }
+}
+
+/// Evicts all expired sessions from the `SessionStorage`, closing their websocket connections.
+/// Returns the count of evicted sessions. Also removes expired sessions from the `SharedSessionStore`
+///
+/// Runs periodically in the background once `resolve_session` has been used
+pub async fn evict_expired_sessions(global_state: &GlobalState) -> usize {
+    // sessions are shared by all namespaces
+    let global_state = &global_state.root();
+    let session_config = session_config(global_state).await;
+    let now = SystemTime::now();
+
+    let removed_stored_count =
+        remove_expired_stored_sessions(global_state, &session_config, now).await;
+    if removed_stored_count > 0 {
+        tracing::debug!("removed {removed_stored_count} expired sessions from store");
+    }
+
+    let session_states: Vec<_> = match global_state.get::<SessionStorage>().await {
+        Some(session_storage) => session_storage
+            .get()
+            .iter()
+            .map(|(&session_id, session_state)| (session_id, session_state.clone()))
+            .collect(),
+        None => return 0,
+    };
+
+    let mut expired_session_ids = Vec::new();
+    for (session_id, session_state) in session_states {
+        if is_session_expired(&session_state, &session_config, now).await {
+            expired_session_ids.push(session_id);
+        }
+    }
+
+    if expired_session_ids.is_empty() {
+        return 0;
+    }
+
+    let evicted_session_states = {
+        let Some(mut session_storage) = global_state.get_mut::<SessionStorage>().await else {
+            return 0;
+        };
+
+        let mut evicted_session_states = Vec::new();
+        for session_id in expired_session_ids {
+            // the session could have been accessed in the meantime
+            let Some(session_state) = session_storage.get().get(&session_id) else {
+                continue;
+            };
+            if !is_session_expired(session_state, &session_config, now).await {
+                continue;
+            }
+
+            evicted_session_states.extend(session_storage.get_mut().remove(&session_id));
+        }
+        evicted_session_states
+    };
+
+    for session_state in &evicted_session_states {
+        close_session_connections(session_state).await;
+    }
+
+    evicted_session_states.len()
+}
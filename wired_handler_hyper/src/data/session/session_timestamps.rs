@@ -0,0 +1,59 @@
+use std::time::SystemTime;
+
+use crate::data::config::SessionConfig;
+
+/// Tracks when a session has been created and last accessed. Stored in the `SessionState`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SessionTimestamps {
+    created_at: SystemTime,
+    last_access: SystemTime,
+}
+
+impl Default for SessionTimestamps {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SessionTimestamps {
+    /// Creates timestamps for a session created now
+    pub fn new() -> Self {
+        let now = SystemTime::now();
+        Self::from_parts(now, now)
+    }
+
+    pub fn from_parts(created_at: SystemTime, last_access: SystemTime) -> Self {
+        Self {
+            created_at,
+            last_access,
+        }
+    }
+
+    pub fn created_at(&self) -> SystemTime {
+        self.created_at
+    }
+
+    pub fn last_access(&self) -> SystemTime {
+        self.last_access
+    }
+
+    /// Sets the last access to now
+    pub fn touch(&mut self) {
+        self.last_access = SystemTime::now();
+    }
+
+    /// Whether the session has expired at `now` according to `session_config`
+    pub fn is_expired(&self, session_config: &SessionConfig, now: SystemTime) -> bool {
+        let elapsed_since =
+            |timestamp: SystemTime| now.duration_since(timestamp).unwrap_or_default();
+
+        let idle_expired = session_config
+            .idle_timeout()
+            .is_some_and(|idle_timeout| elapsed_since(self.last_access) > idle_timeout);
+        let lifetime_expired = session_config
+            .max_lifetime()
+            .is_some_and(|max_lifetime| elapsed_since(self.created_at) > max_lifetime);
+
+        idle_expired || lifetime_expired
+    }
+}
@@ -0,0 +1,477 @@
+use std::time::{Duration, SystemTime};
+
+use http::{
+    HeaderMap, HeaderValue,
+    header::{COOKIE, SET_COOKIE},
+};
+use wired_handler::ContextBuilder;
+
+use super::{
+    ContextGetSessionIdExt, ContextPersistSessionExt, ContextResolveSessionExt,
+    ContextSessionDataExt, FileSessionStore, MemorySessionStore, SessionData, SessionRecord,
+    SessionStorage, SessionTimestamps, SharedSessionStore, evict_expired_sessions,
+};
+use crate::{
+    data::{
+        config::{SameSite, SessionConfig},
+        cookies::apply_cookies,
+        response::Response,
+        response_body::ResponseBody,
+        session_id::SessionId,
+    },
+    prelude::*,
+    state::{
+        context::{HttpRequestContext, SessionlessRequestContextBuilder},
+        global_state::GlobalState,
+        request_state::RequestState,
+        session_state::SessionState,
+    },
+};
+
+#[test]
+fn test() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    runtime.block_on(run_test());
+}
+
+#[test]
+fn test_expiry() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    runtime.block_on(run_test_expiry());
+}
+
+#[test]
+fn test_store() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    runtime.block_on(run_test_store());
+}
+
+#[test]
+fn test_namespace() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    runtime.block_on(run_test_namespace());
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Marker(u32);
+
+async fn resolve(global_state: &GlobalState, cookie: Option<&str>) -> HttpRequestContext {
+    let mut headers = HeaderMap::new();
+    if let Some(cookie) = cookie {
+        headers.insert(COOKIE, HeaderValue::from_str(cookie).unwrap());
+    }
+    let mut request_state = RequestState::default();
+    request_state.insert(headers);
+
+    SessionlessRequestContextBuilder { request_state }
+        .build(global_state.clone())
+        .resolve_session()
+        .await
+}
+
+fn set_cookie(ctx: &mut HttpRequestContext) -> Option<String> {
+    let mut response = Response::builder().body(ResponseBody::empty()).unwrap();
+    apply_cookies(RequestState::get_mut_from_ctx(ctx), &mut response);
+
+    response
+        .headers()
+        .get(SET_COOKIE)
+        .map(|header_value| header_value.to_str().unwrap().to_string())
+}
+
+async fn session_count(global_state: &GlobalState) -> usize {
+    global_state
+        .get::<SessionStorage>()
+        .await
+        .map_or(0, |session_storage| session_storage.get().len())
+}
+
+async fn run_test() {
+    // new session without cookie
+    let global_state = GlobalState::default();
+    let mut ctx = resolve(&global_state, None).await;
+    let session_id = ctx.session_id().await.unwrap();
+    let cookie = set_cookie(&mut ctx).unwrap();
+    assert!(cookie.starts_with(&format!("session_id={session_id}")));
+    assert!(cookie.contains("HttpOnly"));
+    assert!(cookie.contains("Secure"));
+    assert!(cookie.contains("SameSite=Lax"));
+    assert!(cookie.contains("Path=/"));
+    assert_eq!(session_count(&global_state).await, 1);
+    SessionState::get_from_ctx(&ctx).insert(Marker(42)).await;
+
+    // existing session is reused
+    {
+        let mut ctx = resolve(
+            &global_state,
+            Some(&format!("other=value; session_id={session_id}")),
+        )
+        .await;
+        assert_eq!(ctx.session_id().await, Some(session_id));
+        assert_eq!(
+            SessionState::get_from_ctx(&ctx)
+                .get_cloned::<Marker>()
+                .await,
+            Some(Marker(42))
+        );
+        assert!(set_cookie(&mut ctx).is_none());
+        assert_eq!(session_count(&global_state).await, 1);
+    }
+
+    // unknown session creates a new one
+    {
+        let unknown_session_id = SessionId::generate();
+        let mut ctx = resolve(
+            &global_state,
+            Some(&format!("session_id={unknown_session_id}")),
+        )
+        .await;
+        let new_session_id = ctx.session_id().await.unwrap();
+        assert_ne!(new_session_id, unknown_session_id);
+        assert_ne!(new_session_id, session_id);
+        assert!(set_cookie(&mut ctx).is_some());
+        assert_eq!(session_count(&global_state).await, 2);
+    }
+
+    // invalid session id creates a new one
+    {
+        let mut ctx = resolve(&global_state, Some("session_id=invalid")).await;
+        assert!(set_cookie(&mut ctx).is_some());
+        assert_eq!(session_count(&global_state).await, 3);
+    }
+
+    // custom config
+    {
+        let global_state = GlobalState::default();
+        global_state
+            .insert(SessionConfig {
+                cookie_name: "sid".into(),
+                cookie_path: "/app".into(),
+                same_site: SameSite::Strict,
+                secure: false,
+                http_only: false,
+                ..Default::default()
+            })
+            .await;
+
+        let mut ctx = resolve(&global_state, Some(&format!("session_id={session_id}"))).await;
+        let new_session_id = ctx.session_id().await.unwrap();
+        let cookie = set_cookie(&mut ctx).unwrap();
+        assert!(cookie.starts_with(&format!("sid={new_session_id}")));
+        assert!(!cookie.contains("HttpOnly"));
+        assert!(!cookie.contains("Secure"));
+        assert!(cookie.contains("SameSite=Strict"));
+        assert!(cookie.contains("Path=/app"));
+
+        let mut ctx = resolve(&global_state, Some(&format!("sid={new_session_id}"))).await;
+        assert_eq!(ctx.session_id().await, Some(new_session_id));
+        assert!(set_cookie(&mut ctx).is_none());
+    }
+}
+
+/// Moves the timestamps of the session into the past
+async fn age_session(ctx: &HttpRequestContext, created_ago: Duration, accessed_ago: Duration) {
+    let now = SystemTime::now();
+    SessionState::get_from_ctx(ctx)
+        .insert(SessionTimestamps::from_parts(
+            now - created_ago,
+            now - accessed_ago,
+        ))
+        .await;
+}
+
+async fn run_test_expiry() {
+    let global_state = GlobalState::default();
+    global_state
+        .insert(SessionConfig {
+            idle_timeout_secs: Some(60),
+            max_lifetime_secs: Some(600),
+            ..Default::default()
+        })
+        .await;
+
+    // timestamps
+    {
+        let session_config = SessionConfig {
+            idle_timeout_secs: Some(60),
+            max_lifetime_secs: Some(600),
+            ..Default::default()
+        };
+        let now = SystemTime::now();
+        let session_timestamps = SessionTimestamps::from_parts(now, now);
+        assert!(!session_timestamps.is_expired(&session_config, now));
+        assert!(!session_timestamps.is_expired(&session_config, now + Duration::from_secs(59)));
+        assert!(session_timestamps.is_expired(&session_config, now + Duration::from_secs(61)));
+
+        let session_timestamps = SessionTimestamps::from_parts(now - Duration::from_secs(601), now);
+        assert!(session_timestamps.is_expired(&session_config, now));
+
+        let session_config = SessionConfig {
+            idle_timeout_secs: None,
+            max_lifetime_secs: None,
+            ..Default::default()
+        };
+        let session_timestamps = SessionTimestamps::from_parts(SystemTime::UNIX_EPOCH, now);
+        assert!(!session_timestamps.is_expired(&session_config, now));
+    }
+
+    // max lifetime is sent as Max-Age
+    let mut ctx = resolve(&global_state, None).await;
+    assert!(set_cookie(&mut ctx).unwrap().contains("Max-Age=600"));
+    let session_id = ctx.session_id().await.unwrap();
+
+    // access refreshes the last access
+    {
+        age_session(&ctx, Duration::from_secs(30), Duration::from_secs(30)).await;
+        let ctx = resolve(&global_state, Some(&format!("session_id={session_id}"))).await;
+        let session_timestamps = SessionState::get_from_ctx(&ctx)
+            .get_cloned::<SessionTimestamps>()
+            .await
+            .unwrap();
+        assert!(session_timestamps.last_access().elapsed().unwrap() < Duration::from_secs(30));
+        assert!(session_timestamps.created_at().elapsed().unwrap() >= Duration::from_secs(30));
+    }
+
+    // idle session is replaced on access
+    {
+        age_session(&ctx, Duration::from_secs(61), Duration::from_secs(61)).await;
+        let mut ctx = resolve(&global_state, Some(&format!("session_id={session_id}"))).await;
+        assert_ne!(ctx.session_id().await, Some(session_id));
+        assert!(set_cookie(&mut ctx).is_some());
+        assert_eq!(session_count(&global_state).await, 1);
+    }
+
+    // sweeping only evicts expired sessions
+    {
+        let idle_ctx = resolve(&global_state, None).await;
+        age_session(&idle_ctx, Duration::from_secs(61), Duration::from_secs(61)).await;
+        let old_ctx = resolve(&global_state, None).await;
+        age_session(&old_ctx, Duration::from_secs(601), Duration::ZERO).await;
+        let active_ctx = resolve(&global_state, None).await;
+        assert_eq!(session_count(&global_state).await, 4);
+
+        assert_eq!(evict_expired_sessions(&global_state).await, 2);
+        assert_eq!(session_count(&global_state).await, 2);
+        assert_eq!(evict_expired_sessions(&global_state).await, 0);
+
+        let active_session_id = active_ctx.session_id().await.unwrap();
+        let ctx = resolve(
+            &global_state,
+            Some(&format!("session_id={active_session_id}")),
+        )
+        .await;
+        assert_eq!(ctx.session_id().await, Some(active_session_id));
+    }
+
+    // the sweeper doesn't keep the GlobalState alive
+    {
+        let weak_global_state = global_state.downgrade();
+        drop(ctx);
+        drop(global_state);
+        assert!(weak_global_state.upgrade().is_none());
+    }
+}
+
+async fn run_test_store() {
+    run_test_session_store(SharedSessionStore::new(MemorySessionStore::new())).await;
+
+    let directory =
+        std::env::temp_dir().join(format!("wired_handler_sessions_{}", SessionId::generate()));
+    run_test_session_store(SharedSessionStore::new(FileSessionStore::new(&directory))).await;
+    std::fs::remove_dir_all(directory).unwrap();
+}
+
+async fn run_test_session_store(session_store: SharedSessionStore) {
+    let session_config = SessionConfig {
+        idle_timeout_secs: Some(60),
+        max_lifetime_secs: None,
+        ..Default::default()
+    };
+    let global_state = GlobalState::default();
+    global_state.insert(session_config.clone()).await;
+    global_state.insert(session_store.clone()).await;
+
+    // sessions without data aren't stored
+    let ctx = resolve(&global_state, None).await;
+    let session_id = ctx.session_id().await.unwrap();
+    ctx.persist_session().await.unwrap();
+    assert!(
+        session_store
+            .get()
+            .load(session_id)
+            .await
+            .unwrap()
+            .is_none()
+    );
+
+    // changed data is stored
+    ctx.session_data()
+        .await
+        .insert("name", &"Franz".to_string())
+        .unwrap();
+    assert!(ctx.session_data().await.is_changed());
+    ctx.persist_session().await.unwrap();
+    assert!(!ctx.session_data().await.is_changed());
+    let session_record = session_store.get().load(session_id).await.unwrap().unwrap();
+    assert_eq!(
+        session_record
+            .data
+            .get::<String>("name")
+            .unwrap()
+            .as_deref(),
+        Some("Franz")
+    );
+
+    // session is rehydrated after restart
+    {
+        let global_state = GlobalState::default();
+        global_state.insert(session_config.clone()).await;
+        global_state.insert(session_store.clone()).await;
+
+        let mut ctx = resolve(&global_state, Some(&format!("session_id={session_id}"))).await;
+        assert_eq!(ctx.session_id().await, Some(session_id));
+        assert!(set_cookie(&mut ctx).is_none());
+        let session_data = ctx.session_data().await;
+        assert_eq!(
+            session_data.get::<String>("name").unwrap().as_deref(),
+            Some("Franz")
+        );
+        assert!(!session_data.is_changed());
+    }
+
+    // removing data is stored
+    {
+        let mut session_data = ctx.session_data().await;
+        assert!(session_data.remove("name").is_some());
+        assert!(session_data.remove("name").is_none());
+        assert!(session_data.is_empty());
+    }
+    ctx.persist_session().await.unwrap();
+    let session_record = session_store.get().load(session_id).await.unwrap().unwrap();
+    assert!(session_record.data.is_empty());
+
+    // expired stored sessions aren't rehydrated
+    {
+        let expired_session_id = SessionId::generate();
+        let now = SystemTime::now();
+        let expired_timestamps = SessionTimestamps::from_parts(
+            now - Duration::from_secs(120),
+            now - Duration::from_secs(61),
+        );
+        session_store
+            .get()
+            .save(
+                expired_session_id,
+                SessionRecord::new(SessionData::default(), expired_timestamps),
+            )
+            .await
+            .unwrap();
+
+        let global_state = GlobalState::default();
+        global_state.insert(session_config.clone()).await;
+        global_state.insert(session_store.clone()).await;
+
+        let ctx = resolve(
+            &global_state,
+            Some(&format!("session_id={expired_session_id}")),
+        )
+        .await;
+        assert_ne!(ctx.session_id().await, Some(expired_session_id));
+        assert!(
+            session_store
+                .get()
+                .load(expired_session_id)
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    // expired sessions are removed from the store
+    {
+        let expired_session_id = SessionId::generate();
+        let now = SystemTime::now();
+        let expired_timestamps = SessionTimestamps::from_parts(
+            now - Duration::from_secs(120),
+            now - Duration::from_secs(61),
+        );
+        session_store
+            .get()
+            .save(
+                expired_session_id,
+                SessionRecord::new(SessionData::default(), expired_timestamps),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(
+            session_store
+                .get()
+                .remove_expired(&session_config, now)
+                .await
+                .unwrap(),
+            1
+        );
+        assert!(
+            session_store
+                .get()
+                .load(expired_session_id)
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            session_store
+                .get()
+                .load(session_id)
+                .await
+                .unwrap()
+                .is_some()
+        );
+    }
+
+    session_store.get().remove(session_id).await.unwrap();
+    assert!(
+        session_store
+            .get()
+            .load(session_id)
+            .await
+            .unwrap()
+            .is_none()
+    );
+}
+
+async fn run_test_namespace() {
+    let global_state = GlobalState::default();
+    let blog = global_state.namespace("blog").await;
+
+    // session created in a namespace is stored in the root
+    let mut ctx = resolve(&blog, None).await;
+    let session_id = ctx.session_id().await.unwrap();
+    assert!(set_cookie(&mut ctx).is_some());
+    assert_eq!(session_count(&global_state).await, 1);
+    assert!(blog.get_mut::<SessionStorage>().await.is_none());
+
+    // and reused by the root and other namespaces
+    let cookie = format!("session_id={session_id}");
+    let mut ctx = resolve(&global_state, Some(&cookie)).await;
+    assert_eq!(ctx.session_id().await, Some(session_id));
+    assert!(set_cookie(&mut ctx).is_none());
+
+    let shop = global_state.namespace("shop").await;
+    let mut ctx = resolve(&shop, Some(&cookie)).await;
+    assert_eq!(ctx.session_id().await, Some(session_id));
+    assert!(set_cookie(&mut ctx).is_none());
+    assert_eq!(session_count(&global_state).await, 1);
+
+    // expired sessions are evicted from the root, even when sweeping from a namespace
+    global_state
+        .insert(SessionConfig {
+            idle_timeout_secs: Some(0),
+            ..Default::default()
+        })
+        .await;
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    evict_expired_sessions(&shop).await;
+    assert_eq!(session_count(&global_state).await, 0);
+}
@@ -1,15 +1,31 @@
+use std::{fmt::Display, str::FromStr};
+
 use uuid::Uuid;
 
 /// Identifies a session
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct SessionId(Uuid);
 
+impl Display for SessionId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("{}", self.uuid()))
+    }
+}
+
 impl From<Uuid> for SessionId {
     fn from(value: Uuid) -> Self {
         Self(value)
     }
 }
 
+impl FromStr for SessionId {
+    type Err = uuid::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self(Uuid::parse_str(s)?))
+    }
+}
+
 impl SessionId {
     pub fn generate() -> Self {
         Self(Uuid::new_v4())
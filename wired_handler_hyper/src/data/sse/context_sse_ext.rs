@@ -0,0 +1,108 @@
+use std::{convert::Infallible, pin::pin, time::Duration};
+
+use futures::{StreamExt, future::Either};
+use http::header::{CACHE_CONTROL, CONTENT_TYPE};
+use hyper::body::Bytes;
+use tokio::{sync::mpsc, time::MissedTickBehavior};
+
+use super::sse_sender::SseSender;
+use crate::{
+    data::{
+        request::request_header,
+        response::Response,
+        response_body::{ResponseBody, ResponseBodyExt},
+    },
+    prelude::*,
+    state::{context::HttpRequestContext, request_state::RequestState},
+};
+
+const LAST_EVENT_ID: &str = "last-event-id";
+
+const KEEP_ALIVE_COMMENT: &[u8] = b": keep-alive\n\n";
+
+/// The config of a Server-Sent Events stream
+#[derive(Debug, Clone)]
+pub struct SseConfig {
+    /// Time between two comments keeping the connection open, `None` to disable
+    pub keep_alive_interval: Option<Duration>,
+    /// Time the client waits before reconnecting, sent at the start of the stream. Uses the client's default if `None`
+    pub retry: Option<Duration>,
+    /// Number of events buffered before sending waits for the client
+    pub capacity: usize,
+}
+
+impl Default for SseConfig {
+    fn default() -> Self {
+        Self {
+            keep_alive_interval: Some(Duration::from_secs(15)),
+            retry: None,
+            capacity: 64,
+        }
+    }
+}
+
+/// Creates the body of the stream, sending the events of `receiver` and keep-alive comments
+fn create_sse_body(receiver: mpsc::Receiver<Bytes>, sse_config: &SseConfig) -> ResponseBody {
+    // the retry hint is sent without any data, so clients don't dispatch an event
+    let retry = sse_config
+        .retry
+        .map(|retry| Bytes::from(format!("retry: {}\n\n", retry.as_millis())));
+
+    let keep_alive = sse_config.keep_alive_interval.map(|keep_alive_interval| {
+        let mut keep_alive = tokio::time::interval_at(
+            tokio::time::Instant::now() + keep_alive_interval,
+            keep_alive_interval,
+        );
+        keep_alive.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        keep_alive
+    });
+
+    let events = futures::stream::unfold(
+        (receiver, keep_alive),
+        |(mut receiver, mut keep_alive)| async move {
+            let bytes = match &mut keep_alive {
+                Some(keep_alive) => {
+                    match futures::future::select(pin!(receiver.recv()), pin!(keep_alive.tick()))
+                        .await
+                    {
+                        Either::Left((bytes, _)) => bytes?,
+                        Either::Right(_) => Bytes::from_static(KEEP_ALIVE_COMMENT),
+                    }
+                }
+                None => receiver.recv().await?,
+            };
+
+            Some((Ok::<_, Infallible>(bytes), (receiver, keep_alive)))
+        },
+    );
+
+    ResponseBody::from_stream(futures::stream::iter(retry.map(Ok::<_, Infallible>)).chain(events))
+}
+
+/// For responding with a Server-Sent Events stream
+pub trait ContextSseExt {
+    /// Returns the id of the last event the client has received before reconnecting, to resume the stream
+    fn last_event_id(&self) -> Option<&str>;
+
+    /// Inserts a `text/event-stream` `Response` and returns the `SseSender` sending its events.
+    /// The stream is open until all senders are dropped or the client disconnects
+    fn create_sse(&mut self, sse_config: SseConfig) -> SseSender;
+}
+
+impl ContextSseExt for HttpRequestContext {
+    fn last_event_id(&self) -> Option<&str> {
+        request_header(self, LAST_EVENT_ID)
+    }
+
+    fn create_sse(&mut self, sse_config: SseConfig) -> SseSender {
+        let (sender, receiver) = mpsc::channel(sse_config.capacity.max(1));
+        let response = Response::builder()
+            .header(CONTENT_TYPE, "text/event-stream")
+            .header(CACHE_CONTROL, "no-cache")
+            .body(create_sse_body(receiver, &sse_config))
+            .expect("Response builder failed with valid headers");
+        RequestState::get_mut_from_ctx(self).insert(response);
+
+        SseSender::new(sender)
+    }
+}
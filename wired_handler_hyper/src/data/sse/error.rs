@@ -0,0 +1,9 @@
+use thiserror::Error;
+
+/// Error returned when an event can't be sent
+#[derive(Debug, Error)]
+pub enum SseError {
+    /// The client has disconnected
+    #[error("the client has disconnected")]
+    Closed,
+}
@@ -0,0 +1,11 @@
+pub use context_sse_ext::*;
+pub use error::*;
+pub use sse_event::*;
+pub use sse_sender::*;
+
+mod context_sse_ext;
+mod error;
+mod sse_event;
+mod sse_sender;
+#[cfg(test)]
+mod test;
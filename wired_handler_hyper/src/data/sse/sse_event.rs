@@ -0,0 +1,79 @@
+use std::time::Duration;
+
+use hyper::body::Bytes;
+use serde::Serialize;
+
+/// Removes line breaks, which would end the field
+fn single_line(value: String) -> String {
+    if value.contains(['\r', '\n']) {
+        value.replace(['\r', '\n'], "")
+    } else {
+        value
+    }
+}
+
+/// An event of a Server-Sent Events stream
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SseEvent {
+    id: Option<String>,
+    event: Option<String>,
+    data: String,
+    retry: Option<Duration>,
+}
+
+impl SseEvent {
+    /// Creates an unnamed event, `data` can span multiple lines
+    pub fn new(data: impl Into<String>) -> Self {
+        Self {
+            data: data.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Creates an unnamed event with `data` serialized as JSON
+    pub fn json<T: Serialize>(data: &T) -> Result<Self, serde_json::Error> {
+        Ok(Self::new(serde_json::to_string(data)?))
+    }
+
+    /// Sets the id, which the client sends as `Last-Event-ID` when reconnecting
+    pub fn id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(single_line(id.into()));
+        self
+    }
+
+    /// Sets the name, clients can listen to named events using `addEventListener`
+    pub fn event(mut self, event: impl Into<String>) -> Self {
+        self.event = Some(single_line(event.into()));
+        self
+    }
+
+    /// Sets the time the client waits before reconnecting
+    pub fn retry(mut self, retry: Duration) -> Self {
+        self.retry = Some(retry);
+        self
+    }
+
+    /// Encodes the event in the `text/event-stream` format
+    pub fn to_bytes(&self) -> Bytes {
+        let mut encoded = String::new();
+        if let Some(id) = &self.id {
+            encoded += &format!("id: {id}\n");
+        }
+        if let Some(event) = &self.event {
+            encoded += &format!("event: {event}\n");
+        }
+        if let Some(retry) = self.retry {
+            encoded += &format!("retry: {}\n", retry.as_millis());
+        }
+        for line in self
+            .data
+            .split("\r\n")
+            .flat_map(|line| line.split(['\r', '\n']))
+        {
+            encoded += &format!("data: {line}\n");
+        }
+        encoded.push('\n');
+
+        encoded.into()
+    }
+}
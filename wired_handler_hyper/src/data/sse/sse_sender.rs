@@ -0,0 +1,49 @@
+use hyper::body::Bytes;
+use tokio::sync::mpsc;
+
+use super::{error::SseError, sse_event::SseEvent};
+
+/// Sends events to a Server-Sent Events stream, can be cloned and kept in the `SessionState` or `GlobalState`.
+///
+/// The stream ends once all senders are dropped. Sending fails with `SseError::Closed` once the client has disconnected,
+/// the sender should be removed then
+#[derive(Debug, Clone)]
+pub struct SseSender(mpsc::Sender<Bytes>);
+
+impl SseSender {
+    pub(super) fn new(sender: mpsc::Sender<Bytes>) -> Self {
+        Self(sender)
+    }
+
+    /// Sends `event`, waits if the client doesn't keep up
+    pub async fn send(&self, event: SseEvent) -> Result<(), SseError> {
+        self.0
+            .send(event.to_bytes())
+            .await
+            .map_err(|_| SseError::Closed)
+    }
+
+    /// Sends a comment, which is ignored by the client
+    pub async fn send_comment(&self, comment: &str) -> Result<(), SseError> {
+        let encoded: String = comment
+            .split("\r\n")
+            .flat_map(|line| line.split(['\r', '\n']))
+            .map(|line| format!(": {line}\n"))
+            .collect();
+
+        self.0
+            .send(format!("{encoded}\n").into())
+            .await
+            .map_err(|_| SseError::Closed)
+    }
+
+    /// Whether the client has disconnected
+    pub fn is_closed(&self) -> bool {
+        self.0.is_closed()
+    }
+
+    /// Completes once the client has disconnected
+    pub async fn closed(&self) {
+        self.0.closed().await
+    }
+}
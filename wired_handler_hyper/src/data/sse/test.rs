@@ -0,0 +1,102 @@
+use std::time::Duration;
+
+use http::{
+    HeaderMap, HeaderValue,
+    header::{CACHE_CONTROL, CONTENT_TYPE},
+};
+use http_body_util::BodyExt;
+
+use super::{SseConfig, SseError, SseEvent};
+use crate::{
+    data::response::Response,
+    prelude::*,
+    state::{
+        context::HttpRequestContext, global_state::GlobalState, request_state::RequestState,
+        session_state::SessionState,
+    },
+};
+
+#[test]
+fn test() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    runtime.block_on(run_test());
+}
+
+fn create_context(last_event_id: Option<&'static str>) -> HttpRequestContext {
+    let mut request_state = RequestState::default();
+    if let Some(last_event_id) = last_event_id {
+        let mut headers = HeaderMap::new();
+        headers.insert("Last-Event-ID", HeaderValue::from_static(last_event_id));
+        request_state.insert(headers);
+    }
+
+    HttpRequestContext::from_states(
+        GlobalState::default(),
+        SessionState::default(),
+        request_state,
+    )
+}
+
+async fn run_test() {
+    // encoding
+    assert_eq!(
+        SseEvent::new("first\nsecond")
+            .id("1\n")
+            .event("update")
+            .retry(Duration::from_secs(3))
+            .to_bytes(),
+        "id: 1\nevent: update\nretry: 3000\ndata: first\ndata: second\n\n"
+    );
+    assert_eq!(
+        SseEvent::json(&[1, 2]).unwrap().to_bytes(),
+        "data: [1,2]\n\n"
+    );
+
+    // resumption
+    assert_eq!(create_context(None).last_event_id(), None);
+    assert_eq!(create_context(Some("41")).last_event_id(), Some("41"));
+
+    // events, comments and keep-alives are streamed until all senders are dropped
+    {
+        let mut ctx = create_context(None);
+        let sender = ctx.create_sse(SseConfig {
+            keep_alive_interval: Some(Duration::from_millis(100)),
+            retry: Some(Duration::from_secs(5)),
+            ..Default::default()
+        });
+        let response = RequestState::get_mut_from_ctx(&mut ctx)
+            .remove_get::<Response>()
+            .unwrap();
+        assert_eq!(response.headers()[CONTENT_TYPE], "text/event-stream");
+        assert_eq!(response.headers()[CACHE_CONTROL], "no-cache");
+
+        let body = tokio::spawn(response.into_body().collect());
+        sender.send(SseEvent::new("hello").id("42")).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        sender.clone().send_comment("ping").await.unwrap();
+        drop(sender);
+
+        let body = body.await.unwrap().unwrap().to_bytes();
+        assert_eq!(
+            body,
+            "retry: 5000\n\nid: 42\ndata: hello\n\n: keep-alive\n\n: ping\n\n"
+        );
+    }
+
+    // a disconnected client closes the senders
+    {
+        let mut ctx = create_context(None);
+        let sender = ctx.create_sse(SseConfig::default());
+        assert!(!sender.is_closed());
+
+        RequestState::get_mut_from_ctx(&mut ctx).remove::<Response>();
+        tokio::time::timeout(Duration::from_secs(1), sender.closed())
+            .await
+            .unwrap();
+        assert!(sender.is_closed());
+        assert!(matches!(
+            sender.send(SseEvent::new("lost")).await,
+            Err(SseError::Closed)
+        ));
+    }
+}
@@ -4,13 +4,19 @@ use async_fn_traits::AsyncFn1;
 use futures::StreamExt;
 use http::StatusCode;
 use http_body_util::BodyExt;
+use hyper_tungstenite::tungstenite::{
+    Message,
+    protocol::{CloseFrame, WebSocketConfig, frame::coding::CloseCode},
+};
 
 use super::{
     connection_id::ConnectionId,
     connection_storage::ConnectionStorage,
     http_error::{HttpError, HttpErrorFromResponseExt},
+    request_body::ContextCreateBodyExt,
     response::Response,
     response_body::ResponseBody,
+    send_message::SendMessageExt,
 };
 use crate::{
     prelude::*,
@@ -70,9 +76,16 @@ impl ContextWebsocketExt for HttpRequestContext {
             return Err(HttpError::websocket_upgrade_required());
         }
 
+        // messages over the limit are rejected while reading them, not only once they are buffered
+        let max_message_size = self.max_body_size().await;
+        let websocket_config = WebSocketConfig::default()
+            .max_message_size(max_message_size)
+            .max_frame_size(max_message_size);
+
         // upgrade to websocket
-        let (response, websocket) = hyper_tungstenite::upgrade(self.request_mut(), None)
-            .map_err(|err| HttpError::new(StatusCode::BAD_REQUEST, err.to_string()))?;
+        let (response, websocket) =
+            hyper_tungstenite::upgrade(self.request_mut(), Some(websocket_config))
+                .map_err(|err| HttpError::new(StatusCode::BAD_REQUEST, err.to_string()))?;
 
         // clone (semi-)global states
         let session_state = SessionState::get_from_ctx(self).clone();
@@ -113,11 +126,19 @@ impl ContextWebsocketExt for HttpRequestContext {
                         .insert(connection_id, connection_state.clone());
                 }
 
+                // insert connection state into the root global state, for closing it on shutdown
+                global_state
+                    .root()
+                    .get_mut_or_insert_default::<ConnectionStorage>()
+                    .await
+                    .get_mut()
+                    .insert(connection_id, connection_state.clone());
+
                 (connection_state, connection_id, rx)
             };
 
             let mut ctx = WebsocketRequestContext::from_states(
-                global_state,
+                global_state.clone(),
                 session_state.clone(),
                 connection_state.clone(),
                 RequestState::default(),
@@ -131,6 +152,21 @@ impl ContextWebsocketExt for HttpRequestContext {
                 // output errors
                 let message = match message {
                     Ok(message) => message,
+                    // the rest of the message can't be skipped, so the connection is closed
+                    Err(TungsteniteError::Capacity(err)) => {
+                        tracing::debug!("websocket message too large, closing: {err}");
+                        let close_frame = CloseFrame {
+                            code: CloseCode::Size,
+                            reason: "message too large".into(),
+                        };
+                        if let Err(err) = connection_state
+                            .send_message(Message::Close(Some(close_frame)))
+                            .await
+                        {
+                            tracing::debug!("failed to close websocket connection: {err}");
+                        }
+                        break;
+                    }
                     // abort on fatal errors
                     Err(err)
                         if matches!(
@@ -175,6 +211,14 @@ impl ContextWebsocketExt for HttpRequestContext {
                 .await
                 .get_mut()
                 .remove(&connection_id);
+
+            // remove connection state from the root global state
+            global_state
+                .root()
+                .get_mut_or_insert_default::<ConnectionStorage>()
+                .await
+                .get_mut()
+                .remove(&connection_id);
         });
 
         // convert response to the correct type
@@ -189,3 +233,88 @@ impl ContextWebsocketExt for HttpRequestContext {
         self.next(converted_response)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use hyper_tungstenite::tungstenite::{self, Message, protocol::frame::coding::CloseCode};
+    use tokio::sync::oneshot;
+    use wired_handler::Handler;
+
+    use crate::{
+        data::config::BodyConfig,
+        prelude::*,
+        state::{
+            context::{HttpRequestContext, SessionlessRequestContext, WebsocketRequestContext},
+            global_state::GlobalState,
+            session_state::SessionState,
+        },
+        test_util::spawn_server,
+    };
+
+    async fn echo(ctx: &mut WebsocketRequestContext) {
+        let message = ctx.message().clone();
+        ctx.send_message(message).await.unwrap();
+    }
+
+    async fn handle(ctx: SessionlessRequestContext) -> HttpRequestContext {
+        let (global_state, request_state) = ctx.into_states();
+        let mut ctx =
+            HttpRequestContext::from_states(global_state, SessionState::default(), request_state);
+
+        let _ = ctx.next_websocket(echo).await.unwrap();
+
+        ctx
+    }
+
+    #[test]
+    fn test() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(run_test());
+    }
+
+    async fn run_test() {
+        let global_state = GlobalState::default();
+        global_state.insert(BodyConfig { max_size: Some(16) }).await;
+
+        let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+        let (addr, server) = spawn_server(
+            Handler::new(global_state, handle),
+            Default::default(),
+            async {
+                shutdown_rx.await.ok();
+            },
+        )
+        .await;
+
+        let close_frame = tokio::task::spawn_blocking(move || {
+            let stream = std::net::TcpStream::connect(addr).unwrap();
+            stream
+                .set_read_timeout(Some(Duration::from_secs(5)))
+                .unwrap();
+            let (mut websocket, _) = tungstenite::client(format!("ws://{addr}/"), stream).unwrap();
+
+            // messages within the limit are received
+            websocket.send(Message::text("0123456789abcdef")).unwrap();
+            assert_eq!(websocket.read().unwrap(), Message::text("0123456789abcdef"));
+
+            // larger messages close the connection instead of being received
+            websocket.send(Message::binary(vec![0; 1024])).unwrap();
+            match websocket.read().unwrap() {
+                Message::Close(close_frame) => close_frame,
+                message => panic!("expected close frame, got {message:?}"),
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(close_frame.unwrap().code, CloseCode::Size);
+
+        shutdown_tx.send(()).unwrap();
+        tokio::time::timeout(Duration::from_secs(5), server)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+    }
+}
@@ -1,4 +1,12 @@
-use std::future::Future;
+use std::{
+    future::Future,
+    net::SocketAddr,
+    pin::{Pin, pin},
+    sync::Arc,
+    time::Duration,
+};
+
+use futures::future::Either;
 
 use hyper::{
     StatusCode,
@@ -6,12 +14,24 @@ use hyper::{
 };
 use hyper_util::rt::TokioTimer;
 use thiserror::Error;
-use tokio::net::{TcpListener, TcpStream};
-use tracing::{info, trace};
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    sync::{OwnedSemaphorePermit, Semaphore, watch},
+    task::JoinSet,
+};
+use tracing::{debug, info, trace, warn};
 use wired_handler::Handler;
 
 use crate::{
-    data::{config::BindConfig, request::Request, response::Response, response_body::ResponseBody},
+    data::{
+        config::{BindConfig, ConnectionLimitMode},
+        connection_info::ConnectionInfo,
+        cookies::apply_cookies,
+        request::Request,
+        response::Response,
+        response_body::ResponseBody,
+    },
+    listener::Listener,
     prelude::*,
     state::{
         context::{
@@ -29,36 +49,320 @@ fn internal_server_error_response() -> Response {
         .expect("ResponseBuilder failed with a valid StatusCode")
 }
 
-/// Handles a connection, can be a single HTTP request or multiple if keep-alive is used
-async fn handle_connection(
-    stream: TcpStream,
-    http_service_fn: impl Service<Request, Error = hyper::http::Error, Response = Response>,
-) -> Result<(), hyper::Error> {
-    let io = hyper_util::rt::TokioIo::new(stream);
+/// Error of a single connection, differs between HTTP versions
+type ConnectionError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Drives `conn` to completion. Shuts it down gracefully once `close` completes, finishing the current requests
+async fn serve_until_closed<C: Future<Output = Result<(), E>>, E>(
+    conn: C,
+    graceful_shutdown: impl FnOnce(Pin<&mut C>),
+    close: impl Future<Output = ()>,
+) -> Result<(), E> {
+    let mut conn = pin!(conn);
+    if let Either::Left((result, _)) = futures::future::select(conn.as_mut(), pin!(close)).await {
+        return result;
+    }
 
+    graceful_shutdown(conn.as_mut());
+    conn.await
+}
+
+/// Builder of HTTP connections, differs between HTTP versions
+#[cfg(not(feature = "http2"))]
+type HttpBuilder = hyper::server::conn::http1::Builder;
+
+/// Builder of HTTP connections, differs between HTTP versions
+#[cfg(feature = "http2")]
+type HttpBuilder = hyper_util::server::conn::auto::Builder<hyper_util::rt::TokioExecutor>;
+
+/// Creates the builder of all connections, applying the timeouts and limits of `bind_config`
+#[cfg(not(feature = "http2"))]
+fn create_http_builder(bind_config: &BindConfig) -> HttpBuilder {
     let mut http_builder = hyper::server::conn::http1::Builder::new();
-    let conn = http_builder
+    http_builder
+        .timer(TokioTimer::new())
+        .header_read_timeout(bind_config.header_read_timeout())
+        .keep_alive(bind_config.keep_alive);
+    if let Some(max_headers) = bind_config.max_headers {
+        http_builder.max_headers(max_headers);
+    }
+
+    http_builder
+}
+
+/// Creates the builder of all connections, applying the timeouts and limits of `bind_config`
+#[cfg(feature = "http2")]
+fn create_http_builder(bind_config: &BindConfig) -> HttpBuilder {
+    let mut http_builder =
+        hyper_util::server::conn::auto::Builder::new(hyper_util::rt::TokioExecutor::new());
+    http_builder
+        .http1()
+        .timer(TokioTimer::new())
+        .header_read_timeout(bind_config.header_read_timeout())
+        .keep_alive(bind_config.keep_alive);
+    if let Some(max_headers) = bind_config.max_headers {
+        http_builder.http1().max_headers(max_headers);
+    }
+    http_builder
+        .http2()
         .timer(TokioTimer::new())
-        .serve_connection(io, http_service_fn);
+        .keep_alive_interval(bind_config.http2_keep_alive_interval());
+    // `None` would remove hyper's default limit
+    if let Some(max_concurrent_streams) = bind_config.http2_max_concurrent_streams {
+        http_builder
+            .http2()
+            .max_concurrent_streams(max_concurrent_streams);
+    }
+
+    http_builder
+}
+
+/// Handles a connection, can be a single HTTP request or multiple if keep-alive is used
+#[cfg(not(feature = "http2"))]
+async fn handle_connection<I, S>(
+    stream: I,
+    http_builder: Arc<HttpBuilder>,
+    http_service_fn: S,
+    close: impl Future<Output = ()>,
+) -> Result<(), ConnectionError>
+where
+    I: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    S: Service<Request, Error = hyper::http::Error, Response = Response>,
+    S::Future: Send + 'static,
+{
+    let io = hyper_util::rt::TokioIo::new(stream);
+
+    let conn = http_builder.serve_connection(io, http_service_fn);
 
     #[cfg(feature = "websocket")]
-    conn.with_upgrades().await?;
+    let conn = conn.with_upgrades();
 
-    #[cfg(not(feature = "websocket"))]
-    conn.await?;
+    serve_until_closed(conn, |conn| conn.graceful_shutdown(), close).await?;
 
     Ok(())
 }
 
-async fn handle_connection_and_output_errors(
-    stream: TcpStream,
-    http_service_fn: impl Service<Request, Error = hyper::http::Error, Response = Response>,
-) {
-    if let Err(err) = handle_connection(stream, http_service_fn).await {
+/// Handles a connection, can be a single HTTP request or multiple if keep-alive is used.
+/// Serves HTTP/1.1 and HTTP/2, which is detected by its connection preface (h2c with prior knowledge)
+#[cfg(feature = "http2")]
+async fn handle_connection<I, S>(
+    stream: I,
+    http_builder: Arc<HttpBuilder>,
+    http_service_fn: S,
+    close: impl Future<Output = ()>,
+) -> Result<(), ConnectionError>
+where
+    I: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    S: Service<Request, Error = hyper::http::Error, Response = Response>,
+    S::Future: Send + 'static,
+{
+    let io = hyper_util::rt::TokioIo::new(stream);
+
+    // websockets are only supported by HTTP/1.1
+    #[cfg(feature = "websocket")]
+    let conn = http_builder.serve_connection_with_upgrades(io, http_service_fn);
+
+    #[cfg(not(feature = "websocket"))]
+    let conn = http_builder.serve_connection(io, http_service_fn);
+
+    serve_until_closed(conn, |conn| conn.graceful_shutdown(), close).await
+}
+
+async fn handle_connection_and_output_errors<I, S>(
+    stream: I,
+    http_builder: Arc<HttpBuilder>,
+    http_service_fn: S,
+    close: impl Future<Output = ()>,
+) where
+    I: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    S: Service<Request, Error = hyper::http::Error, Response = Response>,
+    S::Future: Send + 'static,
+{
+    if let Err(err) = handle_connection(stream, http_builder, http_service_fn, close).await {
         tracing::debug!("connection error: {err}");
     }
 }
 
+/// Performs the TLS handshake within `handshake_timeout`, then handles the connection like `handle_connection_and_output_errors`
+#[cfg(feature = "tls")]
+async fn handle_tls_connection_and_output_errors<S>(
+    tls_acceptor: tokio_rustls::TlsAcceptor,
+    handshake_timeout: Option<std::time::Duration>,
+    stream: crate::listener::Stream,
+    http_builder: Arc<HttpBuilder>,
+    http_service_fn: S,
+    close: impl Future<Output = ()>,
+) where
+    S: Service<Request, Error = hyper::http::Error, Response = Response>,
+    S::Future: Send + 'static,
+{
+    let handshake = tls_acceptor.accept(stream);
+    let handshake_result = match handshake_timeout {
+        Some(handshake_timeout) => match tokio::time::timeout(handshake_timeout, handshake).await {
+            Ok(handshake_result) => handshake_result,
+            Err(_) => {
+                tracing::debug!("tls handshake timed out");
+                return;
+            }
+        },
+        None => handshake.await,
+    };
+    let tls_stream = match handshake_result {
+        Ok(tls_stream) => tls_stream,
+        Err(err) => {
+            tracing::debug!("tls handshake failed: {err}");
+            return;
+        }
+    };
+
+    handle_connection_and_output_errors(tls_stream, http_builder, http_service_fn, close).await;
+}
+
+/// Requests of a single connection, for closing it once it's idle or has served its maximum number of requests
+#[derive(Debug, Clone, Copy, Default)]
+struct RequestCounts {
+    /// Requests received so far
+    started: usize,
+    /// Requests whose response hasn't been produced yet
+    in_progress: usize,
+}
+
+/// Counts a request as in progress until dropped
+struct RequestGuard(Arc<watch::Sender<RequestCounts>>);
+
+impl RequestGuard {
+    fn start(request_counts: &Arc<watch::Sender<RequestCounts>>) -> Self {
+        request_counts.send_modify(|request_counts| {
+            request_counts.started += 1;
+            request_counts.in_progress += 1;
+        });
+        Self(request_counts.clone())
+    }
+}
+
+impl Drop for RequestGuard {
+    fn drop(&mut self) {
+        self.0
+            .send_modify(|request_counts| request_counts.in_progress -= 1);
+    }
+}
+
+/// Completes once a connection is to be closed: when the server shuts down,
+/// when no request has been in progress for `keep_alive_timeout` or once `max_requests` have been received
+async fn wait_for_close(
+    mut shutdown_rx: watch::Receiver<()>,
+    mut request_counts_rx: watch::Receiver<RequestCounts>,
+    keep_alive_timeout: Option<Duration>,
+    max_requests: Option<usize>,
+) {
+    let limits_reached = async {
+        loop {
+            let request_counts = *request_counts_rx.borrow_and_update();
+            if max_requests.is_some_and(|max_requests| request_counts.started >= max_requests) {
+                return;
+            }
+
+            let changed = request_counts_rx.changed();
+            let changed_result = match keep_alive_timeout {
+                Some(keep_alive_timeout) if request_counts.in_progress == 0 => {
+                    match tokio::time::timeout(keep_alive_timeout, changed).await {
+                        Ok(changed_result) => changed_result,
+                        Err(_) => {
+                            trace!("connection idle for {keep_alive_timeout:?}, closing it");
+                            return;
+                        }
+                    }
+                }
+                _ => changed.await,
+            };
+            // the connection itself is gone
+            if changed_result.is_err() {
+                return std::future::pending().await;
+            }
+        }
+    };
+
+    // an error means the sender is gone, which is a shutdown as well
+    futures::future::select(pin!(shutdown_rx.changed()), pin!(limits_reached)).await;
+}
+
+/// Runs `connection`, then frees its slot of the connection limit
+async fn hold_connection_permit(
+    connection_permit: Option<OwnedSemaphorePermit>,
+    connection: impl Future<Output = ()>,
+) {
+    connection.await;
+    drop(connection_permit);
+}
+
+/// Sends a close frame to all open websocket connections and waits until they are closed.
+/// Connections opened meanwhile are closed as well
+#[cfg(feature = "websocket")]
+async fn close_websockets(global_state: &GlobalState) {
+    use std::{collections::HashSet, time::Duration};
+
+    use crate::data::connection_storage::{ConnectionStorage, close_connections};
+
+    const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+    let mut closed_connection_ids = HashSet::new();
+    loop {
+        // connections remove themselves from the `ConnectionStorage` when closed
+        let connection_states: Vec<_> = match global_state.get::<ConnectionStorage>().await {
+            Some(connection_storage) if !connection_storage.get().is_empty() => connection_storage
+                .get()
+                .iter()
+                .filter(|(connection_id, _)| !closed_connection_ids.contains(*connection_id))
+                .map(|(connection_id, connection_state)| (*connection_id, connection_state.clone()))
+                .collect(),
+            _ => return,
+        };
+        closed_connection_ids.extend(
+            connection_states
+                .iter()
+                .map(|(connection_id, _)| *connection_id),
+        );
+        close_connections(
+            connection_states
+                .into_iter()
+                .map(|(_, connection_state)| connection_state),
+        )
+        .await;
+
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+#[cfg(not(feature = "websocket"))]
+async fn close_websockets(_global_state: &GlobalState) {}
+
+/// A connection accepted by the server, used for creating the `ConnectionInfo` of its requests
+#[derive(Debug, Clone)]
+struct AcceptedConnection {
+    /// Peer and local address, `None` for Unix sockets
+    addrs: Option<(SocketAddr, SocketAddr)>,
+    is_tls: bool,
+    /// Config of the listener which has accepted the connection
+    bind_config: Arc<BindConfig>,
+}
+
+impl AcceptedConnection {
+    fn connection_info(&self, request: &Request) -> ConnectionInfo {
+        let connection_info = match self.addrs {
+            Some((peer_addr, local_addr)) => {
+                ConnectionInfo::new(peer_addr, local_addr, self.is_tls, request.version())
+            }
+            None => ConnectionInfo::new_unix(self.is_tls, request.version()),
+        };
+
+        connection_info.with_forwarded_client_ip(
+            request.headers(),
+            &self.bind_config.trusted_proxies,
+            self.bind_config.trust_unix_socket_peers,
+        )
+    }
+}
+
 /// Handles a single request, turning a `Request` in a `Response`
 async fn handle_request(
     request: Request,
@@ -68,9 +372,12 @@ async fn handle_request(
         GlobalState,
         impl Future<Output = HttpRequestContext> + 'static + Send,
     >,
+    accepted_connection: AcceptedConnection,
 ) -> Result<Response, hyper::http::Error> {
     let request_state = {
         let mut request_state = RequestState::default();
+        request_state.insert(accepted_connection.connection_info(&request));
+        request_state.insert(accepted_connection.bind_config);
         request_state.insert(request);
         request_state
     };
@@ -79,12 +386,20 @@ async fn handle_request(
         .handle(SessionlessRequestContextBuilder { request_state })
         .await;
 
-    let response: Option<Response> = RequestState::get_mut_from_ctx(&mut result_ctx).remove_get();
+    if let Err(err) = result_ctx.persist_session().await {
+        tracing::warn!("failed to persist session: {err}");
+    }
+
+    let request_state = RequestState::get_mut_from_ctx(&mut result_ctx);
+    let response: Option<Response> = request_state.remove_get();
 
-    Ok(response.unwrap_or_else(|| {
+    let mut response = response.unwrap_or_else(|| {
         tracing::error!("handler produced no response");
         internal_server_error_response()
-    }))
+    });
+    apply_cookies(request_state, &mut response);
+
+    Ok(response)
 }
 
 #[derive(Debug, Error)]
@@ -92,6 +407,8 @@ async fn handle_request(
 pub enum RunHttpServerError {
     Hyper(#[from] hyper::Error),
     Io(#[from] std::io::Error),
+    #[cfg(feature = "tls")]
+    Tls(#[from] crate::TlsError),
 }
 
 pub trait RunHttpServerExt {
@@ -100,29 +417,852 @@ pub trait RunHttpServerExt {
         self,
         bind_config: BindConfig,
     ) -> impl Future<Output = Result<(), RunHttpServerError>>;
+
+    /// Runs the HTTP server with the given `bind_config` until `shutdown` completes.
+    /// Then stops accepting connections, lets in-flight requests finish and closes open websockets.
+    /// Returns once all connections are closed or the `shutdown_timeout_secs` of the `bind_config` have elapsed,
+    /// aborting the connections still open
+    ///
+    /// `shutdown` can be a signal, e.g. `async { tokio::signal::ctrl_c().await.ok(); }`
+    fn run_http_server_with_shutdown(
+        self,
+        bind_config: BindConfig,
+        shutdown: impl Future<Output = ()>,
+    ) -> impl Future<Output = Result<(), RunHttpServerError>>;
 }
 
 impl<F: Future<Output = HttpRequestContext> + 'static + Send> RunHttpServerExt
     for Handler<SessionlessRequestContext, HttpRequestContext, GlobalState, F>
 {
     async fn run_http_server(self, bind_config: BindConfig) -> Result<(), RunHttpServerError> {
-        let bind_addr = bind_config.addr.as_str();
+        self.run_http_server_with_shutdown(bind_config, std::future::pending())
+            .await
+    }
+
+    async fn run_http_server_with_shutdown(
+        self,
+        bind_config: BindConfig,
+        shutdown: impl Future<Output = ()>,
+    ) -> Result<(), RunHttpServerError> {
+        info!("starting http server on {}", bind_config.addr);
+        let listener = Listener::bind(&bind_config).await?;
 
-        info!("starting http server on {bind_addr}");
-        let tcp_listener = TcpListener::bind(bind_addr).await?;
-        info!("listening on http://{bind_addr}");
+        run_http_server_on_listener(self, listener, bind_config, shutdown).await
+    }
+}
 
-        self.state().insert(bind_config).await;
+/// Time to wait before accepting again after an error which isn't specific to one connection
+const ACCEPT_ERROR_DELAY: Duration = Duration::from_secs(1);
 
-        let http_service_fn =
-            service_fn(move |request: Request| handle_request(request, self.clone()));
-        loop {
-            let (stream, addr) = tcp_listener.accept().await?;
-            trace!("new connection on {:?}", addr);
-            tokio::spawn(handle_connection_and_output_errors(
-                stream,
-                http_service_fn.clone(),
+/// Whether an accept error only affects the connection being accepted
+fn is_connection_error(err: &std::io::Error) -> bool {
+    matches!(
+        err.kind(),
+        std::io::ErrorKind::ConnectionRefused
+            | std::io::ErrorKind::ConnectionAborted
+            | std::io::ErrorKind::ConnectionReset
+    )
+}
+
+/// Runs the HTTP server of `handler` on the already bound `listener` like `run_http_server_with_shutdown`
+pub(crate) async fn run_http_server_on_listener<
+    F: Future<Output = HttpRequestContext> + 'static + Send,
+>(
+    handler: Handler<SessionlessRequestContext, HttpRequestContext, GlobalState, F>,
+    listener: Listener,
+    bind_config: BindConfig,
+    shutdown: impl Future<Output = ()>,
+) -> Result<(), RunHttpServerError> {
+    let bind_addr = bind_config.addr.clone();
+
+    #[cfg(feature = "tls")]
+    let (tls_acceptor, _cert_reloader) = match &bind_config.tls {
+        Some(tls_config) => {
+            let (tls_acceptor, cert_reloader) = crate::tls::create_tls_acceptor(tls_config).await?;
+            (Some(tls_acceptor), cert_reloader)
+        }
+        None => (None, None),
+    };
+
+    #[cfg(feature = "tls")]
+    let is_tls = tls_acceptor.is_some();
+    #[cfg(not(feature = "tls"))]
+    let is_tls = false;
+    let scheme = if is_tls { "https" } else { "http" };
+    info!("listening on {scheme}://{bind_addr}");
+
+    let shutdown_timeout = bind_config.shutdown_timeout();
+    #[cfg(feature = "tls")]
+    let handshake_timeout = bind_config.header_read_timeout();
+    let keep_alive_timeout = bind_config.keep_alive_timeout();
+    let max_requests_per_connection = bind_config.max_requests_per_connection;
+    let http_builder = Arc::new(create_http_builder(&bind_config));
+    let connection_limit = bind_config
+        .max_connections
+        .map(|max_connections| Arc::new(Semaphore::new(max_connections)));
+    let connection_limit_mode = bind_config.connection_limit_mode;
+    // every listener has its own, so it's stored with the requests instead of the shared `GlobalState`
+    let bind_config = Arc::new(bind_config);
+    let global_state = handler.state().clone();
+
+    // notifies the connections to shut down gracefully
+    let (shutdown_tx, shutdown_rx) = watch::channel(());
+    let mut connections = JoinSet::new();
+
+    let mut shutdown = pin!(shutdown);
+    loop {
+        // finished connections are only removed from the `JoinSet` when joined
+        while connections.try_join_next().is_some() {}
+
+        // waits for a free slot before accepting the next connection
+        let queued_permit = match (&connection_limit, connection_limit_mode) {
+            (Some(connection_limit), ConnectionLimitMode::Queue) => {
+                match futures::future::select(
+                    pin!(connection_limit.clone().acquire_owned()),
+                    shutdown.as_mut(),
+                )
+                .await
+                {
+                    Either::Left((permit, _)) => {
+                        Some(permit.expect("connection limit is never closed"))
+                    }
+                    Either::Right(((), _)) => break,
+                }
+            }
+            _ => None,
+        };
+
+        let (stream, addrs) =
+            match futures::future::select(pin!(listener.accept()), shutdown.as_mut()).await {
+                Either::Left((Ok(accepted), _)) => accepted,
+                Either::Left((Err(err), _)) => {
+                    warn!("failed to accept connection on {bind_addr}: {err}");
+                    if is_connection_error(&err) {
+                        continue;
+                    }
+
+                    // like running out of file descriptors, accepting again right away would fail as well
+                    match futures::future::select(
+                        pin!(tokio::time::sleep(ACCEPT_ERROR_DELAY)),
+                        shutdown.as_mut(),
+                    )
+                    .await
+                    {
+                        Either::Left(((), _)) => continue,
+                        Either::Right(((), _)) => break,
+                    }
+                }
+                Either::Right(((), _)) => break,
+            };
+        trace!("new connection on {:?}", addrs);
+
+        let connection_permit = match (&connection_limit, queued_permit) {
+            (_, Some(queued_permit)) => Some(queued_permit),
+            (Some(connection_limit), None) => match connection_limit.clone().try_acquire_owned() {
+                Ok(permit) => Some(permit),
+                Err(_) => {
+                    debug!(
+                        "connection limit reached, rejecting connection on {:?}",
+                        addrs
+                    );
+                    continue;
+                }
+            },
+            (None, None) => None,
+        };
+
+        let accepted_connection = AcceptedConnection {
+            addrs,
+            is_tls,
+            bind_config: bind_config.clone(),
+        };
+        let request_counts = Arc::new(watch::Sender::new(RequestCounts::default()));
+        let close = wait_for_close(
+            shutdown_rx.clone(),
+            request_counts.subscribe(),
+            keep_alive_timeout,
+            max_requests_per_connection,
+        );
+        let handler = handler.clone();
+        let http_service_fn = service_fn(move |request: Request| {
+            let request_guard = RequestGuard::start(&request_counts);
+            let response = handle_request(request, handler.clone(), accepted_connection.clone());
+            async move {
+                let response = response.await;
+                drop(request_guard);
+                response
+            }
+        });
+
+        #[cfg(feature = "tls")]
+        if let Some(tls_acceptor) = &tls_acceptor {
+            connections.spawn(hold_connection_permit(
+                connection_permit,
+                handle_tls_connection_and_output_errors(
+                    tls_acceptor.clone(),
+                    handshake_timeout,
+                    stream,
+                    http_builder.clone(),
+                    http_service_fn,
+                    close,
+                ),
             ));
+            continue;
+        }
+
+        connections.spawn(hold_connection_permit(
+            connection_permit,
+            handle_connection_and_output_errors(
+                stream,
+                http_builder.clone(),
+                http_service_fn,
+                close,
+            ),
+        ));
+    }
+
+    info!("shutting down http server on {bind_addr}");
+    listener.close().await;
+    drop(shutdown_rx);
+    shutdown_tx.send_replace(());
+
+    let drain = async {
+        // websockets don't have to wait for long-running requests
+        futures::future::join(
+            async { while connections.join_next().await.is_some() {} },
+            close_websockets(&global_state),
+        )
+        .await;
+        // for websockets upgraded from requests which have finished in the meantime
+        close_websockets(&global_state).await;
+    };
+    if tokio::time::timeout(shutdown_timeout, drain).await.is_err() {
+        warn!("connections still open after {shutdown_timeout:?}, shutting down anyway");
+        connections.abort_all();
+        while connections.join_next().await.is_some() {}
+    }
+
+    info!("http server on {bind_addr} stopped");
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::{io::Write, net::SocketAddr, sync::Arc, time::Duration};
+
+    use tokio::sync::{Notify, oneshot, watch};
+    use wired_handler::Handler;
+
+    use crate::{
+        data::{
+            config::{BindConfig, ConnectionLimitMode},
+            response::Response,
+            response_body::ResponseBody,
+        },
+        prelude::*,
+        state::{
+            context::{HttpRequestContext, SessionlessRequestContext},
+            global_state::GlobalState,
+            session_state::SessionState,
+        },
+        test_util::{send_request, spawn_server},
+    };
+
+    /// Notified when the handler has received a request
+    #[derive(Debug, Clone, Default)]
+    struct RequestStarted(Arc<Notify>);
+
+    async fn handle(ctx: SessionlessRequestContext) -> HttpRequestContext {
+        let (global_state, request_state) = ctx.into_states();
+        if let Some(request_started) = global_state.get::<RequestStarted>().await {
+            request_started.0.notify_one();
+        }
+        let mut ctx =
+            HttpRequestContext::from_states(global_state, SessionState::default(), request_state);
+
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        let _ = ctx.next(
+            Response::builder()
+                .body(ResponseBody::from_bytes("done"))
+                .unwrap(),
+        );
+
+        ctx
+    }
+
+    /// Sends a `GET /` request on the keep-alive connection `stream` and reads the response
+    fn send_keep_alive_request(stream: &mut std::net::TcpStream) -> std::io::Result<String> {
+        use std::io::Read;
+
+        stream.write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n")?;
+
+        let mut response = Vec::new();
+        let mut buf = [0; 1024];
+        while !response.ends_with(b"done") {
+            let read = stream.read(&mut buf)?;
+            if read == 0 {
+                break;
+            }
+            response.extend_from_slice(&buf[..read]);
+        }
+        Ok(String::from_utf8_lossy(&response).into_owned())
+    }
+
+    /// Sends requests to `addr` until one is answered, e.g. once a connection slot is free again
+    async fn send_request_until_answered(addr: SocketAddr) -> String {
+        tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                let response = tokio::task::spawn_blocking(move || send_request(addr))
+                    .await
+                    .unwrap()
+                    .unwrap_or_default();
+                if !response.is_empty() {
+                    return response;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap()
+    }
+
+    #[test]
+    fn test() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(run_test());
+    }
+
+    #[cfg(feature = "tls")]
+    #[test]
+    fn test_tls() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(run_test_tls());
+    }
+
+    #[cfg(feature = "http2")]
+    #[test]
+    fn test_http2() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(run_test_http2());
+    }
+
+    #[cfg(feature = "websocket")]
+    #[test]
+    fn test_websocket_shutdown() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(run_test_websocket_shutdown());
+    }
+
+    #[test]
+    fn test_limits() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(run_test_limits());
+    }
+
+    async fn run_test() {
+        let request_started = RequestStarted::default();
+        let global_state = GlobalState::default();
+        global_state.insert(request_started.clone()).await;
+
+        let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+        let (addr, server) = spawn_server(
+            Handler::new(global_state, handle),
+            BindConfig::default(),
+            async {
+                shutdown_rx.await.ok();
+            },
+        )
+        .await;
+
+        // in-flight request finishes after shutdown has been requested
+        let client = tokio::task::spawn_blocking(move || send_request(addr));
+        request_started.0.notified().await;
+        shutdown_tx.send(()).unwrap();
+
+        let response = client.await.unwrap().unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.ends_with("done"));
+
+        tokio::time::timeout(Duration::from_secs(5), server)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+
+        // no new connections are accepted
+        assert!(
+            tokio::task::spawn_blocking(move || send_request(addr))
+                .await
+                .unwrap()
+                .is_err()
+        );
+    }
+
+    #[cfg(feature = "websocket")]
+    async fn echo(ctx: &mut crate::state::context::WebsocketRequestContext) {
+        let message = ctx.message().clone();
+        ctx.send_message(message).await.unwrap();
+    }
+
+    /// Opens a websocket at `/ws`, answers other requests with a body which never ends
+    #[cfg(feature = "websocket")]
+    async fn handle_websocket_or_endless(ctx: SessionlessRequestContext) -> HttpRequestContext {
+        let (global_state, request_state) = ctx.into_states();
+        if let Some(request_started) = global_state.get::<RequestStarted>().await {
+            request_started.0.notify_one();
+        }
+        let mut ctx =
+            HttpRequestContext::from_states(global_state, SessionState::default(), request_state);
+
+        if ctx.request().uri().path() == "/ws" {
+            let _ = ctx.next_websocket(echo).await.unwrap();
+        } else {
+            let _ = ctx.next(
+                Response::builder()
+                    .body(ResponseBody::from_stream(futures::stream::pending::<
+                        Result<hyper::body::Bytes, std::io::Error>,
+                    >()))
+                    .unwrap(),
+            );
+        }
+
+        ctx
+    }
+
+    #[cfg(feature = "websocket")]
+    async fn run_test_websocket_shutdown() {
+        use hyper_tungstenite::tungstenite::{self, Message};
+
+        let request_started = RequestStarted::default();
+        let global_state = GlobalState::default();
+        global_state.insert(request_started.clone()).await;
+
+        let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+        let (addr, server) = spawn_server(
+            Handler::new(global_state, handle_websocket_or_endless),
+            BindConfig {
+                shutdown_timeout_secs: 2,
+                ..Default::default()
+            },
+            async {
+                shutdown_rx.await.ok();
+            },
+        )
+        .await;
+
+        // the echo shows that the websocket is open on the server
+        let mut websocket = tokio::task::spawn_blocking(move || {
+            let stream = std::net::TcpStream::connect(addr).unwrap();
+            stream
+                .set_read_timeout(Some(Duration::from_secs(5)))
+                .unwrap();
+            let (mut websocket, _) =
+                tungstenite::client(format!("ws://{addr}/ws"), stream).unwrap();
+            websocket.send(Message::text("ping")).unwrap();
+            assert_eq!(websocket.read().unwrap(), Message::text("ping"));
+            websocket
+        })
+        .await
+        .unwrap();
+        request_started.0.notified().await;
+
+        // a request which never finishes
+        let endless_client = tokio::task::spawn_blocking(move || send_request(addr));
+        request_started.0.notified().await;
+
+        let shutdown_started_at = std::time::Instant::now();
+        shutdown_tx.send(()).unwrap();
+
+        // websockets are closed without waiting for the request
+        let message = tokio::task::spawn_blocking(move || websocket.read().unwrap())
+            .await
+            .unwrap();
+        assert!(matches!(message, Message::Close(_)));
+        assert!(shutdown_started_at.elapsed() < Duration::from_secs(1));
+
+        // the request is aborted after the shutdown timeout
+        tokio::time::timeout(Duration::from_secs(5), server)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        let response = tokio::time::timeout(Duration::from_secs(1), endless_client)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(!response.is_ok_and(|response| response.ends_with("0\r\n\r\n")));
+    }
+
+    async fn run_test_limits() {
+        let (shutdown_tx, shutdown_rx) = watch::channel(());
+        let mut addrs = Vec::new();
+        for connection_limit_mode in [ConnectionLimitMode::Reject, ConnectionLimitMode::Queue] {
+            let mut shutdown_rx = shutdown_rx.clone();
+            let (addr, _) = spawn_server(
+                Handler::new(GlobalState::default(), handle),
+                BindConfig {
+                    header_read_timeout_secs: Some(1),
+                    max_connections: Some(1),
+                    connection_limit_mode,
+                    ..Default::default()
+                },
+                async move {
+                    shutdown_rx.changed().await.ok();
+                },
+            )
+            .await;
+            addrs.push(addr);
+        }
+        let [reject_addr, queue_addr] = addrs[..] else {
+            unreachable!()
+        };
+
+        // connections over the limit are closed in `Reject` mode.
+        // Connections are accepted in order, so the idle one has the only slot
+        let idle_stream = std::net::TcpStream::connect(reject_addr).unwrap();
+        let response = tokio::task::spawn_blocking(move || send_request(reject_addr))
+            .await
+            .unwrap()
+            .unwrap_or_default();
+        assert_eq!(response, "");
+        drop(idle_stream);
+        let response = send_request_until_answered(reject_addr).await;
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+
+        // connections over the limit wait in `Queue` mode
+        let idle_stream = std::net::TcpStream::connect(queue_addr).unwrap();
+        let client = tokio::task::spawn_blocking(move || send_request(queue_addr));
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        assert!(!client.is_finished());
+        drop(idle_stream);
+        let response = client.await.unwrap().unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+
+        // clients sending incomplete headers are disconnected
+        let started_at = std::time::Instant::now();
+        let response = tokio::task::spawn_blocking(move || {
+            use std::io::Read;
+
+            let mut stream = std::net::TcpStream::connect(queue_addr).unwrap();
+            stream
+                .set_read_timeout(Some(Duration::from_secs(5)))
+                .unwrap();
+            stream.write_all(b"GET / HTTP/1.1\r\nHost: local").unwrap();
+
+            let mut response = String::new();
+            stream.read_to_string(&mut response).map(|_| response)
+        })
+        .await
+        .unwrap();
+        assert!(response.is_ok());
+        assert!(started_at.elapsed() < Duration::from_secs(3));
+
+        // idle connections are closed after the keep-alive timeout,
+        // connections are closed after their maximum number of requests
+        for (keep_alive_timeout_secs, max_requests_per_connection) in
+            [(Some(1), None), (None, Some(2))]
+        {
+            let mut shutdown_rx = shutdown_rx.clone();
+            let (addr, _) = spawn_server(
+                Handler::new(GlobalState::default(), handle),
+                BindConfig {
+                    keep_alive_timeout_secs,
+                    max_requests_per_connection,
+                    ..Default::default()
+                },
+                async move {
+                    shutdown_rx.changed().await.ok();
+                },
+            )
+            .await;
+
+            let (responses, closed_after) = tokio::task::spawn_blocking(move || {
+                use std::io::Read;
+
+                let mut stream = std::net::TcpStream::connect(addr).unwrap();
+                stream
+                    .set_read_timeout(Some(Duration::from_secs(5)))
+                    .unwrap();
+                let responses: Vec<_> = (0..max_requests_per_connection.unwrap_or(1))
+                    .map(|_| send_keep_alive_request(&mut stream).unwrap())
+                    .collect();
+
+                let last_response_at = std::time::Instant::now();
+                let mut rest = Vec::new();
+                stream.read_to_end(&mut rest).unwrap();
+                assert!(rest.is_empty());
+                (responses, last_response_at.elapsed())
+            })
+            .await
+            .unwrap();
+            assert!(
+                responses
+                    .iter()
+                    .all(|response| response.starts_with("HTTP/1.1 200 OK"))
+            );
+            assert!(closed_after < Duration::from_secs(3));
         }
+
+        shutdown_tx.send_replace(());
+    }
+
+    /// Sends the HTTP/2 connection preface to `addr`, returns the `SETTINGS_MAX_CONCURRENT_STREAMS` of the server's `SETTINGS`
+    #[cfg(feature = "http2")]
+    fn advertised_max_concurrent_streams(addr: SocketAddr) -> Option<u32> {
+        use std::io::Read;
+
+        const SETTINGS: u8 = 0x4;
+        const SETTINGS_MAX_CONCURRENT_STREAMS: u16 = 0x3;
+
+        let mut stream = std::net::TcpStream::connect(addr).unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        stream
+            .write_all(b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n\0\0\0\x04\0\0\0\0\0")
+            .unwrap();
+
+        // the server starts with its `SETTINGS`
+        let mut frame_header = [0; 9];
+        stream.read_exact(&mut frame_header).unwrap();
+        assert_eq!(frame_header[3], SETTINGS);
+        let length = u32::from_be_bytes([0, frame_header[0], frame_header[1], frame_header[2]]);
+        let mut payload = vec![0; length as usize];
+        stream.read_exact(&mut payload).unwrap();
+
+        payload.chunks_exact(6).find_map(|setting| {
+            (u16::from_be_bytes([setting[0], setting[1]]) == SETTINGS_MAX_CONCURRENT_STREAMS)
+                .then(|| u32::from_be_bytes([setting[2], setting[3], setting[4], setting[5]]))
+        })
+    }
+
+    #[cfg(feature = "http2")]
+    async fn run_test_http2() {
+        use http_body_util::{BodyExt, Empty};
+        use hyper::body::Bytes;
+
+        let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+        let (addr, server) = spawn_server(
+            Handler::new(GlobalState::default(), handle),
+            BindConfig::default(),
+            async {
+                shutdown_rx.await.ok();
+            },
+        )
+        .await;
+
+        // HTTP/2 with prior knowledge
+        {
+            let stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+            let (mut sender, conn) = hyper::client::conn::http2::handshake(
+                hyper_util::rt::TokioExecutor::new(),
+                hyper_util::rt::TokioIo::new(stream),
+            )
+            .await
+            .unwrap();
+            tokio::spawn(conn);
+
+            let request = hyper::Request::builder()
+                .uri(format!("http://{addr}/"))
+                .body(Empty::<Bytes>::new())
+                .unwrap();
+            let response = sender.send_request(request).await.unwrap();
+            assert_eq!(response.version(), http::Version::HTTP_2);
+            assert_eq!(response.status(), http::StatusCode::OK);
+            let body = response.into_body().collect().await.unwrap().to_bytes();
+            assert_eq!(body, "done");
+        }
+
+        // HTTP/1.1 still works
+        let response = tokio::task::spawn_blocking(move || send_request(addr))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+
+        // hyper's limit of concurrent streams is kept unless configured
+        let max_concurrent_streams =
+            tokio::task::spawn_blocking(move || advertised_max_concurrent_streams(addr))
+                .await
+                .unwrap();
+        assert_eq!(max_concurrent_streams, Some(200));
+        let (limited_addr, _) = spawn_server(
+            Handler::new(GlobalState::default(), handle),
+            BindConfig {
+                http2_max_concurrent_streams: Some(10),
+                ..Default::default()
+            },
+            std::future::pending(),
+        )
+        .await;
+        let max_concurrent_streams =
+            tokio::task::spawn_blocking(move || advertised_max_concurrent_streams(limited_addr))
+                .await
+                .unwrap();
+        assert_eq!(max_concurrent_streams, Some(10));
+
+        shutdown_tx.send(()).unwrap();
+        tokio::time::timeout(Duration::from_secs(5), server)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+    }
+
+    /// Generates a self-signed certificate for localhost, writes it to `cert_path` and `key_path` and returns it
+    #[cfg(feature = "tls")]
+    fn write_self_signed_cert(
+        cert_path: &std::path::Path,
+        key_path: &std::path::Path,
+    ) -> tokio_rustls::rustls::pki_types::CertificateDer<'static> {
+        let rcgen::CertifiedKey { cert, signing_key } =
+            rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+        std::fs::write(cert_path, cert.pem()).unwrap();
+        std::fs::write(key_path, signing_key.serialize_pem()).unwrap();
+
+        cert.der().clone()
+    }
+
+    /// Sends a request over TLS, returns the response and the certificate of the server
+    #[cfg(feature = "tls")]
+    async fn send_tls_request(
+        tls_connector: &tokio_rustls::TlsConnector,
+        addr: SocketAddr,
+    ) -> (
+        String,
+        tokio_rustls::rustls::pki_types::CertificateDer<'static>,
+    ) {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        use tokio_rustls::rustls::pki_types::ServerName;
+
+        let stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+        let mut tls_stream = tls_connector
+            .connect(ServerName::try_from("localhost").unwrap(), stream)
+            .await
+            .unwrap();
+        let server_cert = tls_stream.get_ref().1.peer_certificates().unwrap()[0].clone();
+
+        tls_stream
+            .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = String::new();
+        tls_stream.read_to_string(&mut response).await.unwrap();
+
+        (response, server_cert)
+    }
+
+    #[cfg(feature = "tls")]
+    async fn run_test_tls() {
+        use tokio_rustls::{
+            TlsConnector,
+            rustls::{self, ClientConfig, RootCertStore, client::Resumption},
+        };
+
+        use crate::data::{config::TlsConfig, session_id::SessionId};
+
+        let directory =
+            std::env::temp_dir().join(format!("wired_handler_tls_{}", SessionId::generate()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let cert_path = directory.join("cert.pem");
+        let key_path = directory.join("key.pem");
+
+        let first_cert = write_self_signed_cert(&cert_path, &key_path);
+
+        let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+        let (addr, server) = spawn_server(
+            Handler::new(GlobalState::default(), handle),
+            BindConfig {
+                tls: Some(TlsConfig {
+                    reload_interval_secs: Some(1),
+                    ..TlsConfig::new(&cert_path, &key_path)
+                }),
+                ..Default::default()
+            },
+            async {
+                shutdown_rx.await.ok();
+            },
+        )
+        .await;
+
+        // invalid key fails to start
+        {
+            let invalid_key_path = directory.join("invalid_key.pem");
+            std::fs::write(&invalid_key_path, "invalid").unwrap();
+            let handler = Handler::new(GlobalState::default(), handle);
+            let result = handler
+                .run_http_server(BindConfig {
+                    addr: "127.0.0.1:0".into(),
+                    tls: Some(TlsConfig::new(&cert_path, &invalid_key_path)),
+                    ..Default::default()
+                })
+                .await;
+            assert!(matches!(result, Err(super::RunHttpServerError::Tls(_))));
+        }
+
+        let second_cert = {
+            let second_cert_path = directory.join("second_cert.pem");
+            let second_key_path = directory.join("second_key.pem");
+            let second_cert = write_self_signed_cert(&second_cert_path, &second_key_path);
+            // file times can be too coarse to tell the files apart from the first ones
+            for path in [&second_cert_path, &second_key_path] {
+                std::fs::File::options()
+                    .write(true)
+                    .open(path)
+                    .unwrap()
+                    .set_modified(std::time::SystemTime::now() + Duration::from_secs(1))
+                    .unwrap();
+            }
+            (second_cert, second_cert_path, second_key_path)
+        };
+
+        let tls_connector = {
+            let mut root_cert_store = RootCertStore::empty();
+            root_cert_store.add(first_cert.clone()).unwrap();
+            root_cert_store.add(second_cert.0.clone()).unwrap();
+            let mut client_config = ClientConfig::builder_with_provider(Arc::new(
+                rustls::crypto::ring::default_provider(),
+            ))
+            .with_safe_default_protocol_versions()
+            .unwrap()
+            .with_root_certificates(root_cert_store)
+            .with_no_client_auth();
+            // a resumed session would report the certificate of the first handshake
+            client_config.resumption = Resumption::disabled();
+            TlsConnector::from(Arc::new(client_config))
+        };
+
+        // served over TLS
+        let (response, server_cert) = send_tls_request(&tls_connector, addr).await;
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.ends_with("done"));
+        assert_eq!(server_cert, first_cert);
+
+        // certificate is reloaded when the files change
+        let (second_cert, second_cert_path, second_key_path) = second_cert;
+        std::fs::rename(second_key_path, &key_path).unwrap();
+        std::fs::rename(second_cert_path, &cert_path).unwrap();
+        tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                let (response, server_cert) = send_tls_request(&tls_connector, addr).await;
+                assert!(response.ends_with("done"));
+                if server_cert == second_cert {
+                    break;
+                }
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+        })
+        .await
+        .unwrap();
+
+        shutdown_tx.send(()).unwrap();
+        tokio::time::timeout(Duration::from_secs(5), server)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+
+        std::fs::remove_dir_all(directory).unwrap();
     }
 }
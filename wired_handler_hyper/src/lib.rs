@@ -2,7 +2,16 @@
 
 pub mod data;
 mod http;
+mod listener;
 pub mod prelude;
+mod server;
 pub mod state;
+#[cfg(test)]
+mod test_util;
+#[cfg(feature = "tls")]
+mod tls;
 
 pub use http::*;
+pub use server::HttpServer;
+#[cfg(feature = "tls")]
+pub use tls::TlsError;
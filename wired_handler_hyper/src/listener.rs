@@ -0,0 +1,764 @@
+use std::{
+    io,
+    net::SocketAddr,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use tokio::{
+    io::{AsyncRead, AsyncWrite, ReadBuf},
+    net::{TcpListener, TcpStream},
+};
+
+use crate::data::config::BindConfig;
+
+#[cfg(unix)]
+use std::{
+    mem::MaybeUninit,
+    os::fd::{FromRawFd, RawFd},
+    path::{Path, PathBuf},
+    sync::{Mutex, OnceLock, PoisonError},
+};
+#[cfg(unix)]
+use tokio::net::{UnixListener, UnixStream};
+
+/// First file descriptor passed by systemd socket activation
+#[cfg(unix)]
+const SD_LISTEN_FDS_START: RawFd = 3;
+
+/// The Unix socket file created by the server. Removed by `remove`, or in the background when dropped
+#[cfg(unix)]
+#[derive(Debug)]
+pub(crate) struct UnixSocketFile(Option<PathBuf>);
+
+#[cfg(unix)]
+impl UnixSocketFile {
+    async fn remove(mut self) {
+        if let Some(path) = self.0.take() {
+            remove_socket_file(&path, tokio::fs::remove_file(&path).await);
+        }
+    }
+}
+
+#[cfg(unix)]
+fn remove_socket_file(path: &Path, result: io::Result<()>) {
+    if let Err(err) = result {
+        tracing::debug!("failed to remove unix socket {}: {err}", path.display());
+    }
+}
+
+#[cfg(unix)]
+impl Drop for UnixSocketFile {
+    fn drop(&mut self) {
+        let Some(path) = self.0.take() else {
+            return;
+        };
+        // only blocks if there's no runtime to do it in the background
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) => {
+                handle.spawn_blocking(move || {
+                    remove_socket_file(&path, std::fs::remove_file(&path));
+                });
+            }
+            Err(_) => remove_socket_file(&path, std::fs::remove_file(&path)),
+        }
+    }
+}
+
+/// Listens for connections on the address of a `BindConfig`
+#[derive(Debug)]
+pub(crate) enum Listener {
+    Tcp {
+        tcp_listener: TcpListener,
+        /// Declared after the listener to only be released once it's closed
+        #[cfg(unix)]
+        _claimed_fd: Option<ClaimedFd>,
+    },
+    #[cfg(unix)]
+    Unix {
+        unix_listener: UnixListener,
+        /// Removes the socket file when dropped, `None` if the listener has been inherited
+        _unix_socket_file: Option<UnixSocketFile>,
+        _claimed_fd: Option<ClaimedFd>,
+    },
+}
+
+impl From<TcpListener> for Listener {
+    fn from(tcp_listener: TcpListener) -> Self {
+        Self::Tcp {
+            tcp_listener,
+            #[cfg(unix)]
+            _claimed_fd: None,
+        }
+    }
+}
+
+/// A connection accepted by a `Listener`
+#[derive(Debug)]
+pub(crate) enum Stream {
+    Tcp(TcpStream),
+    #[cfg(unix)]
+    Unix(UnixStream),
+}
+
+fn invalid_addr(addr: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("invalid listen address {addr}"),
+    )
+}
+
+/// Removes the socket file at `path` if no server is listening on it anymore
+#[cfg(unix)]
+async fn remove_stale_socket(path: &Path) -> io::Result<()> {
+    use std::os::unix::fs::FileTypeExt;
+
+    let metadata = match tokio::fs::symlink_metadata(path).await {
+        Ok(metadata) => metadata,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err),
+    };
+
+    if !metadata.file_type().is_socket() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} exists and is not a socket", path.display()),
+        ));
+    }
+
+    if UnixStream::connect(path).await.is_ok() {
+        return Err(io::Error::new(
+            io::ErrorKind::AddrInUse,
+            format!("{} is in use by another server", path.display()),
+        ));
+    }
+
+    tracing::debug!("removing stale unix socket {}", path.display());
+    tokio::fs::remove_file(path).await
+}
+
+/// Binds a Unix socket at `path` with the permissions `mode`.
+/// It's created in a private directory and moved to `path` once it has its permissions,
+/// so it's never accessible with the permissions of the umask
+#[cfg(unix)]
+async fn bind_unix_with_mode(path: &Path, mode: u32) -> io::Result<UnixListener> {
+    use std::os::unix::fs::PermissionsExt;
+
+    // in the same directory for moving the socket, with a short name to keep within the length limit of socket paths
+    let parent = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let private_dir = parent.join(format!(
+        ".{}",
+        &uuid::Uuid::new_v4().simple().to_string()[..8]
+    ));
+    tokio::fs::DirBuilder::new()
+        .mode(0o700)
+        .create(&private_dir)
+        .await?;
+
+    let private_path = private_dir.join("sock");
+    let result = async {
+        let unix_listener = UnixListener::bind(&private_path)?;
+        tokio::fs::set_permissions(&private_path, std::fs::Permissions::from_mode(mode)).await?;
+        tokio::fs::rename(&private_path, path).await?;
+        Ok(unix_listener)
+    }
+    .await;
+
+    // only contains the socket if anything has failed
+    if let Err(err) = tokio::fs::remove_dir_all(&private_dir).await {
+        tracing::debug!("failed to remove {}: {err}", private_dir.display());
+    }
+
+    result
+}
+
+#[cfg(unix)]
+async fn bind_unix(path: &Path, mode: Option<u32>) -> io::Result<Listener> {
+    remove_stale_socket(path).await?;
+    let unix_listener = match mode {
+        Some(mode) => bind_unix_with_mode(path, mode).await?,
+        None => UnixListener::bind(path)?,
+    };
+    let unix_socket_file = UnixSocketFile(Some(path.to_path_buf()));
+
+    Ok(Listener::Unix {
+        unix_listener,
+        _unix_socket_file: Some(unix_socket_file),
+        _claimed_fd: None,
+    })
+}
+
+/// File descriptors owned by a `Listener`, so the same one isn't used (and closed) twice
+#[cfg(unix)]
+static CLAIMED_FDS: Mutex<Vec<RawFd>> = Mutex::new(Vec::new());
+
+/// A file descriptor in `CLAIMED_FDS`. Released when dropped, so the number can be reused after it's closed
+#[cfg(unix)]
+#[derive(Debug)]
+pub(crate) struct ClaimedFd(RawFd);
+
+#[cfg(unix)]
+impl Drop for ClaimedFd {
+    fn drop(&mut self) {
+        CLAIMED_FDS
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .retain(|&fd| fd != self.0);
+    }
+}
+
+/// Returns the address family of `fd`, fails if it isn't a listening socket. Doesn't take ownership of `fd`
+#[cfg(unix)]
+fn listening_socket_family(fd: RawFd) -> io::Result<libc::c_int> {
+    let invalid_fd = |reason: &str| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("file descriptor {fd} {reason}"),
+        )
+    };
+
+    let mut stat = MaybeUninit::<libc::stat>::uninit();
+    // SAFETY: `fstat` only writes into `stat`. Invalid file descriptors fail with `EBADF`
+    if unsafe { libc::fstat(fd, stat.as_mut_ptr()) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    // SAFETY: initialized by the successful `fstat`
+    let stat = unsafe { stat.assume_init() };
+    if stat.st_mode & libc::S_IFMT != libc::S_IFSOCK {
+        return Err(invalid_fd("isn't a socket"));
+    }
+
+    let mut is_listening: libc::c_int = 0;
+    let mut len = std::mem::size_of::<libc::c_int>() as libc::socklen_t;
+    // SAFETY: `getsockopt` writes at most `len` bytes into `is_listening`
+    let result = unsafe {
+        libc::getsockopt(
+            fd,
+            libc::SOL_SOCKET,
+            libc::SO_ACCEPTCONN,
+            (&raw mut is_listening).cast(),
+            &mut len,
+        )
+    };
+    if result != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    if is_listening == 0 {
+        return Err(invalid_fd("isn't a listening socket"));
+    }
+
+    // SAFETY: all zeros is a valid `sockaddr_storage`
+    let mut addr: libc::sockaddr_storage = unsafe { std::mem::zeroed() };
+    let mut len = std::mem::size_of::<libc::sockaddr_storage>() as libc::socklen_t;
+    // SAFETY: `getsockname` writes at most `len` bytes into `addr`
+    if unsafe { libc::getsockname(fd, (&raw mut addr).cast(), &mut len) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(addr.ss_family.into())
+}
+
+/// Creates a `Listener` from an inherited file descriptor, which can be a TCP or Unix socket.
+/// Fails without closing it if it's neither or already used by another `Listener`
+#[cfg(unix)]
+fn listener_from_fd(fd: RawFd) -> io::Result<Listener> {
+    let mut claimed_fds = CLAIMED_FDS.lock().unwrap_or_else(PoisonError::into_inner);
+    if claimed_fds.contains(&fd) {
+        return Err(io::Error::new(
+            io::ErrorKind::AddrInUse,
+            format!("file descriptor {fd} is already used by another listener"),
+        ));
+    }
+
+    let family = listening_socket_family(fd)?;
+    if ![libc::AF_INET, libc::AF_INET6, libc::AF_UNIX].contains(&family) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("socket {fd} is neither a TCP nor a Unix socket"),
+        ));
+    }
+    claimed_fds.push(fd);
+    drop(claimed_fds);
+    // released after the listener below on errors as well
+    let claimed_fd = ClaimedFd(fd);
+
+    if family == libc::AF_UNIX {
+        // SAFETY: `fd` is a listening socket passed to the process for being used as listener,
+        // no other `Listener` owns it since it hasn't been claimed before
+        let unix_listener = unsafe { std::os::unix::net::UnixListener::from_raw_fd(fd) };
+        unix_listener.set_nonblocking(true)?;
+        return Ok(Listener::Unix {
+            unix_listener: UnixListener::from_std(unix_listener)?,
+            _unix_socket_file: None,
+            _claimed_fd: Some(claimed_fd),
+        });
+    }
+
+    // SAFETY: like above
+    let tcp_listener = unsafe { std::net::TcpListener::from_raw_fd(fd) };
+    tcp_listener.set_nonblocking(true)?;
+
+    Ok(Listener::Tcp {
+        tcp_listener: TcpListener::from_std(tcp_listener)?,
+        _claimed_fd: Some(claimed_fd),
+    })
+}
+
+/// Number of sockets passed by systemd, `None` if the process hasn't been activated by systemd.
+/// Read once, the environment is left as it is since other threads may read it at the same time
+#[cfg(unix)]
+static SYSTEMD_LISTEN_FDS: OnceLock<Option<usize>> = OnceLock::new();
+
+/// Parses the number of sockets passed by systemd from the values of `LISTEN_PID` and `LISTEN_FDS`.
+/// `None` if they're missing or meant for another process than `pid`
+#[cfg(unix)]
+fn parse_systemd_listen_fds(
+    listen_pid: Option<&str>,
+    listen_fds: Option<&str>,
+    pid: u32,
+) -> Option<usize> {
+    if listen_pid?.parse::<u32>().ok()? != pid {
+        return None;
+    }
+    listen_fds?.parse().ok()
+}
+
+/// Reads the number of sockets passed by systemd from the environment
+#[cfg(unix)]
+fn read_systemd_listen_fds() -> Option<usize> {
+    let listen_pid = std::env::var("LISTEN_PID").ok();
+    let listen_fds = std::env::var("LISTEN_FDS").ok();
+
+    parse_systemd_listen_fds(
+        listen_pid.as_deref(),
+        listen_fds.as_deref(),
+        std::process::id(),
+    )
+}
+
+/// Returns the file descriptor of the socket at `index` passed by systemd
+#[cfg(unix)]
+fn systemd_fd(index: usize) -> io::Result<RawFd> {
+    let not_activated = || io::Error::new(io::ErrorKind::NotFound, "no sockets passed by systemd");
+
+    let listen_fds = SYSTEMD_LISTEN_FDS
+        .get_or_init(read_systemd_listen_fds)
+        .ok_or_else(not_activated)?;
+    if index >= listen_fds {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("systemd passed {listen_fds} sockets, index {index} doesn't exist"),
+        ));
+    }
+
+    RawFd::try_from(index)
+        .ok()
+        .and_then(|index| SD_LISTEN_FDS_START.checked_add(index))
+        .ok_or_else(not_activated)
+}
+
+impl Listener {
+    /// Binds to the `addr` of `bind_config`
+    pub(crate) async fn bind(bind_config: &BindConfig) -> io::Result<Self> {
+        let addr = bind_config.addr.as_str();
+
+        #[cfg(unix)]
+        if let Some(path) = addr.strip_prefix("unix:") {
+            return bind_unix(Path::new(path), bind_config.unix_socket_mode).await;
+        }
+
+        #[cfg(unix)]
+        if let Some(fd) = addr.strip_prefix("fd:") {
+            let fd = fd.parse().map_err(|_| invalid_addr(addr))?;
+            return listener_from_fd(fd);
+        }
+
+        #[cfg(unix)]
+        if let Some(index) = addr.strip_prefix("systemd") {
+            let index = match index.strip_prefix(':') {
+                Some(index) => index.parse().map_err(|_| invalid_addr(addr))?,
+                None if index.is_empty() => 0,
+                None => return Err(invalid_addr(addr)),
+            };
+            return listener_from_fd(systemd_fd(index)?);
+        }
+
+        Ok(TcpListener::bind(addr).await?.into())
+    }
+
+    /// Stops listening and removes the Unix socket file created by `bind`
+    pub(crate) async fn close(self) {
+        #[cfg(unix)]
+        if let Self::Unix {
+            unix_listener,
+            _unix_socket_file: Some(unix_socket_file),
+            ..
+        } = self
+        {
+            drop(unix_listener);
+            unix_socket_file.remove().await;
+        }
+    }
+
+    /// Accepts a new connection, returns the addresses of the peer and the local socket for TCP connections
+    pub(crate) async fn accept(&self) -> io::Result<(Stream, Option<(SocketAddr, SocketAddr)>)> {
+        match self {
+            Self::Tcp { tcp_listener, .. } => {
+                let (tcp_stream, peer_addr) = tcp_listener.accept().await?;
+                let local_addr = tcp_stream
+                    .local_addr()
+                    .or_else(|_| tcp_listener.local_addr())?;
+                Ok((Stream::Tcp(tcp_stream), Some((peer_addr, local_addr))))
+            }
+            #[cfg(unix)]
+            Self::Unix { unix_listener, .. } => {
+                let (unix_stream, _) = unix_listener.accept().await?;
+                Ok((Stream::Unix(unix_stream), None))
+            }
+        }
+    }
+}
+
+impl AsyncRead for Stream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Tcp(tcp_stream) => Pin::new(tcp_stream).poll_read(cx, buf),
+            #[cfg(unix)]
+            Self::Unix(unix_stream) => Pin::new(unix_stream).poll_read(cx, buf),
+        }
+    }
+}
+
+impl AsyncWrite for Stream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            Self::Tcp(tcp_stream) => Pin::new(tcp_stream).poll_write(cx, buf),
+            #[cfg(unix)]
+            Self::Unix(unix_stream) => Pin::new(unix_stream).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            Self::Tcp(tcp_stream) => Pin::new(tcp_stream).poll_write_vectored(cx, bufs),
+            #[cfg(unix)]
+            Self::Unix(unix_stream) => Pin::new(unix_stream).poll_write_vectored(cx, bufs),
+        }
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        match self {
+            Self::Tcp(tcp_stream) => tcp_stream.is_write_vectored(),
+            #[cfg(unix)]
+            Self::Unix(unix_stream) => unix_stream.is_write_vectored(),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Tcp(tcp_stream) => Pin::new(tcp_stream).poll_flush(cx),
+            #[cfg(unix)]
+            Self::Unix(unix_stream) => Pin::new(unix_stream).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Tcp(tcp_stream) => Pin::new(tcp_stream).poll_shutdown(cx),
+            #[cfg(unix)]
+            Self::Unix(unix_stream) => Pin::new(unix_stream).poll_shutdown(cx),
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod test {
+    use std::{
+        io::{self, Write},
+        os::fd::{AsRawFd, IntoRawFd},
+    };
+
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    use super::{Listener, SD_LISTEN_FDS_START, parse_systemd_listen_fds};
+    use crate::data::{config::BindConfig, session_id::SessionId};
+
+    fn bind_config(addr: String, unix_socket_mode: Option<u32>) -> BindConfig {
+        BindConfig {
+            addr,
+            unix_socket_mode,
+            ..Default::default()
+        }
+    }
+
+    /// Accepts a connection on `listener` and echoes a single message
+    async fn echo_once(
+        listener: &Listener,
+    ) -> Option<(std::net::SocketAddr, std::net::SocketAddr)> {
+        let (mut stream, addrs) = listener.accept().await.unwrap();
+        let mut buf = [0; 4];
+        stream.read_exact(&mut buf).await.unwrap();
+        stream.write_all(&buf).await.unwrap();
+        addrs
+    }
+
+    #[test]
+    fn test() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(run_test());
+    }
+
+    #[test]
+    fn test_parse_systemd_listen_fds() {
+        assert_eq!(parse_systemd_listen_fds(Some("42"), Some("2"), 42), Some(2));
+        // meant for another process
+        assert_eq!(parse_systemd_listen_fds(Some("41"), Some("2"), 42), None);
+        // missing or invalid
+        assert_eq!(parse_systemd_listen_fds(None, Some("2"), 42), None);
+        assert_eq!(parse_systemd_listen_fds(Some("42"), None, 42), None);
+        assert_eq!(
+            parse_systemd_listen_fds(Some("invalid"), Some("2"), 42),
+            None
+        );
+        assert_eq!(
+            parse_systemd_listen_fds(Some("42"), Some("invalid"), 42),
+            None
+        );
+    }
+
+    /// Passes a listening socket to a child process like systemd does and lets it echo a message
+    #[test]
+    fn test_systemd() {
+        use std::{
+            io::Read,
+            os::unix::process::CommandExt,
+            process::{Command, Stdio},
+            time::Duration,
+        };
+
+        let std_listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let local_addr = std_listener.local_addr().unwrap();
+        let fd = std_listener.as_raw_fd();
+
+        // the shell passes its PID, which `exec` keeps, like systemd sets `LISTEN_PID` after forking
+        let mut command = Command::new("sh");
+        command
+            .args(["-c", r#"LISTEN_PID=$$ exec "$0" "$@""#])
+            .arg(std::env::current_exe().unwrap())
+            .args(["--exact", "listener::test::systemd_child", "--ignored"])
+            .env("LISTEN_FDS", "1")
+            .env(SYSTEMD_CHILD_VAR, "1")
+            .stdout(Stdio::null());
+        // SAFETY: `fcntl` and `dup2` are async-signal-safe. `dup2` doesn't copy the close-on-exec flag
+        unsafe {
+            command.pre_exec(move || {
+                let result = if fd == SD_LISTEN_FDS_START {
+                    libc::fcntl(fd, libc::F_SETFD, 0)
+                } else {
+                    libc::dup2(fd, SD_LISTEN_FDS_START)
+                };
+                if result == -1 {
+                    return Err(io::Error::last_os_error());
+                }
+                Ok(())
+            });
+        }
+        let mut child = command.spawn().unwrap();
+        drop(std_listener);
+
+        let mut stream = std::net::TcpStream::connect(local_addr).unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(10)))
+            .unwrap();
+        stream.write_all(b"ping").unwrap();
+        let mut buf = [0; 4];
+        stream.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"ping");
+
+        assert!(child.wait().unwrap().success());
+    }
+
+    /// Set for the child process of `test_systemd`
+    const SYSTEMD_CHILD_VAR: &str = "WIRED_HANDLER_SYSTEMD_CHILD";
+
+    /// Run by `test_systemd` in a child process with the socket of systemd
+    #[test]
+    #[ignore = "run by test_systemd"]
+    fn systemd_child() {
+        if std::env::var_os(SYSTEMD_CHILD_VAR).is_none() {
+            return;
+        }
+
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let err = Listener::bind(&bind_config("systemd:1".into(), None))
+                .await
+                .unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::NotFound);
+
+            let listener = Listener::bind(&bind_config("systemd".into(), None))
+                .await
+                .unwrap();
+            assert!(matches!(listener, Listener::Tcp { .. }));
+            assert!(echo_once(&listener).await.is_some());
+        });
+    }
+
+    async fn run_test() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let directory =
+            std::env::temp_dir().join(format!("wired_handler_listener_{}", SessionId::generate()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let socket_path = directory.join("server.sock");
+        let addr = format!("unix:{}", socket_path.display());
+
+        // stale socket is removed
+        drop(std::os::unix::net::UnixListener::bind(&socket_path).unwrap());
+        assert!(socket_path.exists());
+
+        let listener = Listener::bind(&bind_config(addr.clone(), Some(0o600)))
+            .await
+            .unwrap();
+        let mode = std::fs::metadata(&socket_path)
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o600);
+        // the private directory the socket has been created in is removed
+        assert_eq!(std::fs::read_dir(&directory).unwrap().count(), 1);
+
+        let client = tokio::spawn({
+            let socket_path = socket_path.clone();
+            async move {
+                let mut stream = tokio::net::UnixStream::connect(socket_path).await.unwrap();
+                stream.write_all(b"ping").await.unwrap();
+                let mut buf = [0; 4];
+                stream.read_exact(&mut buf).await.unwrap();
+                buf
+            }
+        });
+        assert!(echo_once(&listener).await.is_none());
+        assert_eq!(&client.await.unwrap(), b"ping");
+
+        // socket in use isn't removed
+        let err = Listener::bind(&bind_config(addr.clone(), None))
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::AddrInUse);
+
+        // socket file is removed when closing the listener
+        listener.close().await;
+        assert!(!socket_path.exists());
+
+        // or in the background when dropping it
+        let listener = Listener::bind(&bind_config(addr.clone(), None))
+            .await
+            .unwrap();
+        assert!(socket_path.exists());
+        drop(listener);
+        for _ in 0..100 {
+            if !socket_path.exists() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert!(!socket_path.exists());
+
+        // other files aren't removed
+        std::fs::write(&socket_path, "").unwrap();
+        let err = Listener::bind(&bind_config(addr, None)).await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+        assert!(socket_path.exists());
+
+        // inherited file descriptor
+        let std_listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let local_addr = std_listener.local_addr().unwrap();
+        let fd = std_listener.into_raw_fd();
+        let listener = Listener::bind(&bind_config(format!("fd:{fd}"), None))
+            .await
+            .unwrap();
+        assert!(matches!(listener, Listener::Tcp { .. }));
+
+        let client = tokio::spawn(async move {
+            let mut stream = tokio::net::TcpStream::connect(local_addr).await.unwrap();
+            stream.write_all(b"pong").await.unwrap();
+            let mut buf = [0; 4];
+            stream.read_exact(&mut buf).await.unwrap();
+            (stream.local_addr().unwrap(), buf)
+        });
+        let (peer_addr, accepted_local_addr) = echo_once(&listener).await.unwrap();
+        let (client_addr, buf) = client.await.unwrap();
+        assert_eq!(&buf, b"pong");
+        assert_eq!(peer_addr, client_addr);
+        assert_eq!(accepted_local_addr, local_addr);
+
+        // file descriptors can only be used by one listener
+        let err = Listener::bind(&bind_config(format!("fd:{fd}"), None))
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::AddrInUse);
+
+        // the number of a closed file descriptor can be used again
+        let std_listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        // SAFETY: `F_DUPFD` returns a new file descriptor, at least 500 to not take one used by other tests
+        let duplicated_fd = unsafe { libc::fcntl(std_listener.as_raw_fd(), libc::F_DUPFD, 500) };
+        assert!(duplicated_fd >= 500);
+        let listener = Listener::bind(&bind_config(format!("fd:{duplicated_fd}"), None))
+            .await
+            .unwrap();
+        drop(listener);
+        // SAFETY: like above, returns the lowest free number, which is the closed one
+        let reused_fd =
+            unsafe { libc::fcntl(std_listener.as_raw_fd(), libc::F_DUPFD, duplicated_fd) };
+        assert_eq!(reused_fd, duplicated_fd);
+        let listener = Listener::bind(&bind_config(format!("fd:{reused_fd}"), None))
+            .await
+            .unwrap();
+        assert!(matches!(listener, Listener::Tcp { .. }));
+        drop(listener);
+
+        // other file descriptors are neither used nor closed
+        let mut file = std::fs::File::create(directory.join("file")).unwrap();
+        let udp_socket = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        for other_fd in [file.as_raw_fd(), udp_socket.as_raw_fd()] {
+            let err = Listener::bind(&bind_config(format!("fd:{other_fd}"), None))
+                .await
+                .unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        }
+        file.write_all(b"still open").unwrap();
+        udp_socket.local_addr().unwrap();
+
+        // invalid addresses
+        for addr in ["fd:invalid", "systemd:invalid", "systemdx"] {
+            let err = Listener::bind(&bind_config(addr.into(), None))
+                .await
+                .unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        }
+
+        // not activated by systemd
+        let err = Listener::bind(&bind_config("systemd".into(), None))
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+}
@@ -3,12 +3,22 @@ pub use wired_handler::prelude::*;
 pub use crate::{
     actions,
     data::{
+        bind_config::ContextBindConfigExt,
+        connection_info::ContextConnectionInfoExt,
+        cookies::ContextCookieExt,
+        mount::ContextMountExt,
         path::ContextGetPathExt,
         query_params::ContextGetQueryParamsExt,
         request::ContextGetRequestExt,
-        request_body::ContextGetBodyExt,
+        request_body::{ContextBodyStreamExt, ContextGetBodyExt},
         response::{ContextReturnResponseExt, ResponseBuilderExt},
         response_body::{CtxParseBodyExt, ResponseBodyExt, ResponseBuilderParsedBodyExt},
+        router::{ContextPathParamsExt, ContextRouterExt, ContextUrlForExt},
+        session::{
+            ContextGetSessionIdExt, ContextPersistSessionExt, ContextResolveSessionExt,
+            ContextSessionDataExt,
+        },
+        sse::ContextSseExt,
     },
     http::RunHttpServerExt,
     routes, run_handler,
@@ -17,6 +27,12 @@ pub use crate::{
 #[cfg(feature = "diesel")]
 pub use crate::data::db::{ContextGetDbExt, DbConnectionExt, DbPoolExt, LoadDbExt};
 
+#[cfg(feature = "multipart")]
+pub use crate::data::multipart::ContextMultipartExt;
+
+#[cfg(feature = "serve-dir")]
+pub use crate::data::serve_dir::ContextServeDirExt;
+
 #[cfg(feature = "websocket")]
 pub use crate::data::{
     message::ContextMessageExt, send_message::ContextSendMessageExt, websocket::ContextWebsocketExt,
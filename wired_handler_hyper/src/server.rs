@@ -0,0 +1,267 @@
+use std::{future::Future, pin::pin};
+
+use futures::future::{BoxFuture, Either};
+use tokio::sync::watch;
+use wired_handler::Handler;
+
+use crate::{
+    RunHttpServerError, RunHttpServerExt,
+    data::config::BindConfig,
+    state::{
+        context::{HttpRequestContext, SessionlessRequestContext},
+        global_state::GlobalState,
+    },
+};
+
+/// Runs a listener until the `watch::Receiver` turns `true`
+type RunListenerFn = Box<
+    dyn FnOnce(watch::Receiver<bool>) -> BoxFuture<'static, Result<(), RunHttpServerError>> + Send,
+>;
+
+/// Waits until `shutdown_rx` turns `true`
+async fn wait_for_shutdown(mut shutdown_rx: watch::Receiver<bool>) {
+    // an error means the sender is gone, which is a shutdown as well
+    let _ = shutdown_rx.wait_for(|shutdown| *shutdown).await;
+}
+
+/// Runs multiple listeners, each with its own `Handler`, under one shutdown signal
+///
+/// Create the `Handler`s with clones of the same `GlobalState` to share it between listeners
+#[derive(Default)]
+pub struct HttpServer {
+    listeners: Vec<RunListenerFn>,
+}
+
+impl std::fmt::Debug for HttpServer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HttpServer")
+            .field("listeners", &self.listeners.len())
+            .finish()
+    }
+}
+
+impl HttpServer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a listener on `bind_config`, handled by `handler`
+    pub fn listen<F: Future<Output = HttpRequestContext> + 'static + Send>(
+        mut self,
+        bind_config: BindConfig,
+        handler: Handler<SessionlessRequestContext, HttpRequestContext, GlobalState, F>,
+    ) -> Self {
+        self.listeners.push(Box::new(move |shutdown_rx| {
+            Box::pin(
+                handler.run_http_server_with_shutdown(bind_config, wait_for_shutdown(shutdown_rx)),
+            )
+        }));
+        self
+    }
+
+    /// Runs all listeners until one of them fails
+    pub async fn run(self) -> Result<(), RunHttpServerError> {
+        self.run_with_shutdown(std::future::pending()).await
+    }
+
+    /// Runs all listeners until `shutdown` completes or one of them fails, which shuts down the others.
+    /// Every listener shuts down gracefully like `run_http_server_with_shutdown`.
+    /// Returns the first error of any listener
+    pub async fn run_with_shutdown(
+        self,
+        shutdown: impl Future<Output = ()>,
+    ) -> Result<(), RunHttpServerError> {
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+
+        let listeners = futures::future::join_all(self.listeners.into_iter().map(|run_listener| {
+            let shutdown_tx = &shutdown_tx;
+            let listener = run_listener(shutdown_rx.clone());
+            async move {
+                let result = listener.await;
+                if result.is_err() {
+                    shutdown_tx.send_replace(true);
+                }
+                result
+            }
+        }));
+
+        let results = match futures::future::select(pin!(shutdown), pin!(listeners)).await {
+            Either::Left(((), listeners)) => {
+                shutdown_tx.send_replace(true);
+                listeners.await
+            }
+            Either::Right((results, _)) => results,
+        };
+
+        results.into_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use tokio::sync::oneshot;
+    use wired_handler::Handler;
+
+    use super::{HttpServer, wait_for_shutdown};
+    use crate::{
+        data::{config::BindConfig, response::Response, response_body::ResponseBody},
+        http::run_http_server_on_listener,
+        listener::Listener,
+        prelude::*,
+        state::{
+            context::{HttpRequestContext, SessionlessRequestContext},
+            global_state::GlobalState,
+            session_state::SessionState,
+        },
+        test_util::{bind_local, send_request},
+    };
+
+    #[derive(Debug, Default)]
+    struct RequestCount(usize);
+
+    /// Adds a listener like `HttpServer::listen` on the already bound `listener`
+    fn listen_on<F: Future<Output = HttpRequestContext> + 'static + Send>(
+        mut http_server: HttpServer,
+        listener: Listener,
+        bind_config: BindConfig,
+        handler: Handler<SessionlessRequestContext, HttpRequestContext, GlobalState, F>,
+    ) -> HttpServer {
+        http_server.listeners.push(Box::new(move |shutdown_rx| {
+            Box::pin(run_http_server_on_listener(
+                handler,
+                listener,
+                bind_config,
+                wait_for_shutdown(shutdown_rx),
+            ))
+        }));
+        http_server
+    }
+
+    fn respond(ctx: SessionlessRequestContext, body: String) -> HttpRequestContext {
+        let (global_state, request_state) = ctx.into_states();
+        let mut ctx =
+            HttpRequestContext::from_states(global_state, SessionState::default(), request_state);
+
+        let _ = ctx.next(
+            Response::builder()
+                .body(ResponseBody::from_bytes(body))
+                .unwrap(),
+        );
+
+        ctx
+    }
+
+    async fn handle_public(ctx: SessionlessRequestContext) -> HttpRequestContext {
+        GlobalState::get_from_ctx(&ctx)
+            .get_mut_or_insert_default::<RequestCount>()
+            .await
+            .0 += 1;
+
+        let max_connections = ctx.bind_config().max_connections;
+        respond(ctx, format!("public, max connections: {max_connections:?}"))
+    }
+
+    async fn handle_admin(ctx: SessionlessRequestContext) -> HttpRequestContext {
+        let request_count = GlobalState::get_from_ctx(&ctx)
+            .get::<RequestCount>()
+            .await
+            .map(|request_count| request_count.0)
+            .unwrap_or_default();
+
+        let max_connections = ctx.bind_config().max_connections;
+        respond(
+            ctx,
+            format!("requests: {request_count}, max connections: {max_connections:?}"),
+        )
+    }
+
+    #[test]
+    fn test() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(run_test());
+    }
+
+    async fn run_test() {
+        let global_state = GlobalState::default();
+        let (public_listener, public_addr) = bind_local().await;
+        let (admin_listener, admin_addr) = bind_local().await;
+        let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+        let http_server = listen_on(
+            HttpServer::new(),
+            public_listener,
+            BindConfig {
+                max_connections: Some(100),
+                ..Default::default()
+            },
+            Handler::new(global_state.clone(), handle_public),
+        );
+        let http_server = listen_on(
+            http_server,
+            admin_listener,
+            BindConfig {
+                max_connections: Some(1),
+                ..Default::default()
+            },
+            Handler::new(global_state, handle_admin),
+        );
+        let server = tokio::spawn(http_server.run_with_shutdown(async {
+            shutdown_rx.await.ok();
+        }));
+
+        // each listener has its own handler and `BindConfig`, sharing the `GlobalState`
+        for _ in 0..2 {
+            let response = tokio::task::spawn_blocking(move || send_request(public_addr))
+                .await
+                .unwrap()
+                .unwrap();
+            assert!(response.ends_with("public, max connections: Some(100)"));
+        }
+        let response = tokio::task::spawn_blocking(move || send_request(admin_addr))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(response.ends_with("requests: 2, max connections: Some(1)"));
+
+        // one signal shuts down all listeners
+        shutdown_tx.send(()).unwrap();
+        tokio::time::timeout(Duration::from_secs(5), server)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        for addr in [public_addr, admin_addr] {
+            assert!(
+                tokio::task::spawn_blocking(move || send_request(addr))
+                    .await
+                    .unwrap()
+                    .is_err()
+            );
+        }
+
+        // a failing listener shuts down the others
+        let result = tokio::time::timeout(
+            Duration::from_secs(5),
+            HttpServer::new()
+                .listen(
+                    BindConfig {
+                        addr: "127.0.0.1:0".into(),
+                        ..Default::default()
+                    },
+                    Handler::new(GlobalState::default(), handle_public),
+                )
+                .listen(
+                    BindConfig {
+                        addr: "invalid".into(),
+                        ..Default::default()
+                    },
+                    Handler::new(GlobalState::default(), handle_admin),
+                )
+                .run(),
+        )
+        .await
+        .unwrap();
+        assert!(result.is_err());
+    }
+}
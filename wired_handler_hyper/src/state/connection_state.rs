@@ -1,6 +1,6 @@
 use wired_handler::{
-    async_double_rwlock::AsyncDoubleRwLockState, State, StateAsyncGet, StateAsyncGetCloned,
-    StateAsyncGetMut, StateAsyncGetMutOrInsert, StateAsyncInsert, StateAsyncRemoveGetCloned,
+    State, StateAsyncGet, StateAsyncGetCloned, StateAsyncGetMut, StateAsyncGetMutOrInsert,
+    StateAsyncInsert, StateAsyncRemoveGetCloned, async_double_rwlock::AsyncDoubleRwLockState,
 };
 
 /// Holds data persistent over a connection
@@ -1,19 +1,173 @@
+use std::{
+    collections::HashMap,
+    ops::{Deref, DerefMut},
+    sync::Arc,
+};
+
 use wired_handler::{
-    async_double_rwlock::AsyncDoubleRwLockState, State, StateAsyncGet, StateAsyncGetCloned,
-    StateAsyncGetMut, StateAsyncGetMutOrInsert, StateAsyncInsert, StateAsyncRemoveGetCloned,
+    State, StateAsyncGet, StateAsyncGetCloned, StateAsyncGetMut, StateAsyncGetMutOrInsert,
+    StateAsyncInsert, StateAsyncRemoveGetCloned,
+    async_double_rwlock::{AsyncDoubleRwLockState, WeakAsyncDoubleRwLockState},
 };
 
-/// Should only exist once
-#[derive(
-    Debug,
-    Default,
-    Clone,
-    State,
-    StateAsyncGet,
-    StateAsyncGetCloned,
-    StateAsyncGetMut,
-    StateAsyncInsert,
-    StateAsyncRemoveGetCloned,
-    StateAsyncGetMutOrInsert,
-)]
-pub struct GlobalState(AsyncDoubleRwLockState);
+/// The namespaces of a `GlobalState`, only holding their own data to not keep their parents alive
+#[derive(Debug, Default)]
+struct Namespaces(HashMap<String, AsyncDoubleRwLockState>);
+
+/// Should only exist once. Sub-applications can get their own view of it with `namespace`
+#[derive(Debug, Default, Clone, State)]
+pub struct GlobalState {
+    state: AsyncDoubleRwLockState,
+    /// The states of the parent namespaces, the nearest first
+    parents: Arc<[AsyncDoubleRwLockState]>,
+}
+
+impl GlobalState {
+    /// Creates a `WeakGlobalState`, which doesn't keep the `GlobalState` alive
+    pub fn downgrade(&self) -> WeakGlobalState {
+        WeakGlobalState {
+            state: self.state.downgrade(),
+            parents: self
+                .parents
+                .iter()
+                .map(AsyncDoubleRwLockState::downgrade)
+                .collect(),
+        }
+    }
+
+    /// Returns the namespace `name`, a view of the `GlobalState` for a sub-application.
+    ///
+    /// Data of the `GlobalState` is visible in the namespace unless the namespace has its own.
+    /// Data inserted into the namespace is only visible there.
+    /// Mutable access only reaches the namespace's own data, use `root` to change shared data.
+    ///
+    /// Data managed by the framework (sessions, websocket connections and the session sweeper)
+    /// always lives in the `root`, so it is shared by all namespaces
+    pub async fn namespace(&self, name: &str) -> GlobalState {
+        let existing_state = self
+            .state
+            .get::<Namespaces>()
+            .await
+            .and_then(|namespaces| namespaces.0.get(name).cloned());
+        // only creating a namespace needs a write lock, as it happens for every mounted request
+        let state = match existing_state {
+            Some(state) => state,
+            None => self
+                .state
+                .get_mut_or_insert_default::<Namespaces>()
+                .await
+                .0
+                .entry(name.to_string())
+                .or_default()
+                .clone(),
+        };
+
+        GlobalState {
+            state,
+            parents: std::iter::once(self.state.clone())
+                .chain(self.parents.iter().cloned())
+                .collect(),
+        }
+    }
+
+    /// Returns the `GlobalState` all namespaces belong to
+    pub fn root(&self) -> GlobalState {
+        match self.parents.last() {
+            Some(root) => GlobalState {
+                state: root.clone(),
+                parents: Arc::new([]),
+            },
+            None => self.clone(),
+        }
+    }
+
+    /// Returns the states to look up data in, the own one first
+    fn layers(&self) -> impl Iterator<Item = &AsyncDoubleRwLockState> {
+        std::iter::once(&self.state).chain(self.parents.iter())
+    }
+}
+
+impl StateAsyncGet for GlobalState {
+    async fn get<T: 'static + Send + Sync>(&self) -> Option<impl Deref<Target = T>> {
+        for layer in self.layers() {
+            if let Some(data) = layer.get::<T>().await {
+                return Some(data);
+            }
+        }
+
+        None
+    }
+
+    async fn exists<T: 'static + Send + Sync>(&self) -> bool {
+        for layer in self.layers() {
+            if layer.exists::<T>().await {
+                return true;
+            }
+        }
+
+        false
+    }
+}
+
+impl StateAsyncGetMut for GlobalState {
+    /// Only gets data of the own namespace
+    async fn get_mut<T: 'static + Send + Sync>(&self) -> Option<impl DerefMut<Target = T>> {
+        self.state.get_mut::<T>().await
+    }
+}
+
+impl StateAsyncGetCloned for GlobalState {
+    async fn get_cloned<T: 'static + Send + Sync + Clone>(&self) -> Option<T> {
+        self.get::<T>().await.map(|data| data.clone())
+    }
+}
+
+impl StateAsyncInsert for GlobalState {
+    /// Inserts into the own namespace
+    async fn insert<T: 'static + Send + Sync>(&self, data: T) {
+        self.state.insert(data).await
+    }
+
+    /// Removes from the own namespace
+    async fn remove<T: 'static + Send + Sync>(&self) {
+        self.state.remove::<T>().await
+    }
+}
+
+impl StateAsyncRemoveGetCloned for GlobalState {
+    /// Removes from the own namespace
+    async fn remove_get_cloned<T: 'static + Send + Sync + Clone>(&self) -> Option<T> {
+        self.state.remove_get_cloned().await
+    }
+}
+
+impl StateAsyncGetMutOrInsert for GlobalState {
+    /// Only gets or inserts data of the own namespace
+    async fn get_mut_or_insert_with<T: 'static + Send + Sync>(
+        &self,
+        get_data: impl FnOnce() -> T + std::marker::Send,
+    ) -> impl DerefMut<Target = T> {
+        self.state.get_mut_or_insert_with(get_data).await
+    }
+}
+
+/// Weak reference to the `GlobalState`, for background tasks that shouldn't outlive it
+#[derive(Debug, Clone, Default)]
+pub struct WeakGlobalState {
+    state: WeakAsyncDoubleRwLockState,
+    parents: Vec<WeakAsyncDoubleRwLockState>,
+}
+
+impl WeakGlobalState {
+    /// Returns the `GlobalState` if it still exists
+    pub fn upgrade(&self) -> Option<GlobalState> {
+        Some(GlobalState {
+            state: self.state.upgrade()?,
+            parents: self
+                .parents
+                .iter()
+                .map(WeakAsyncDoubleRwLockState::upgrade)
+                .collect::<Option<_>>()?,
+        })
+    }
+}
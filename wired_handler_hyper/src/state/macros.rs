@@ -30,7 +30,9 @@ macro_rules! routes {
     };
 }
 
-/// Sugar for definining actions by method. Creates an OPTIONS endpoint as well, so use it as late as possible. Returns an error to the client for non-existing routes
+/// Sugar for definining actions by method. Creates an OPTIONS endpoint as well, so use it as late as possible.
+/// `HEAD` requests are answered by the `GET` arm without a body unless `HEAD` is listed.
+/// Returns 405 Method Not Allowed for other methods, listing the allowed ones in the `Allow` header like the OPTIONS endpoint
 #[macro_export]
 macro_rules! actions {
     {
@@ -38,18 +40,40 @@ macro_rules! actions {
         $($methods:pat => $handler:expr),*$(,)?
     } => {
         {
-            use $crate::prelude::{ContextGetRequestExt, ContextReturnResponseExt};
-            match $ctx.request().method() {
+            use $crate::prelude::ContextReturnResponseExt;
+            use $crate::data::allowed_methods::ContextHeadResponseExt as _;
+
+            #[allow(unreachable_patterns)]
+            let is_listed = |method: &::http::Method| match method {
+                $(
+                    &$methods => true,
+                )*
+                _ => false,
+            };
+            let allowed_methods = $crate::data::allowed_methods::AllowedMethods::new(is_listed);
+            let request_method = $crate::data::allowed_methods::request_method(&$ctx);
+            let head_from_get = request_method == ::http::Method::HEAD
+                && !is_listed(&::http::Method::HEAD)
+                && is_listed(&::http::Method::GET);
+            let method = match head_from_get {
+                true => ::http::Method::GET,
+                false => request_method,
+            };
+
+            let result = match &method {
                 $(
                     &$methods => $handler,
                 )*
-                &::http::Method::OPTIONS => $ctx.next($crate::data::response::Response::builder().body($crate::data::response_body::ResponseBody::empty())?),
+                &::http::Method::OPTIONS => $ctx.next(allowed_methods.options_response()),
 
                 #[allow(unreachable_patterns)]
-                &::http::Method::GET | &::http::Method::HEAD => ::std::result::Result::Err($crate::data::http_error::HttpError::not_found("not found")),
-
-                _ => ::std::result::Result::Err($crate::data::http_error::HttpError::not_implemented("not implemented")),
+                _ => ::std::result::Result::Err(allowed_methods.method_not_allowed()),
+            };
+            if head_from_get {
+                $ctx.strip_response_body();
             }
+
+            result
         }
     };
 }
@@ -89,3 +113,127 @@ mod test_run_handler {
         assert!(test_continue().unwrap().is_break());
     }
 }
+
+/// test `actions!`
+#[cfg(test)]
+mod test_actions {
+    #![allow(clippy::result_large_err)]
+    use std::ops::ControlFlow;
+
+    use http::{Method, StatusCode, header::ALLOW};
+    use http_body_util::BodyExt;
+
+    use crate::{
+        data::{http_error::HttpError, response::Response, response_body::ResponseBody},
+        prelude::*,
+        state::{
+            context::HttpRequestContext, global_state::GlobalState, request_state::RequestState,
+            session_state::SessionState,
+        },
+    };
+
+    fn respond(
+        ctx: &mut HttpRequestContext,
+        body: &'static str,
+    ) -> Result<ControlFlow<()>, HttpError> {
+        ctx.next(Response::builder().body(ResponseBody::from_bytes(body))?)
+    }
+
+    fn read(ctx: &mut HttpRequestContext) -> Result<ControlFlow<()>, HttpError> {
+        actions! {
+            ctx,
+            Method::GET => respond(ctx, "read"),
+        }
+    }
+
+    fn write(ctx: &mut HttpRequestContext) -> Result<ControlFlow<()>, HttpError> {
+        actions! {
+            ctx,
+            Method::PUT | Method::DELETE => respond(ctx, "written"),
+            Method::POST => respond(ctx, "created"),
+        }
+    }
+
+    fn head(ctx: &mut HttpRequestContext) -> Result<ControlFlow<()>, HttpError> {
+        actions! {
+            ctx,
+            Method::GET => respond(ctx, "get"),
+            Method::HEAD => ctx.next(
+                Response::builder()
+                    .header("x-arm", "head")
+                    .body(ResponseBody::empty())?,
+            ),
+        }
+    }
+
+    /// Runs `handler` for a request with `method`, returns the response and its body
+    async fn request(
+        handler: fn(&mut HttpRequestContext) -> Result<ControlFlow<()>, HttpError>,
+        method: Method,
+    ) -> (Response, String) {
+        let mut request_state = RequestState::default();
+        request_state.insert(method);
+        let mut ctx = HttpRequestContext::from_states(
+            GlobalState::default(),
+            SessionState::default(),
+            request_state,
+        );
+
+        let response = match handler(&mut ctx) {
+            Ok(_) => RequestState::get_mut_from_ctx(&mut ctx)
+                .remove_get::<Response>()
+                .unwrap(),
+            Err(err) => err.into(),
+        };
+        let (parts, body) = response.into_parts();
+        let body = body.collect().await.unwrap().to_bytes();
+
+        (
+            Response::from_parts(parts, ResponseBody::empty()),
+            String::from_utf8(body.to_vec()).unwrap(),
+        )
+    }
+
+    fn allow(response: &Response) -> &str {
+        response.headers()[ALLOW].to_str().unwrap()
+    }
+
+    #[test]
+    fn test() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(run_test());
+    }
+
+    async fn run_test() {
+        let (response, body) = request(read, Method::GET).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(body, "read");
+
+        // HEAD is answered by the GET arm without the body
+        let (response, body) = request(read, Method::HEAD).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["content-length"], "4");
+        assert_eq!(body, "");
+
+        let (response, _) = request(read, Method::OPTIONS).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(allow(&response), "GET, HEAD, OPTIONS");
+
+        let (response, _) = request(read, Method::POST).await;
+        assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(allow(&response), "GET, HEAD, OPTIONS");
+
+        // patterns with multiple methods are listed as well
+        let (_, body) = request(write, Method::DELETE).await;
+        assert_eq!(body, "written");
+        let (response, _) = request(write, Method::GET).await;
+        assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(allow(&response), "POST, PUT, DELETE, OPTIONS");
+        let (response, _) = request(write, Method::OPTIONS).await;
+        assert_eq!(allow(&response), "POST, PUT, DELETE, OPTIONS");
+
+        // a listed HEAD arm is used instead of the GET arm
+        let (response, _) = request(head, Method::HEAD).await;
+        assert_eq!(response.headers()["x-arm"], "head");
+    }
+}
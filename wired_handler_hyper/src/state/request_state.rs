@@ -1,6 +1,7 @@
 use wired_handler::{
-    plain::PlainState, State, StateSyncGet, StateSyncGetCloned, StateSyncMutableGetMut,
+    State, StateSyncGet, StateSyncGetCloned, StateSyncMutableGetMut,
     StateSyncMutableGetMutOrInsert, StateSyncMutableInsert, StateSyncMutableRemoveGet,
+    plain::PlainState,
 };
 
 /// Data specific to a request
@@ -0,0 +1,107 @@
+use std::{
+    collections::VecDeque,
+    future::Future,
+    io::{Read, Write},
+    net::SocketAddr,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use hyper::body::{Body, Bytes, Frame};
+use tokio::task::JoinHandle;
+use wired_handler::Handler;
+
+use crate::{
+    RunHttpServerError,
+    data::config::BindConfig,
+    http::run_http_server_on_listener,
+    listener::Listener,
+    state::{
+        context::{HttpRequestContext, SessionlessRequestContext},
+        global_state::GlobalState,
+    },
+};
+
+/// Binds a listener to a free port on localhost, returns it with its address
+pub(crate) async fn bind_local() -> (Listener, SocketAddr) {
+    let tcp_listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = tcp_listener.local_addr().unwrap();
+
+    (tcp_listener.into(), addr)
+}
+
+/// Runs the HTTP server of `handler` on a free port until `shutdown` completes.
+/// Returns its address, which accepts connections right away, and the task running it
+pub(crate) async fn spawn_server<F: Future<Output = HttpRequestContext> + 'static + Send>(
+    handler: Handler<SessionlessRequestContext, HttpRequestContext, GlobalState, F>,
+    bind_config: BindConfig,
+    shutdown: impl Future<Output = ()> + Send + 'static,
+) -> (SocketAddr, JoinHandle<Result<(), RunHttpServerError>>) {
+    let (listener, addr) = bind_local().await;
+    let bind_config = BindConfig {
+        addr: addr.to_string(),
+        ..bind_config
+    };
+    let server = tokio::spawn(run_http_server_on_listener(
+        handler,
+        listener,
+        bind_config,
+        shutdown,
+    ));
+
+    (addr, server)
+}
+
+/// Sends a `GET /` request to `addr` and returns the response, empty if the connection has been closed without one
+pub(crate) fn send_request(addr: SocketAddr) -> std::io::Result<String> {
+    let mut stream = std::net::TcpStream::connect(addr)?;
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+    stream.write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")?;
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response)?;
+    Ok(response)
+}
+
+/// In-memory request body, used in the `RequestState` instead of the body of a `Request<Incoming>`.
+/// Each frame arrives separately, the next one is pending right after a frame has been polled
+#[derive(Debug, Default)]
+pub(crate) struct TestBody {
+    frames: VecDeque<Frame<Bytes>>,
+    is_frame_polled: bool,
+}
+
+impl TestBody {
+    pub(crate) fn from_frames(frames: impl IntoIterator<Item = Frame<Bytes>>) -> Self {
+        Self {
+            frames: frames.into_iter().collect(),
+            is_frame_polled: false,
+        }
+    }
+}
+
+impl From<Bytes> for TestBody {
+    fn from(bytes: Bytes) -> Self {
+        Self::from_frames([Frame::data(bytes)])
+    }
+}
+
+impl Body for TestBody {
+    type Data = Bytes;
+    type Error = hyper::Error;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Bytes>, hyper::Error>>> {
+        if std::mem::take(&mut self.is_frame_polled) {
+            cx.waker().wake_by_ref();
+            return Poll::Pending;
+        }
+
+        let frame = self.frames.pop_front();
+        self.is_frame_polled = frame.is_some();
+        Poll::Ready(frame.map(Ok))
+    }
+}
@@ -0,0 +1,163 @@
+use std::{
+    sync::{Arc, PoisonError, RwLock},
+    time::{Duration, SystemTime},
+};
+
+use thiserror::Error;
+use tokio::task::JoinHandle;
+use tokio_rustls::{
+    TlsAcceptor,
+    rustls::{
+        self, ServerConfig,
+        crypto::CryptoProvider,
+        pki_types::{CertificateDer, PrivateKeyDer, pem::PemObject},
+        server::{ClientHello, ResolvesServerCert},
+        sign::CertifiedKey,
+    },
+};
+use tracing::{info, warn};
+
+use crate::data::config::TlsConfig;
+
+#[derive(Debug, Error)]
+pub enum TlsError {
+    #[error("failed to read certificate or key: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("invalid PEM file: {0}")]
+    Pem(#[from] rustls::pki_types::pem::Error),
+    #[error("{0}")]
+    Rustls(#[from] rustls::Error),
+    #[error("no certificate found in certificate file")]
+    NoCertificates,
+}
+
+/// Resolves the current certificate, which is replaced when the files change
+#[derive(Debug)]
+struct ReloadingCertResolver(RwLock<Arc<CertifiedKey>>);
+
+impl ReloadingCertResolver {
+    fn replace(&self, certified_key: CertifiedKey) {
+        *self.0.write().unwrap_or_else(PoisonError::into_inner) = Arc::new(certified_key);
+    }
+}
+
+impl ResolvesServerCert for ReloadingCertResolver {
+    fn resolve(&self, _client_hello: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        Some(
+            self.0
+                .read()
+                .unwrap_or_else(PoisonError::into_inner)
+                .clone(),
+        )
+    }
+}
+
+/// Handle of the task reloading the certificate. Aborts the task when dropped
+#[derive(Debug)]
+pub(crate) struct CertReloader(JoinHandle<()>);
+
+impl Drop for CertReloader {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// Loads the certificate chain and private key from the files of `tls_config`
+async fn load_certified_key(
+    tls_config: &TlsConfig,
+    crypto_provider: &CryptoProvider,
+) -> Result<CertifiedKey, TlsError> {
+    let cert_pem = tokio::fs::read(&tls_config.cert_path).await?;
+    let key_pem = tokio::fs::read(&tls_config.key_path).await?;
+
+    let cert_chain = CertificateDer::pem_slice_iter(&cert_pem).collect::<Result<Vec<_>, _>>()?;
+    if cert_chain.is_empty() {
+        return Err(TlsError::NoCertificates);
+    }
+    let key = PrivateKeyDer::from_pem_slice(&key_pem)?;
+    let signing_key = crypto_provider.key_provider.load_private_key(key)?;
+
+    Ok(CertifiedKey::new(cert_chain, signing_key))
+}
+
+/// When the certificate and key files have last been modified, `None` if unknown
+async fn modified_at(tls_config: &TlsConfig) -> Option<(SystemTime, SystemTime)> {
+    let cert_modified_at = tokio::fs::metadata(&tls_config.cert_path)
+        .await
+        .and_then(|metadata| metadata.modified())
+        .ok()?;
+    let key_modified_at = tokio::fs::metadata(&tls_config.key_path)
+        .await
+        .and_then(|metadata| metadata.modified())
+        .ok()?;
+
+    Some((cert_modified_at, key_modified_at))
+}
+
+async fn run_cert_reloader(
+    tls_config: TlsConfig,
+    reload_interval: Duration,
+    cert_resolver: Arc<ReloadingCertResolver>,
+    crypto_provider: Arc<CryptoProvider>,
+    mut last_modified_at: Option<(SystemTime, SystemTime)>,
+) {
+    loop {
+        tokio::time::sleep(reload_interval).await;
+
+        let modified_at = modified_at(&tls_config).await;
+        if modified_at.is_none() || modified_at == last_modified_at {
+            continue;
+        }
+
+        // keep the current certificate on errors, the files could be in the middle of being replaced
+        match load_certified_key(&tls_config, &crypto_provider).await {
+            Ok(certified_key) => {
+                cert_resolver.replace(certified_key);
+                last_modified_at = modified_at;
+                info!("reloaded certificate {}", tls_config.cert_path.display());
+            }
+            Err(err) => warn!(
+                "failed to reload certificate {}: {err}",
+                tls_config.cert_path.display()
+            ),
+        }
+    }
+}
+
+/// Protocols offered via ALPN, HTTP/2 is preferred if enabled
+fn alpn_protocols() -> Vec<Vec<u8>> {
+    vec![
+        #[cfg(feature = "http2")]
+        b"h2".to_vec(),
+        b"http/1.1".to_vec(),
+    ]
+}
+
+/// Creates the `TlsAcceptor` for `tls_config`. Also starts the task reloading the certificate if enabled
+pub(crate) async fn create_tls_acceptor(
+    tls_config: &TlsConfig,
+) -> Result<(TlsAcceptor, Option<CertReloader>), TlsError> {
+    let crypto_provider = Arc::new(rustls::crypto::ring::default_provider());
+
+    let modified_at = modified_at(tls_config).await;
+    let certified_key = load_certified_key(tls_config, &crypto_provider).await?;
+    let cert_resolver = Arc::new(ReloadingCertResolver(RwLock::new(Arc::new(certified_key))));
+
+    let mut server_config = ServerConfig::builder_with_provider(crypto_provider.clone())
+        .with_safe_default_protocol_versions()?
+        .with_no_client_auth()
+        .with_cert_resolver(cert_resolver.clone());
+    server_config.alpn_protocols = alpn_protocols();
+
+    let cert_reloader = tls_config.reload_interval().map(|reload_interval| {
+        CertReloader(tokio::spawn(run_cert_reloader(
+            tls_config.clone(),
+            reload_interval,
+            cert_resolver,
+            crypto_provider,
+            modified_at,
+        )))
+    });
+
+    Ok((TlsAcceptor::from(Arc::new(server_config)), cert_reloader))
+}